@@ -7,12 +7,35 @@ CREATE TABLE IF NOT EXISTS networks (
     vlan INTEGER,
     description TEXT,
     father TEXT,
-    FOREIGN KEY (father) REFERENCES networks(id) ON DELETE CASCADE
+    vrf_id TEXT,
+    vlan_id TEXT,
+    FOREIGN KEY (father) REFERENCES networks(id) ON DELETE CASCADE,
+    FOREIGN KEY (vrf_id) REFERENCES vrfs(id) ON DELETE RESTRICT,
+    FOREIGN KEY (vlan_id) REFERENCES vlans(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS vrfs (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    rd TEXT UNIQUE,
+    description TEXT
 );
 
 CREATE TABLE IF NOT EXISTS offices (
-    id TEXT PRIMARY KEY, 
-    address TEXT UNIQUE
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    address TEXT UNIQUE,
+    description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS location (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL CHECK(type IN ('Rack', 'Desk', 'Rack Cabinet')),
+    label TEXT NOT NULL,
+    units INTEGER,
+    office_id TEXT,
+    UNIQUE (label, office_id),
+    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS devices (
@@ -20,20 +43,34 @@ CREATE TABLE IF NOT EXISTS devices (
     description TEXT,
     status TEXT NOT NULL,
     location TEXT,
+    rack_unit INTEGER,
+    rack_height INTEGER,
     network_id TEXT NOT NULL,
     credential BLOB,
+    hostname TEXT,
+    fqdn TEXT,
+    mac TEXT,
+    interface_id TEXT,
+    last_seen INTEGER,
     PRIMARY KEY (ip, network_id),
     FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE,
-    FOREIGN KEY (location) REFERENCES location(id) ON DELETE NO ACTION
+    FOREIGN KEY (location) REFERENCES location(id) ON DELETE SET NULL,
+    FOREIGN KEY (interface_id) REFERENCES interfaces(id) ON DELETE SET NULL
 );
 
-CREATE TABLE IF NOT EXISTS location (
-    id TEXT,
-    type text CHECK(type IN ("Rack", "Desk", "Rack Cabinet")),
-    label TEXT,
-    office_id TEXT,
-    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE CASCADE,
-    PRIMARY KEY (id, label)
+CREATE TABLE IF NOT EXISTS assets (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS interfaces (
+    id TEXT PRIMARY KEY,
+    asset_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    mac TEXT,
+    UNIQUE (asset_id, name),
+    FOREIGN KEY (asset_id) REFERENCES assets(id) ON DELETE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS service (
@@ -44,7 +81,7 @@ CREATE TABLE IF NOT EXISTS service (
     description TEXT,
     PRIMARY KEY (port, ip, network_id),
     FOREIGN KEY (service_id) REFERENCES services (id) ON DELETE NO ACTION,
-    FOREIGN KEY (ip, network_id) REFERENCES devices (ip, network_id) ON DELETE CASCADE
+    FOREIGN KEY (ip, network_id) REFERENCES devices (ip, network_id) ON DELETE CASCADE ON UPDATE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS services (
@@ -59,4 +96,112 @@ CREATE TABLE IF NOT EXISTS users (
     username TEXT UNIQUE,
     password TEXT,
     role TEXT CHECK (role IN ('Admin', 'Operator', 'Guest')) 
-);
\ No newline at end of file
+);
+
+CREATE TABLE IF NOT EXISTS vlans (
+    id TEXT PRIMARY KEY,
+    vid INTEGER NOT NULL CHECK (vid BETWEEN 1 AND 4094),
+    name TEXT NOT NULL,
+    description TEXT,
+    office_id TEXT,
+    vlan_group TEXT,
+    CHECK (office_id IS NULL OR vlan_group IS NULL),
+    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS vlans_vid_scope ON vlans (vid, IFNULL(office_id, ''), IFNULL(vlan_group, ''));
+CREATE UNIQUE INDEX IF NOT EXISTS vlans_name_scope ON vlans (name, IFNULL(office_id, ''), IFNULL(vlan_group, ''));
+
+CREATE TABLE IF NOT EXISTS dhcp_scopes (
+    network_id TEXT PRIMARY KEY,
+    pools TEXT NOT NULL,
+    gateway TEXT,
+    dns_servers TEXT NOT NULL,
+    lease_time INTEGER NOT NULL,
+    options TEXT NOT NULL,
+    subnet_id INTEGER NOT NULL UNIQUE,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS dhcp_reservations (
+    network_id TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    mac TEXT NOT NULL,
+    hostname TEXT,
+    PRIMARY KEY (network_id, mac),
+    UNIQUE (network_id, ip),
+    FOREIGN KEY (ip, network_id) REFERENCES devices (ip, network_id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS dns_domains (
+    network_id TEXT PRIMARY KEY,
+    domain TEXT NOT NULL,
+    ttl INTEGER NOT NULL,
+    nameserver TEXT NOT NULL,
+    hostmaster TEXT NOT NULL,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+
+-- A NULL vrf is the global table, the index treats it as a value so it's unique too
+CREATE TABLE IF NOT EXISTS dns_zones (
+    zone TEXT NOT NULL,
+    vrf TEXT,
+    serial INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    FOREIGN KEY (vrf) REFERENCES vrfs(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS dns_zones_key ON dns_zones (zone, IFNULL(vrf, ''));
+
+CREATE TABLE IF NOT EXISTS dns_records (
+    zone TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    data TEXT NOT NULL,
+    ttl INTEGER NOT NULL,
+    PRIMARY KEY (zone, name, type, data)
+);
+
+CREATE TABLE IF NOT EXISTS dns_updates (
+    id TEXT PRIMARY KEY,
+    action TEXT NOT NULL CHECK (action IN ('Add', 'Delete')),
+    zone TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    data TEXT NOT NULL,
+    ttl INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt INTEGER NOT NULL,
+    error TEXT
+);
+
+CREATE TABLE IF NOT EXISTS thresholds (
+    network_id TEXT PRIMARY KEY,
+    warning INTEGER NOT NULL CHECK (warning BETWEEN 1 AND 100),
+    critical INTEGER NOT NULL CHECK (critical BETWEEN 1 AND 100),
+    level TEXT NOT NULL DEFAULT 'Ok' CHECK (level IN ('Ok', 'Warning', 'Critical')),
+    CHECK (warning <= critical),
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT NOT NULL,
+    description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    webhook_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL CHECK (status IN ('Pending', 'Delivered', 'Failed')),
+    response INTEGER,
+    error TEXT,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
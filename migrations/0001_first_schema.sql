@@ -0,0 +1,63 @@
+-- The tables of the first schema, initdb.sql creates the tables that came after them
+ALTER TABLE networks ADD COLUMN vrf_id TEXT REFERENCES vrfs(id) ON DELETE RESTRICT;
+
+-- The name of an office is required, the address was the only thing that told them apart
+CREATE TABLE offices_new (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    address TEXT UNIQUE,
+    description TEXT
+);
+INSERT INTO offices_new (id, name, address)
+    SELECT id, COALESCE(address, LOWER(HEX(id))), address FROM offices;
+DROP TABLE offices;
+ALTER TABLE offices_new RENAME TO offices;
+
+-- The key of a location was (id, label), so devices couldn't reference it
+CREATE TABLE location_new (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL CHECK(type IN ('Rack', 'Desk', 'Rack Cabinet')),
+    label TEXT NOT NULL,
+    units INTEGER,
+    office_id TEXT,
+    UNIQUE (label, office_id),
+    FOREIGN KEY (office_id) REFERENCES offices(id) ON DELETE CASCADE
+);
+-- A label repeated in the same office keeps the first location and the id tells the rest apart
+INSERT INTO location_new (id, type, label, office_id)
+    SELECT id, COALESCE(type, 'Rack'),
+        CASE WHEN EXISTS (
+            SELECT 1 FROM location AS first
+            WHERE first.label = location.label AND first.office_id IS location.office_id
+                AND first.rowid < location.rowid
+        ) THEN label || ' (' || id || ')' ELSE COALESCE(label, LOWER(HEX(id))) END,
+        office_id
+    FROM location;
+DROP TABLE location;
+ALTER TABLE location_new RENAME TO location;
+
+CREATE TABLE devices_new (
+    ip TEXT NOT NULL,
+    description TEXT,
+    status TEXT NOT NULL,
+    location TEXT,
+    rack_unit INTEGER,
+    rack_height INTEGER,
+    network_id TEXT NOT NULL,
+    credential BLOB,
+    hostname TEXT,
+    fqdn TEXT,
+    mac TEXT,
+    interface_id TEXT,
+    last_seen INTEGER,
+    PRIMARY KEY (ip, network_id),
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE,
+    FOREIGN KEY (location) REFERENCES location(id) ON DELETE SET NULL,
+    FOREIGN KEY (interface_id) REFERENCES interfaces(id) ON DELETE SET NULL
+);
+INSERT INTO devices_new (ip, description, status, location, network_id, credential)
+    SELECT ip, description, status, (SELECT id FROM location WHERE location.id = devices.location),
+        network_id, credential
+    FROM devices;
+DROP TABLE devices;
+ALTER TABLE devices_new RENAME TO devices;
@@ -0,0 +1,7 @@
+-- The vlan of a network is an entry of the registry, the vid alone is ambiguous between scopes
+ALTER TABLE networks ADD COLUMN vlan_id TEXT REFERENCES vlans(id) ON DELETE SET NULL;
+
+UPDATE networks
+SET vlan_id = (SELECT id FROM vlans WHERE vlans.vid = networks.vlan)
+WHERE vlan IS NOT NULL
+    AND (SELECT COUNT(*) FROM vlans WHERE vlans.vid = networks.vlan) = 1;
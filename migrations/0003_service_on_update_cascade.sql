@@ -0,0 +1,15 @@
+-- A device that changes its ip keeps its services, sqlite can only add the action rebuilding the table
+CREATE TABLE service_new (
+    port INTEGER,
+    ip TEXT,
+    network_id TEXT,
+    service_id TEXT NOT NULL,
+    description TEXT,
+    PRIMARY KEY (port, ip, network_id),
+    FOREIGN KEY (service_id) REFERENCES services (id) ON DELETE NO ACTION,
+    FOREIGN KEY (ip, network_id) REFERENCES devices (ip, network_id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+INSERT INTO service_new (port, ip, network_id, service_id, description)
+    SELECT port, ip, network_id, service_id, description FROM service;
+DROP TABLE service;
+ALTER TABLE service_new RENAME TO service;
@@ -0,0 +1,15 @@
+-- Kea identifies a subnet by a number, it's stored so it's unique and doesn't change while the scope exists
+CREATE TABLE dhcp_scopes_new (
+    network_id TEXT PRIMARY KEY,
+    pools TEXT NOT NULL,
+    gateway TEXT,
+    dns_servers TEXT NOT NULL,
+    lease_time INTEGER NOT NULL,
+    options TEXT NOT NULL,
+    subnet_id INTEGER NOT NULL UNIQUE,
+    FOREIGN KEY (network_id) REFERENCES networks(id) ON DELETE CASCADE
+);
+INSERT INTO dhcp_scopes_new (network_id, pools, gateway, dns_servers, lease_time, options, subnet_id)
+    SELECT network_id, pools, gateway, dns_servers, lease_time, options, ROW_NUMBER() OVER (ORDER BY rowid) FROM dhcp_scopes;
+DROP TABLE dhcp_scopes;
+ALTER TABLE dhcp_scopes_new RENAME TO dhcp_scopes;
@@ -0,0 +1,13 @@
+-- The reverse zones of every vrf have the same names, the serials are kept per vrf. The zones
+-- published before keep their serial as the ones of the global table
+CREATE TABLE dns_zones_new (
+    zone TEXT NOT NULL,
+    vrf TEXT,
+    serial INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    FOREIGN KEY (vrf) REFERENCES vrfs(id) ON DELETE CASCADE
+);
+INSERT INTO dns_zones_new (zone, vrf, serial, hash) SELECT zone, NULL, serial, hash FROM dns_zones;
+DROP TABLE dns_zones;
+ALTER TABLE dns_zones_new RENAME TO dns_zones;
+CREATE UNIQUE INDEX dns_zones_key ON dns_zones (zone, IFNULL(vrf, ''));
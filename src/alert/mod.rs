@@ -0,0 +1,185 @@
+mod notifier;
+
+use crate::database::{repository::Repository, SqliteRepository};
+use crate::models::{
+    alert::{Alert, Threshold},
+    network::Network,
+};
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::{collections::HashMap, env, sync::Arc, time::Duration};
+use tokio::sync::{Mutex, Notify};
+
+const DEFAULT_INTERVAL: u64 = 300;
+
+/// Evaluates the thresholds every `ALERT_INTERVAL` seconds and every time the returned
+/// `Notify` is notified, sending the level changes to the notifiers of `ALERT_NOTIFIERS`
+pub fn spawn(db: Arc<Mutex<SqliteRepository>>) -> Arc<Notify> {
+    let notify = Arc::new(Notify::new());
+    let notifiers = notifier::from_env();
+    let period = env::var("ALERT_INTERVAL")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INTERVAL)
+        .max(1);
+
+    let changed = notify.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(period));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = changed.notified() => {}
+            }
+
+            let alerts = {
+                let state = db.lock().await;
+                evaluate(&state).await
+            };
+
+            for alert in alerts {
+                for notifier in &notifiers {
+                    if let Err(e) = notifier.notify(&alert).await {
+                        tracing::error!("Alert of the network {}: {}", alert.network.network, e);
+                    }
+                }
+            }
+        }
+    });
+
+    notify
+}
+
+/// Updates the level of every threshold and returns the ones that have changed
+pub async fn evaluate(state: &SqliteRepository) -> Vec<Alert> {
+    let Ok(thresholds) = state.get::<Threshold>(None).await else {
+        return Vec::new();
+    };
+
+    let mut alerts = Vec::new();
+    for threshold in thresholds {
+        let Ok(mut network) = state
+            .get::<Network>(Some(HashMap::from([("id", threshold.network_id.into())])))
+            .await
+        else {
+            continue;
+        };
+        let network = network.remove(0);
+
+        let utilization = if *network.available == 0 {
+            0.0
+        } else {
+            *network.used as f64 * 100.0 / *network.available as f64
+        };
+        let level = threshold.level(utilization);
+
+        if level == threshold.level {
+            continue;
+        }
+
+        if let Err(e) = state
+            .update::<Threshold, _>(
+                level,
+                Some(HashMap::from([("network_id", threshold.network_id.into())])),
+            )
+            .await
+        {
+            tracing::error!("Threshold of the network {}: {}", network.network, e);
+            continue;
+        }
+
+        alerts.push(Alert {
+            network,
+            level,
+            previous: threshold.level,
+            utilization,
+            warning: threshold.warning,
+            critical: threshold.critical,
+        });
+    }
+
+    alerts
+}
+
+pub async fn on_change(State(notify): State<Arc<Notify>>, req: Request, next: Next) -> Response {
+    let change = !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let resp = next.run(req).await;
+
+    if change && resp.status().is_success() {
+        notify.notify_one();
+    }
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::fixture::*, models::alert::AlertLevel};
+    use uuid::Uuid;
+
+    async fn set_used(state: &SqliteRepository, id: Uuid, used: u32) {
+        let updater = crate::models::network::UpdateNetworkCount {
+            used: Some(used.into()),
+            free: Some((200 - used).into()),
+            available: None,
+        };
+        state
+            .update::<Network, _>(updater, Some(HashMap::from([("id", id.into())])))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn evaluate_only_reports_the_changes_of_level() {
+        let state = repository().await;
+        let network = Network {
+            available: 200.into(),
+            used: 100.into(),
+            free: 100.into(),
+            ..network("10.0.0.0/24")
+        };
+        let id = network.id;
+        state.insert(vec![network]).await.unwrap();
+        state
+            .insert(vec![Threshold {
+                network_id: id,
+                warning: 60,
+                critical: 90,
+                level: AlertLevel::Ok,
+            }])
+            .await
+            .unwrap();
+
+        // 50% is under the warning
+        assert!(evaluate(&state).await.is_empty());
+
+        set_used(&state, id, 130).await;
+        let alerts = evaluate(&state).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level, AlertLevel::Warning);
+        assert_eq!(alerts[0].previous, AlertLevel::Ok);
+        assert_eq!(alerts[0].utilization, 65.0);
+
+        // The level is stored, the same utilization doesn't alert again
+        assert!(evaluate(&state).await.is_empty());
+        set_used(&state, id, 140).await;
+        assert!(evaluate(&state).await.is_empty());
+
+        set_used(&state, id, 180).await;
+        let alerts = evaluate(&state).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level, AlertLevel::Critical);
+        assert_eq!(alerts[0].previous, AlertLevel::Warning);
+
+        set_used(&state, id, 10).await;
+        let alerts = evaluate(&state).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level, AlertLevel::Ok);
+        assert_eq!(alerts[0].previous, AlertLevel::Critical);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::models::alert::{Alert, AlertLevel};
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::{env, future::Future, pin::Pin};
+
+pub type ResultNotifier<'a> = Pin<Box<dyn Future<Output = Result<(), NotifierError>> + 'a + Send>>;
+
+pub trait Notifier: Send + Sync {
+    fn notify<'a>(&'a self, alert: &'a Alert) -> ResultNotifier<'a>;
+}
+
+#[derive(Debug)]
+pub enum NotifierError {
+    Webhook(String),
+    Smtp(String),
+}
+
+impl std::fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Webhook(e) => write!(f, "Webhook error: {}", e),
+            Self::Smtp(e) => write!(f, "Smtp error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NotifierError {}
+
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify<'a>(&'a self, alert: &'a Alert) -> ResultNotifier<'a> {
+        Box::pin(async move {
+            match alert.level {
+                AlertLevel::Ok => tracing::info!("{}", alert),
+                AlertLevel::Warning => tracing::warn!("{}", alert),
+                AlertLevel::Critical => tracing::error!("{}", alert),
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, alert: &'a Alert) -> ResultNotifier<'a> {
+        Box::pin(async move {
+            self.client
+                .post(&self.url)
+                .json(alert)
+                .send()
+                .await
+                .and_then(|x| x.error_for_status())
+                .map(|_| ())
+                .map_err(|e| NotifierError::Webhook(e.to_string()))
+        })
+    }
+}
+
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpNotifier {
+    pub fn new(
+        host: &str,
+        port: u16,
+        tls: bool,
+        credentials: Option<(String, String)>,
+        from: &str,
+        to: &[&str],
+    ) -> Result<Self, NotifierError> {
+        let mut builder = if tls {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| NotifierError::Smtp(e.to_string()))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        }
+        .port(port);
+
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let mailbox = |x: &str| {
+            x.trim()
+                .parse::<Mailbox>()
+                .map_err(|e| NotifierError::Smtp(format!("{}: {}", x, e)))
+        };
+
+        Ok(Self {
+            transport: builder.build(),
+            from: mailbox(from)?,
+            to: to.iter().map(|x| mailbox(x)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn notify<'a>(&'a self, alert: &'a Alert) -> ResultNotifier<'a> {
+        Box::pin(async move {
+            let mut message = Message::builder().from(self.from.clone()).subject(format!(
+                "[{:?}] Network {}",
+                alert.level, alert.network.network
+            ));
+            for to in &self.to {
+                message = message.to(to.clone());
+            }
+
+            let message = message
+                .header(ContentType::TEXT_PLAIN)
+                .body(alert.to_string())
+                .map_err(|e| NotifierError::Smtp(e.to_string()))?;
+
+            self.transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| NotifierError::Smtp(e.to_string()))
+        })
+    }
+}
+
+pub fn from_env() -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    for name in env::var("ALERT_NOTIFIERS")
+        .unwrap_or("log".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+    {
+        match name {
+            "log" => notifiers.push(Box::new(LogNotifier)),
+            "webhook" => match env::var("ALERT_WEBHOOK_URL") {
+                Ok(url) => notifiers.push(Box::new(WebhookNotifier::new(url))),
+                Err(_) => tracing::error!("The webhook notifier needs ALERT_WEBHOOK_URL"),
+            },
+            "smtp" => {
+                let (Ok(host), Ok(from), Ok(to)) = (
+                    env::var("SMTP_HOST"),
+                    env::var("ALERT_MAIL_FROM"),
+                    env::var("ALERT_MAIL_TO"),
+                ) else {
+                    tracing::error!(
+                        "The smtp notifier needs SMTP_HOST, ALERT_MAIL_FROM and ALERT_MAIL_TO"
+                    );
+                    continue;
+                };
+                let port = env::var("SMTP_PORT")
+                    .ok()
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(25);
+                let tls = env::var("SMTP_TLS").is_ok_and(|x| x == "true");
+                let credentials = env::var("SMTP_USERNAME")
+                    .ok()
+                    .zip(env::var("SMTP_PASSWORD").ok());
+                let to = to.split(',').collect::<Vec<_>>();
+
+                match SmtpNotifier::new(&host, port, tls, credentials, &from, &to) {
+                    Ok(e) => notifiers.push(Box::new(e)),
+                    Err(e) => tracing::error!("{}", e),
+                }
+            }
+            e => tracing::error!("Unknown notifier {}", e),
+        }
+    }
+
+    notifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::network::Network;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    fn alert() -> Alert {
+        Alert {
+            network: Network {
+                id: uuid::Uuid::new_v4(),
+                father: None,
+                vrf: None,
+                vlan: None,
+                vlan_id: None,
+                network: "10.1.0.0/24".parse().unwrap(),
+                description: None,
+                available: 200.into(),
+                used: 130.into(),
+                free: 70.into(),
+            },
+            level: AlertLevel::Warning,
+            previous: AlertLevel::Ok,
+            utilization: 65.0,
+            warning: 60,
+            critical: 90,
+        }
+    }
+
+    /// Answers a single http request with `status` and returns the body of the request
+    async fn http_server(status: u16) -> (String, tokio::task::JoinHandle<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+
+            let mut body = vec![0; length];
+            stream.read_exact(&mut body).await.unwrap();
+            stream
+                .get_mut()
+                .write_all(format!("HTTP/1.1 {} X\r\ncontent-length: 0\r\n\r\n", status).as_bytes())
+                .await
+                .unwrap();
+
+            serde_json::from_slice(&body).unwrap()
+        });
+
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_the_alert() {
+        let (url, server) = http_server(200).await;
+
+        WebhookNotifier::new(url).notify(&alert()).await.unwrap();
+
+        let body = server.await.unwrap();
+        assert_eq!(body["level"], "Warning");
+        assert_eq!(body["previous"], "Ok");
+        assert_eq!(body["utilization"], 65.0);
+        assert_eq!(body["network"]["network"], "10.1.0.0/24");
+        assert_eq!(body["network"]["used"], 130);
+    }
+
+    #[tokio::test]
+    async fn webhook_fails_on_error_status() {
+        let (url, server) = http_server(500).await;
+
+        let resp = WebhookNotifier::new(url).notify(&alert()).await;
+
+        assert!(matches!(resp, Err(NotifierError::Webhook(_))));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn smtp_sends_the_alert() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Just enough of RFC 5321 for a single message, returns the commands and the data
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            stream.get_mut().write_all(b"220 test\r\n").await.unwrap();
+
+            let mut commands = Vec::new();
+            let mut data = String::new();
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let command = line.trim_end().to_string();
+                let reply: &[u8] = match command.split(' ').next().unwrap() {
+                    "DATA" => {
+                        stream.get_mut().write_all(b"354 go on\r\n").await.unwrap();
+                        loop {
+                            let mut line = String::new();
+                            stream.read_line(&mut line).await.unwrap();
+                            if line == ".\r\n" {
+                                break;
+                            }
+                            data.push_str(&line);
+                        }
+                        b"250 queued\r\n"
+                    }
+                    "QUIT" => {
+                        stream.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                        commands.push(command);
+                        break;
+                    }
+                    _ => b"250 ok\r\n",
+                };
+                commands.push(command);
+                stream.get_mut().write_all(reply).await.unwrap();
+            }
+
+            (commands, data)
+        });
+
+        let notifier = SmtpNotifier::new(
+            "127.0.0.1",
+            port,
+            false,
+            None,
+            "ipam@example.com",
+            &["ops@example.com", "noc@example.com"],
+        )
+        .unwrap();
+        notifier.notify(&alert()).await.unwrap();
+        drop(notifier);
+
+        let (commands, data) = server.await.unwrap();
+        assert!(commands.contains(&"MAIL FROM:<ipam@example.com>".to_string()));
+        assert!(commands.contains(&"RCPT TO:<ops@example.com>".to_string()));
+        assert!(commands.contains(&"RCPT TO:<noc@example.com>".to_string()));
+        assert!(data.contains("Subject: [Warning] Network 10.1.0.0/24"));
+        assert!(data.contains("The network 10.1.0.0/24 is at 65.0% of utilization"));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::database::SqliteRepository;
+use crate::models::backup::Backup;
+use std::{error::Error, fs};
+
+/// Commands that run instead of the server:
+/// `backup [file] [--exclude-credentials] [--exclude-passwords]` writes the backup to the
+/// file or the standard output, `restore <file> [--replace] [--dry-run]` loads it
+pub async fn run(db: &SqliteRepository, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let flag = |name: &str| args.iter().any(|x| x == name);
+    let file = args.iter().skip(1).find(|x| !x.starts_with("--"));
+
+    match args.first().map(String::as_str) {
+        Some("backup") => {
+            let mut backup = db.backup().await.map_err(|x| format!("{:?}", x))?;
+            if flag("--exclude-credentials") {
+                backup = backup.without_credentials();
+            }
+            if flag("--exclude-passwords") {
+                backup = backup.without_passwords();
+            }
+
+            let json = serde_json::to_string_pretty(&backup)?;
+            match file {
+                Some(file) => fs::write(file, json)?,
+                None => println!("{}", json),
+            }
+            Ok(())
+        }
+        Some("restore") => {
+            let file = file.ok_or("The file of the backup is needed")?;
+            let backup: Backup = serde_json::from_str(&fs::read_to_string(file)?)?;
+            let report = db
+                .restore(backup, flag("--replace"), flag("--dry-run"))
+                .await
+                .map_err(|x| format!("{:?}", x))?;
+
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if report.errors.is_empty() {
+                Ok(())
+            } else {
+                Err(format!("The backup has {} errors", report.errors.len()).into())
+            }
+        }
+        _ => Err("The commands are backup and restore".into()),
+    }
+}
@@ -0,0 +1,304 @@
+use super::{bind, Repository, RepositoryError, SqliteRepository, Table};
+use crate::models::{
+    backup::*,
+    dhcp::{next_subnet_id, DhcpScope},
+    user::User,
+    webhook::Webhook,
+};
+use sqlx::{Sqlite, Transaction};
+
+/// The tables in the order they are restored, they are emptied in the inverse one
+const TABLES: [&str; 19] = [
+    "vrfs",
+    "offices",
+    "location",
+    "vlans",
+    "networks",
+    "assets",
+    "interfaces",
+    "devices",
+    "services",
+    "service",
+    "users",
+    "thresholds",
+    "dhcp_scopes",
+    "dhcp_reservations",
+    "dns_domains",
+    "dns_zones",
+    "dns_records",
+    "dns_updates",
+    "webhooks",
+];
+
+impl SqliteRepository {
+    pub async fn backup(&self) -> Result<Backup, RepositoryError> {
+        Ok(Backup {
+            version: VERSION,
+            created_at: time::OffsetDateTime::now_utc().unix_timestamp(),
+            credentials_excluded: false,
+            vrfs: self.get(None).await?,
+            offices: self.get(None).await?,
+            location: self.get(None).await?,
+            vlans: self.get(None).await?,
+            networks: self.get(None).await?,
+            assets: self.get(None).await?,
+            interfaces: self.get(None).await?,
+            devices: self.get(None).await?,
+            services: self.get(None).await?,
+            service: self.get(None).await?,
+            users: self
+                .get::<User>(None)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            thresholds: self.get(None).await?,
+            dhcp_scopes: self.get(None).await?,
+            dhcp_reservations: self.get(None).await?,
+            dns_domains: self.get(None).await?,
+            dns_zones: self.get(None).await?,
+            dns_records: self.get(None).await?,
+            dns_updates: self.get(None).await?,
+            webhooks: self
+                .get::<Webhook>(None)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+
+    /// Loads the backup in a single transaction. With `replace` the database is emptied
+    /// first, otherwise the rows of the backup are added to the ones of the database and
+    /// replace the ones with the same key. The users without a password keep the one they
+    /// have in the database, if they aren't there they are skipped. The devices of a backup
+    /// without credentials keep the ones they have too
+    pub async fn restore(
+        &self,
+        backup: Backup,
+        replace: bool,
+        dry_run: bool,
+    ) -> Result<RestoreReport, RepositoryError> {
+        let current = self.backup().await?;
+        let mut report = RestoreReport {
+            version: backup.version,
+            replace,
+            dry_run,
+            tables: backup.tables(),
+            errors: backup.check((!replace).then_some(&current)),
+            ..Default::default()
+        };
+
+        let mut users = Vec::new();
+        for user in &backup.users {
+            let password = user.password.clone().or_else(|| {
+                current
+                    .users
+                    .iter()
+                    .find(|x| x.id == user.id)
+                    .and_then(|x| x.password.clone())
+            });
+            match password {
+                Some(password) => users.push(User {
+                    id: user.id,
+                    username: user.username.clone(),
+                    password,
+                    role: user.role.clone(),
+                }),
+                None => report.skipped.push(user.username.clone()),
+            }
+        }
+
+        if !report.errors.is_empty() || dry_run {
+            return Ok(report);
+        }
+
+        let mut tx = self.begin().await?;
+        // The rows of a table can reference each other, like the networks
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+
+        if replace {
+            for table in TABLES.iter().rev() {
+                sqlx::query(&format!("DELETE FROM {}", table))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        upsert(&mut tx, backup.vrfs, &["id"]).await?;
+        upsert(&mut tx, backup.offices, &["id"]).await?;
+        upsert(&mut tx, backup.location, &["id"]).await?;
+        upsert(&mut tx, backup.vlans, &["id"]).await?;
+        upsert(&mut tx, backup.networks, &["id"]).await?;
+        upsert(&mut tx, backup.assets, &["id"]).await?;
+        upsert(&mut tx, backup.interfaces, &["id"]).await?;
+        let keep: &[&str] = if backup.credentials_excluded {
+            &["credential"]
+        } else {
+            &[]
+        };
+        upsert_keeping(&mut tx, backup.devices, &["ip", "network_id"], keep).await?;
+        upsert(&mut tx, backup.services, &["id"]).await?;
+        upsert(&mut tx, backup.service, &["port", "ip", "network_id"]).await?;
+        upsert(&mut tx, users, &["id"]).await?;
+        upsert(&mut tx, backup.thresholds, &["network_id"]).await?;
+        upsert(
+            &mut tx,
+            subnet_ids(backup.dhcp_scopes, &current, replace),
+            &["network_id"],
+        )
+        .await?;
+        upsert(&mut tx, backup.dhcp_reservations, &["network_id", "mac"]).await?;
+        upsert(&mut tx, backup.dns_domains, &["network_id"]).await?;
+        upsert(&mut tx, backup.dns_zones, &["zone", "IFNULL(vrf, '')"]).await?;
+        upsert(
+            &mut tx,
+            backup.dns_records,
+            &["zone", "name", "type", "data"],
+        )
+        .await?;
+        upsert(&mut tx, backup.dns_updates, &["id"]).await?;
+        upsert(
+            &mut tx,
+            backup
+                .webhooks
+                .into_iter()
+                .map(Webhook::from)
+                .collect::<Vec<_>>(),
+            &["id"],
+        )
+        .await?;
+
+        tx.commit().await?;
+        report.committed = true;
+
+        // A backup without admins must not lock everybody out
+        self.create_default_user().await?;
+
+        Ok(report)
+    }
+}
+
+/// The scopes of the backups made before the subnets had an id keep the one they have in
+/// the database, or get a new one
+fn subnet_ids(mut scopes: Vec<DhcpScope>, current: &Backup, replace: bool) -> Vec<DhcpScope> {
+    let mut known = scopes.clone();
+    if !replace {
+        known.extend(current.dhcp_scopes.iter().cloned());
+    }
+
+    for scope in scopes.iter_mut().filter(|x| x.subnet_id == 0) {
+        scope.subnet_id = known
+            .iter()
+            .find(|x| x.network_id == scope.network_id && x.subnet_id != 0)
+            .map(|x| x.subnet_id)
+            .unwrap_or_else(|| next_subnet_id(&known));
+        known.push(scope.clone());
+    }
+
+    scopes
+}
+
+async fn upsert<T: Table>(
+    tx: &mut Transaction<'static, Sqlite>,
+    rows: Vec<T>,
+    key: &[&str],
+) -> Result<(), RepositoryError> {
+    upsert_keeping(tx, rows, key, &[]).await
+}
+
+/// Like `upsert`, but the rows that exist keep the values of the columns in `keep`
+async fn upsert_keeping<T: Table>(
+    tx: &mut Transaction<'static, Sqlite>,
+    rows: Vec<T>,
+    key: &[&str],
+    keep: &[&str],
+) -> Result<(), RepositoryError> {
+    let update = T::columns()
+        .into_iter()
+        .filter(|x| !key.contains(x) && !keep.contains(x))
+        .map(|x| format!("{0} = excluded.{0}", x))
+        .collect::<Vec<_>>();
+    let query = format!(
+        "{} ON CONFLICT ({}) DO {}",
+        T::query_insert(),
+        key.join(", "),
+        if update.is_empty() {
+            "NOTHING".to_string()
+        } else {
+            format!("UPDATE SET {}", update.join(", "))
+        }
+    );
+    tracing::debug!("{}", query);
+
+    for row in rows {
+        let mut upsert = sqlx::query(&query);
+        for i in T::get_fields(row) {
+            upsert = bind(upsert, i);
+        }
+        upsert.execute(&mut **tx).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::fixture::*,
+        models::device::{Credential, Device, Status},
+    };
+
+    #[tokio::test]
+    async fn restore_keeps_the_credentials_left_out_of_the_backup() {
+        let state = repository().await;
+        let network = network("10.0.0.0/24");
+        let id = network.id;
+        state.insert(vec![network]).await.unwrap();
+        state
+            .insert(vec![Device {
+                credential: Some(Credential {
+                    username: "admin".to_string(),
+                    password: "secret".to_string(),
+                }),
+                ..device("10.0.0.1", id, Status::Unknown)
+            }])
+            .await
+            .unwrap();
+
+        let backup = state.backup().await.unwrap().without_credentials();
+        assert!(backup.credentials_excluded);
+        let report = state.restore(backup, false, false).await.unwrap();
+        assert!(report.committed);
+
+        let device = state.get::<Device>(None).await.unwrap().remove(0);
+        assert_eq!(
+            device.credential.map(|x| (x.username, x.password)),
+            Some(("admin".to_string(), "secret".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_reports_the_unique_columns_that_clash_with_the_database() {
+        let state = repository().await;
+        // The rows of the backup replace the ones with the same key
+        let backup = state.backup().await.unwrap();
+        let report = state.restore(backup, false, true).await.unwrap();
+        assert!(report.errors.is_empty());
+
+        let mut backup = state.backup().await.unwrap();
+        let mut user = backup.users[0].clone();
+        user.id = uuid::Uuid::new_v4();
+        backup.users = vec![user];
+        let report = state.restore(backup, false, false).await.unwrap();
+        assert!(!report.committed);
+        assert_eq!(
+            report.errors,
+            vec!["users admin: the username is repeated".to_string()]
+        );
+        assert_eq!(state.get::<User>(None).await.unwrap().len(), 1);
+    }
+}
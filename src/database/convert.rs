@@ -1,9 +1,17 @@
 use crate::models::{
+    alert::Threshold,
+    asset::{Asset, Interface},
     device::*,
+    dhcp::{DhcpReservation, DhcpScope},
+    dns::{DnsDomain, DnsRecord, DnsUpdate, DnsZone},
+    location::Location,
     network::{Network, Vlan},
     office::Office,
     service::{Service, Services},
     user::*,
+    vlan::VlanEntry,
+    vrf::Vrf,
+    webhook::{Delivery, Webhook},
 };
 use libipam::type_net::port::Port;
 use sqlx::{sqlite::SqliteRow, Row};
@@ -14,11 +22,20 @@ impl From<SqliteRow> for Device {
             ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
             description: value.get("description"),
             location: value.get("location"),
+            rack_unit: value.get("rack_unit"),
+            rack_height: value.get("rack_height"),
             credential: value
                 .get::<'_, Option<Vec<u8>>, _>("credential")
                 .map(|x| bincode::deserialize::<'_, Credential>(&x).unwrap()),
             status: value.get("status"),
             network_id: value.get("network_id"),
+            hostname: value.get("hostname"),
+            fqdn: value.get("fqdn"),
+            mac: value
+                .get::<'_, Option<&str>, _>("mac")
+                .and_then(|x| x.parse().ok()),
+            interface_id: value.get("interface_id"),
+            last_seen: value.get("last_seen"),
         }
     }
 }
@@ -31,9 +48,13 @@ impl From<SqliteRow> for Network {
             network: value.get::<'_, &str, _>("network").parse().unwrap(),
             available: value.get::<'_, u32, _>("available").into(),
             used: value.get::<'_, u32, _>("used").into(),
-            vlan: Some(Vlan::new(value.get::<'_, i32, _>("vlan") as u16).unwrap()),
+            vlan: value
+                .get::<'_, Option<u16>, _>("vlan")
+                .and_then(|x| Vlan::new(x).ok()),
             free: value.get::<'_, u32, _>("free").into(),
             father: value.get("father"),
+            vrf: value.get("vrf_id"),
+            vlan_id: value.get("vlan_id"),
         }
     }
 }
@@ -49,6 +70,195 @@ impl From<SqliteRow> for Office {
     }
 }
 
+impl From<SqliteRow> for Location {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            location_type: value.get("type"),
+            label: value.get("label"),
+            units: value.get("units"),
+            office_id: value.get("office_id"),
+        }
+    }
+}
+
+impl From<SqliteRow> for VlanEntry {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            vid: Vlan::new(value.get("vid")).unwrap(),
+            name: value.get("name"),
+            description: value.get("description"),
+            office_id: value.get("office_id"),
+            vlan_group: value.get("vlan_group"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Vrf {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            name: value.get("name"),
+            rd: value.get("rd"),
+            description: value.get("description"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Asset {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            name: value.get("name"),
+            description: value.get("description"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Interface {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            asset_id: value.get("asset_id"),
+            name: value.get("name"),
+            mac: value
+                .get::<'_, Option<&str>, _>("mac")
+                .and_then(|x| x.parse().ok()),
+        }
+    }
+}
+
+impl From<SqliteRow> for DhcpScope {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            network_id: value.get("network_id"),
+            pools: serde_json::from_str(value.get("pools")).unwrap_or_default(),
+            gateway: value
+                .get::<'_, Option<&str>, _>("gateway")
+                .and_then(|x| x.parse().ok()),
+            dns_servers: value
+                .get::<'_, &str, _>("dns_servers")
+                .split(',')
+                .filter_map(|x| x.parse().ok())
+                .collect(),
+            lease_time: value.get("lease_time"),
+            options: serde_json::from_str(value.get("options")).unwrap_or_default(),
+            subnet_id: value.get("subnet_id"),
+        }
+    }
+}
+
+impl From<SqliteRow> for DhcpReservation {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            network_id: value.get("network_id"),
+            ip: value.get::<'_, &str, _>("ip").parse().unwrap(),
+            mac: value.get::<'_, &str, _>("mac").parse().unwrap(),
+            hostname: value.get("hostname"),
+        }
+    }
+}
+
+impl From<SqliteRow> for DnsDomain {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            network_id: value.get("network_id"),
+            domain: value.get("domain"),
+            ttl: value.get("ttl"),
+            nameserver: value.get("nameserver"),
+            hostmaster: value.get("hostmaster"),
+        }
+    }
+}
+
+impl From<SqliteRow> for DnsZone {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            zone: value.get("zone"),
+            vrf: value.get("vrf"),
+            serial: value.get("serial"),
+            hash: value.get("hash"),
+        }
+    }
+}
+
+impl From<SqliteRow> for DnsRecord {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            zone: value.get("zone"),
+            name: value.get("name"),
+            kind: value.get("type"),
+            data: value.get("data"),
+            ttl: value.get("ttl"),
+        }
+    }
+}
+
+impl From<SqliteRow> for DnsUpdate {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            action: value.get("action"),
+            record: DnsRecord {
+                zone: value.get("zone"),
+                name: value.get("name"),
+                kind: value.get("type"),
+                data: value.get("data"),
+                ttl: value.get("ttl"),
+            },
+            attempts: value.get("attempts"),
+            next_attempt: value.get("next_attempt"),
+            error: value.get("error"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Threshold {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            network_id: value.get("network_id"),
+            warning: value.get("warning"),
+            critical: value.get("critical"),
+            level: value.get("level"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Webhook {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            url: value.get("url"),
+            secret: value.get("secret"),
+            events: value
+                .get::<String, _>("events")
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+            description: value.get("description"),
+        }
+    }
+}
+
+impl From<SqliteRow> for Delivery {
+    fn from(value: SqliteRow) -> Self {
+        Self {
+            id: value.get("id"),
+            webhook_id: value.get("webhook_id"),
+            event_id: value.get("event_id"),
+            event: value.get("event"),
+            payload: value.get("payload"),
+            attempts: value.get("attempts"),
+            status: value.get("status"),
+            response: value.get("response"),
+            error: value.get("error"),
+            created_at: value.get("created_at"),
+        }
+    }
+}
+
 impl From<SqliteRow> for User {
     fn from(value: SqliteRow) -> Self {
         Self {
@@ -0,0 +1,93 @@
+use super::SqliteRepository;
+use crate::{
+    database::repository::Repository,
+    models::{
+        device::{Device, Status},
+        network::Network,
+        user::Role,
+    },
+    services::Claims,
+};
+use ipnet::IpNet;
+use libipam::type_net::host_count::{HostCount, Prefix};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// A new database in memory, it's gone when the repository is dropped
+pub async fn repository() -> SqliteRepository {
+    SqliteRepository::memory().await.unwrap()
+}
+
+/// The repository shared by the handlers
+pub async fn state() -> Arc<Mutex<SqliteRepository>> {
+    Arc::new(Mutex::new(repository().await))
+}
+
+pub fn admin() -> Claims {
+    Claims {
+        exp: usize::MAX,
+        sub: Uuid::new_v4(),
+        role: Role::Admin,
+        username: "admin".to_string(),
+    }
+}
+
+/// The hosts of the network, without its network and broadcast addresses
+pub fn hosts(network: &str) -> u32 {
+    *HostCount::new(Prefix::from(&network.parse::<IpNet>().unwrap()))
+}
+
+/// A root network with every address free
+pub fn network(network: &str) -> Network {
+    let hosts = hosts(network);
+    Network {
+        id: Uuid::new_v4(),
+        father: None,
+        vrf: None,
+        vlan: None,
+        vlan_id: None,
+        network: network.parse().unwrap(),
+        description: None,
+        available: hosts.into(),
+        used: 0.into(),
+        free: hosts.into(),
+    }
+}
+
+pub fn device(ip: &str, network_id: Uuid, status: Status) -> Device {
+    Device {
+        ip: ip.parse().unwrap(),
+        description: None,
+        location: None,
+        rack_unit: None,
+        rack_height: None,
+        status,
+        network_id,
+        credential: None,
+        hostname: None,
+        fqdn: None,
+        mac: None,
+        interface_id: None,
+        last_seen: None,
+    }
+}
+
+pub async fn get_network(state: &Arc<Mutex<SqliteRepository>>, network: &str) -> Network {
+    state
+        .lock()
+        .await
+        .get::<Network>(Some(HashMap::from([(
+            "network",
+            network.parse::<IpNet>().unwrap().into(),
+        )])))
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+/// (available, used, free) of the network
+pub async fn counters(state: &Arc<Mutex<SqliteRepository>>, network: &str) -> (u32, u32, u32) {
+    let network = get_network(state, network).await;
+    (*network.available, *network.used, *network.free)
+}
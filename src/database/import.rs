@@ -0,0 +1,97 @@
+use super::{bind, RepositoryError, SqliteRepository, Table};
+use crate::models::{device::Device, network::Network, office::Office, vlan::VlanEntry, vrf::Vrf};
+use sqlx::{Sqlite, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Rows to create at once, the devices that exist are updated in place (their services are
+/// kept) and `counters` are the (available, used) addresses each network gains or loses
+#[derive(Debug, Default)]
+pub struct Import {
+    pub vrfs: Vec<Vrf>,
+    pub offices: Vec<Office>,
+    pub vlans: Vec<VlanEntry>,
+    pub networks: Vec<Network>,
+    pub devices: Vec<Device>,
+    pub counters: HashMap<Uuid, (i64, i64)>,
+}
+
+impl Import {
+    /// Adds `available` and `used` to the network `id` and every ancestor of it in `networks`,
+    /// like a reservation or a moved network do
+    pub fn count(&mut self, networks: &[Network], mut id: Option<Uuid>, available: i64, used: i64) {
+        while let Some(network) = id.and_then(|x| networks.iter().find(|y| y.id == x)) {
+            let counter = self.counters.entry(network.id).or_default();
+            counter.0 += available;
+            counter.1 += used;
+            id = network.father;
+        }
+    }
+}
+
+impl SqliteRepository {
+    pub async fn import(&self, import: Import) -> Result<(), RepositoryError> {
+        let Import {
+            vrfs,
+            offices,
+            vlans,
+            networks,
+            devices,
+            counters,
+        } = import;
+        let mut tx = self.begin().await?;
+
+        insert(&mut tx, vrfs).await?;
+        insert(&mut tx, offices).await?;
+        insert(&mut tx, vlans).await?;
+        insert(&mut tx, networks).await?;
+
+        let query = format!(
+            "{} ON CONFLICT (ip, network_id) DO UPDATE SET {}",
+            Device::query_insert(),
+            Device::columns()
+                .into_iter()
+                .filter(|x| !matches!(*x, "ip" | "network_id"))
+                .map(|x| format!("{0} = excluded.{0}", x))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        tracing::debug!("{}", query);
+        for device in devices {
+            let mut upsert = sqlx::query(&query);
+            for i in Device::get_fields(device) {
+                upsert = bind(upsert, i);
+            }
+            upsert.execute(&mut *tx).await?;
+        }
+
+        for (id, (available, used)) in counters.into_iter().filter(|(_, x)| *x != (0, 0)) {
+            sqlx::query(
+                "UPDATE networks SET available = MAX(available + $1, 0), used = MAX(used + $2, 0), free = MAX(free + $1 - $2, 0) WHERE id = $3",
+            )
+            .bind(available)
+            .bind(used)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+}
+
+async fn insert<T: Table>(
+    tx: &mut Transaction<'static, Sqlite>,
+    rows: Vec<T>,
+) -> Result<(), RepositoryError> {
+    let query = T::query_insert();
+    for row in rows {
+        let mut insert = sqlx::query(&query);
+        for i in T::get_fields(row) {
+            insert = bind(insert, i);
+        }
+        insert.execute(&mut **tx).await?;
+    }
+
+    Ok(())
+}
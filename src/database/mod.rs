@@ -1,10 +1,23 @@
+mod backup;
 pub mod convert;
+#[cfg(test)]
+pub mod fixture;
+mod import;
+mod network;
 pub mod repository;
 
+pub use import::Import;
+
 use crate::models::utils::*;
 use futures::stream::StreamExt;
 use repository::{error::RepositoryError, QueryResult, Repository, ResultRepository};
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqliteRow};
+use sqlx::{
+    query::Query,
+    sqlite::{
+        Sqlite, SqliteArguments, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqliteRow,
+    },
+    Connection,
+};
 use std::{
     collections::HashMap,
     fmt::Debug,
@@ -12,6 +25,16 @@ use std::{
     str::FromStr,
 };
 
+/// The changes to the schema of the databases created before them, `initdb.sql` already has
+/// all of them. `PRAGMA user_version` is the count of the ones a database has
+const MIGRATIONS: &[&str] = &[
+    include_str!("../../migrations/0001_first_schema.sql"),
+    include_str!("../../migrations/0002_network_vlan_id.sql"),
+    include_str!("../../migrations/0003_service_on_update_cascade.sql"),
+    include_str!("../../migrations/0004_dhcp_subnet_id.sql"),
+    include_str!("../../migrations/0005_dns_zone_vrf.sql"),
+];
+
 #[derive(Debug)]
 pub struct SqliteRepository(SqlitePool);
 
@@ -26,30 +49,100 @@ impl SqliteRepository {
             create_tables = true;
         }
 
-        let db = Self({
+        let pool = {
             let tmp = SqliteConnectOptions::from_str(url)?
                 .journal_mode(SqliteJournalMode::Wal)
                 .read_only(false);
             SqlitePool::connect_with(tmp).await?
-        });
+        };
 
-        if create_tables {
-            db.init_db().await?;
+        Self::open(pool, create_tables).await
+    }
+
+    /// A new database in memory, for the tests. Every connection to `:memory:` has its own
+    /// database, so the pool keeps its only connection open as long as it lives
+    #[cfg(test)]
+    pub async fn memory() -> Result<Self, RepositoryError> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?)
+            .await?;
+
+        Self::open(pool, true).await
+    }
+
+    async fn open(pool: SqlitePool, new: bool) -> Result<Self, RepositoryError> {
+        let db = Self(pool);
+
+        db.init_db(new).await?;
+        if new {
             db.create_default_user().await?;
         }
+        db.migrate().await?;
 
         Ok(db)
     }
 
-    async fn init_db(&self) -> Result<(), RepositoryError> {
+    /// Every statement of `initdb.sql` is `IF NOT EXISTS`, so it also adds the tables newer than
+    /// an existing database
+    async fn init_db(&self, new: bool) -> Result<(), RepositoryError> {
         let query = include_str!("../../initdb.sql");
         sqlx::query(query)
             .execute(&self.0)
             .await
             .expect("Database error");
+        if new {
+            sqlx::query(&format!("PRAGMA user_version = {}", MIGRATIONS.len()))
+                .execute(&self.0)
+                .await?;
+        }
         Ok(())
     }
 
+    async fn migrate(&self) -> Result<(), RepositoryError> {
+        let mut conn = self.acquire().await?;
+        let version = sqlx::query_scalar::<_, i64>("PRAGMA user_version")
+            .fetch_one(&mut *conn)
+            .await? as usize;
+        if version >= MIGRATIONS.len() {
+            return Ok(());
+        }
+
+        // Rebuilding a table drops it, so the foreign keys are checked once at the end
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+        let resp = async {
+            let mut tx = conn.begin().await?;
+            for (n, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+                tracing::info!("Migration {} of the database", n + 1);
+                sqlx::query(migration).execute(&mut *tx).await?;
+                sqlx::query(&format!("PRAGMA user_version = {}", n + 1))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            if sqlx::query("PRAGMA foreign_key_check")
+                .fetch_optional(&mut *tx)
+                .await?
+                .is_some()
+            {
+                return Err(RepositoryError::Sqlx(
+                    "The migration breaks a foreign key".to_string(),
+                ));
+            }
+            Ok(tx.commit().await?)
+        }
+        .await;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await?;
+
+        resp
+    }
+
     async fn create_default_user(&self) -> Result<(), RepositoryError> {
         use crate::models::user::*;
         use libipam::authentication::encrypt;
@@ -75,6 +168,80 @@ impl SqliteRepository {
             Err(e) => Err(e),
         }
     }
+
+    pub async fn search<T>(&self, columns: &[&str], text: &str) -> Result<Vec<T>, RepositoryError>
+    where
+        T: Table + From<SqliteRow> + Send + Debug,
+    {
+        let cols = T::columns();
+        if let Some(col) = columns.iter().find(|x| !cols.contains(x)) {
+            return Err(RepositoryError::ColumnNotFound(Some(col.to_string())));
+        }
+
+        let condition = columns
+            .iter()
+            .map(|x| format!("{} LIKE $1 ESCAPE '\\'", x))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let query = format!("SELECT * FROM {} WHERE {}", T::name(), condition);
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        tracing::debug!("{} - {}", query, pattern);
+
+        let mut vec_resp = Vec::new();
+        let mut fetch = sqlx::query(&query).bind(pattern).fetch(&self.0);
+        while let Some(Ok(row)) = fetch.next().await {
+            vec_resp.push(T::from(row));
+        }
+
+        Ok(vec_resp)
+    }
+
+    /// The rows whose `column` is any of `values` in a single query, no rows isn't an error
+    pub async fn get_in<T>(
+        &self,
+        column: &str,
+        values: Vec<TypeTable>,
+    ) -> Result<Vec<T>, RepositoryError>
+    where
+        T: Table + From<SqliteRow> + Send + Debug,
+    {
+        if !T::columns().contains(&column) {
+            return Err(RepositoryError::ColumnNotFound(Some(column.to_string())));
+        }
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let params = (1..=values.len())
+            .map(|x| format!("${}", x))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!(
+            "SELECT * FROM {} WHERE {} IN ({})",
+            T::name(),
+            column,
+            params
+        );
+        tracing::debug!("{}", query);
+
+        let mut select = sqlx::query(&query);
+        for value in values {
+            select = bind(select, value);
+        }
+
+        let mut vec_resp = Vec::new();
+        let mut fetch = select.fetch(&self.0);
+        while let Some(row) = fetch.next().await {
+            vec_resp.push(T::from(row?));
+        }
+
+        Ok(vec_resp)
+    }
 }
 
 impl Repository for SqliteRepository {
@@ -96,19 +263,7 @@ impl Repository for SqliteRepository {
                 let mut tmp = sqlx::query(&query);
                 let data = T::get_fields(data);
                 for i in data {
-                    tmp = match i {
-                        TypeTable::String(s) => tmp.bind(s),
-                        TypeTable::OptionString(opt) => tmp.bind(opt),
-                        TypeTable::OptionU16(e) => tmp.bind(e),
-                        TypeTable::Status(status) => tmp.bind(status),
-                        TypeTable::U32(i) => tmp.bind(i),
-                        TypeTable::Uuid(e) => tmp.bind(e),
-                        TypeTable::Role(r) => tmp.bind(r),
-                        TypeTable::OptionUuid(e) => tmp.bind(e),
-                        TypeTable::BytesOption(e) => tmp.bind(e),
-                        TypeTable::Null => tmp,
-                        TypeTable::U16(e) => tmp.bind(e),
-                    };
+                    tmp = bind(tmp, i);
                 }
 
                 match tmp.execute(&mut *tx).await {
@@ -152,19 +307,18 @@ impl Repository for SqliteRepository {
                     let mut data_pos = HashMap::new();
 
                     let mut pos = 1;
-                    let len = col.len();
-                    for i in col.keys() {
-                        if !cols.contains(i) {
+                    for (n, (i, value)) in col.into_iter().enumerate() {
+                        if !cols.contains(&i) {
                             return Err(RepositoryError::ColumnNotFound(Some(i.to_string())));
                         }
-                        if col.get(i).unwrap() == &TypeTable::Null {
+                        if n > 0 {
+                            query.push_str(" AND");
+                        }
+                        if value.is_null() {
                             query.push_str(&format!(" {} IS NULL", i));
                         } else {
                             query.push_str(&format!(" {} = ${}", i, pos));
-                            if pos < len {
-                                query.push_str(" AND");
-                            }
-                            data_pos.insert(pos, col.get(i).unwrap());
+                            data_pos.insert(pos, value);
                             pos += 1;
                         }
                     }
@@ -173,19 +327,7 @@ impl Repository for SqliteRepository {
                     let mut resp = sqlx::query(&query);
 
                     for i in 1..pos {
-                        resp = match data_pos.get(&i).unwrap() {
-                            TypeTable::OptionUuid(e) => resp.bind(e),
-                            TypeTable::OptionU16(e) => resp.bind(e),
-                            TypeTable::Uuid(e) => resp.bind(e),
-                            TypeTable::String(s) => resp.bind(s),
-                            TypeTable::OptionString(opt) => resp.bind(opt),
-                            TypeTable::Status(status) => resp.bind(status),
-                            TypeTable::U32(num) => resp.bind(num),
-                            TypeTable::Role(role) => resp.bind(role),
-                            TypeTable::BytesOption(e) => resp.bind(e),
-                            TypeTable::Null => resp,
-                            TypeTable::U16(e) => resp.bind(e),
-                        };
+                        resp = bind(resp, data_pos.remove(&i).unwrap());
                     }
 
                     let mut resp = resp.fetch(&self.0);
@@ -236,13 +378,13 @@ impl Repository for SqliteRepository {
 
                 let mut pos = 1;
                 let len = pair.len();
-                for i in pair.keys() {
-                    if !cols.contains(i) {
+                for (i, value) in pair {
+                    if !cols.contains(&i) {
                         return Err(RepositoryError::ColumnNotFound(Some(i.to_string())));
                     }
 
                     query.push_str(&format!(" {} = ${}", i, pos));
-                    pos_values.insert(pos, pair.get(i).unwrap());
+                    pos_values.insert(pos, value);
                     if len > pos {
                         query.push(',');
                     }
@@ -257,31 +399,22 @@ impl Repository for SqliteRepository {
                     None => HashMap::new(),
                 };
 
-                let len = condition.len() + pos - 1;
-                for i in condition.keys() {
-                    pos_values.insert(pos, condition.get(i).unwrap());
-                    query.push_str(&format!(" {} = ${}", i, pos));
-                    if pos < len {
+                for (n, (i, value)) in condition.into_iter().enumerate() {
+                    if n > 0 {
                         query.push_str(" AND");
                     }
-                    pos += 1;
+                    if value.is_null() {
+                        query.push_str(&format!(" {} IS NULL", i));
+                    } else {
+                        pos_values.insert(pos, value);
+                        query.push_str(&format!(" {} = ${}", i, pos));
+                        pos += 1;
+                    }
                 }
 
                 let mut sql = sqlx::query(&query);
                 for i in 1..pos {
-                    sql = match pos_values.get(&i).unwrap() {
-                        TypeTable::String(s) => sql.bind(s),
-                        TypeTable::OptionString(value) => sql.bind(value),
-                        TypeTable::Status(value) => sql.bind(value),
-                        TypeTable::Uuid(e) => sql.bind(e),
-                        TypeTable::Role(value) => sql.bind(value),
-                        TypeTable::OptionUuid(e) => sql.bind(e),
-                        TypeTable::OptionU16(e) => sql.bind(e),
-                        TypeTable::BytesOption(e) => sql.bind(e),
-                        TypeTable::U32(e) => sql.bind(e),
-                        TypeTable::Null => sql,
-                        TypeTable::U16(e) => sql.bind(e),
-                    };
+                    sql = bind(sql, pos_values.remove(&i).unwrap());
                 }
 
                 match sql.execute(&self.0).await {
@@ -315,13 +448,13 @@ impl Repository for SqliteRepository {
                     let mut pos = 1;
 
                     let len = condition.len();
-                    for t in condition.keys() {
-                        if !columns.contains(t) {
+                    for (t, value) in condition {
+                        if !columns.contains(&t) {
                             return Err(RepositoryError::ColumnNotFound(Some(t.to_string())));
                         }
 
                         query.push_str(&format!(" {} = ${}", t, pos));
-                        pos_column.insert(pos, condition.get(t).unwrap());
+                        pos_column.insert(pos, value);
                         if pos < len {
                             query.push_str(" AND");
                         }
@@ -331,19 +464,7 @@ impl Repository for SqliteRepository {
                     let mut ex = sqlx::query(&query);
 
                     for i in 1..pos {
-                        ex = match pos_column.get(&i).unwrap() {
-                            TypeTable::OptionUuid(e) => ex.bind(e),
-                            TypeTable::String(s) => ex.bind(s),
-                            TypeTable::OptionString(s) => ex.bind(s),
-                            TypeTable::Uuid(e) => ex.bind(e),
-                            TypeTable::Status(status) => ex.bind(status),
-                            TypeTable::BytesOption(e) => ex.bind(e),
-                            TypeTable::Role(role) => ex.bind(role),
-                            TypeTable::OptionU16(e) => ex.bind(e),
-                            TypeTable::U32(i) => ex.bind(i),
-                            TypeTable::Null => ex,
-                            TypeTable::U16(e) => ex.bind(e),
-                        };
+                        ex = bind(ex, pos_column.remove(&i).unwrap());
                     }
 
                     match ex.execute(&self.0).await {
@@ -364,6 +485,31 @@ impl Repository for SqliteRepository {
     }
 }
 
+fn bind<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: TypeTable,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        TypeTable::String(s) => query.bind(s),
+        TypeTable::OptionString(opt) => query.bind(opt),
+        TypeTable::OptionU16(e) => query.bind(e),
+        TypeTable::Status(status) => query.bind(status),
+        TypeTable::U32(i) => query.bind(i),
+        TypeTable::Uuid(e) => query.bind(e),
+        TypeTable::Role(r) => query.bind(r),
+        TypeTable::LocationType(e) => query.bind(e),
+        TypeTable::AlertLevel(e) => query.bind(e),
+        TypeTable::DeliveryStatus(e) => query.bind(e),
+        TypeTable::DnsAction(e) => query.bind(e),
+        TypeTable::I64(e) => query.bind(e),
+        TypeTable::OptionI64(e) => query.bind(e),
+        TypeTable::OptionUuid(e) => query.bind(e),
+        TypeTable::BytesOption(e) => query.bind(e),
+        TypeTable::Null => query,
+        TypeTable::U16(e) => query.bind(e),
+    }
+}
+
 impl std::fmt::Display for RepositoryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,201 @@
+use super::{bind, RepositoryError, SqliteRepository, StreamExt, Table};
+use crate::models::{device::Device, dhcp::DhcpScope, network::Network};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+impl SqliteRepository {
+    pub async fn get_subtree(&self, id: Uuid) -> Result<Vec<Network>, RepositoryError> {
+        let query = "WITH RECURSIVE tree(id) AS (
+                SELECT id FROM networks WHERE id = $1
+                UNION ALL
+                SELECT n.id FROM networks n JOIN tree t ON n.father = t.id
+            )
+            SELECT * FROM networks WHERE id IN tree";
+        tracing::debug!("{} - {}", query, id);
+
+        let mut vec_resp = Vec::new();
+        let mut fetch = sqlx::query(query).bind(id).fetch(&self.0);
+        while let Some(Ok(row)) = fetch.next().await {
+            vec_resp.push(row.into());
+        }
+
+        if vec_resp.is_empty() {
+            return Err(RepositoryError::RowNotFound);
+        }
+
+        Ok(vec_resp)
+    }
+
+    /// Replaces the networks `merged` with `network` in a single transaction. Their children,
+    /// devices and services (ON UPDATE CASCADE) are moved to the new network, and every
+    /// ancestor adds its delta to its available and free addresses
+    pub async fn merge_networks(
+        &self,
+        network: Network,
+        merged: &[Uuid],
+        ancestors: &[(Uuid, i64)],
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.begin().await?;
+        let id = network.id;
+
+        let query = Network::query_insert();
+        let mut insert = sqlx::query(&query);
+        for i in Network::get_fields(network) {
+            insert = bind(insert, i);
+        }
+        insert.execute(&mut *tx).await?;
+
+        let ids = placeholders(merged.len(), 2);
+        for query in [
+            format!("UPDATE networks SET father = $1 WHERE father IN ({})", ids),
+            format!(
+                "UPDATE devices SET network_id = $1 WHERE network_id IN ({})",
+                ids
+            ),
+        ] {
+            tracing::debug!("{}", query);
+            let mut tmp = sqlx::query(&query).bind(id);
+            for i in merged {
+                tmp = tmp.bind(i);
+            }
+            tmp.execute(&mut *tx).await?;
+        }
+
+        let query = format!(
+            "DELETE FROM networks WHERE id IN ({})",
+            placeholders(merged.len(), 1)
+        );
+        tracing::debug!("{}", query);
+        let mut delete = sqlx::query(&query);
+        for i in merged {
+            delete = delete.bind(i);
+        }
+        delete.execute(&mut *tx).await?;
+
+        for (id, delta) in ancestors {
+            sqlx::query(
+                "UPDATE networks SET available = MAX(available + $1, 0), free = MAX(free + $1, 0) WHERE id = $2",
+            )
+            .bind(delta)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    /// Changes the prefix, description and vlan of the network and the ip of the devices in
+    /// `devices` (old, new) in a single transaction, the services follow their device (ON UPDATE
+    /// CASCADE). The placeholders in `removed` are replaced by `created`, every ancestor adds
+    /// its delta to its available and free addresses and `scope` replaces the dhcp pools and
+    /// gateway of the network
+    pub async fn resize_network(
+        &self,
+        network: &Network,
+        devices: &[(IpAddr, IpAddr)],
+        removed: &[IpAddr],
+        created: Vec<Device>,
+        ancestors: &[(Uuid, i64)],
+        scope: Option<&DhcpScope>,
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query(
+            "UPDATE networks SET network = $1, available = $2, free = $3, description = $4, vlan = $5, vlan_id = $6 WHERE id = $7",
+        )
+        .bind(network.network.to_string())
+        .bind(*network.available)
+        .bind(*network.free)
+        .bind(&network.description)
+        .bind(network.vlan.map(|x| *x))
+        .bind(network.vlan_id)
+        .bind(network.id)
+        .execute(&mut *tx)
+        .await?;
+
+        for ip in removed {
+            sqlx::query("DELETE FROM devices WHERE ip = $1 AND network_id = $2")
+                .bind(ip.to_string())
+                .bind(network.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for (from, to) in devices {
+            sqlx::query("UPDATE devices SET ip = $1 WHERE ip = $2 AND network_id = $3")
+                .bind(to.to_string())
+                .bind(from.to_string())
+                .bind(network.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let query = Device::query_insert();
+        for dev in created {
+            let mut insert = sqlx::query(&query);
+            for i in Device::get_fields(dev) {
+                insert = bind(insert, i);
+            }
+            insert.execute(&mut *tx).await?;
+        }
+
+        for (id, delta) in ancestors {
+            sqlx::query(
+                "UPDATE networks SET available = MAX(available + $1, 0), free = MAX(free + $1, 0) WHERE id = $2",
+            )
+            .bind(delta)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(scope) = scope {
+            sqlx::query("UPDATE dhcp_scopes SET pools = $1, gateway = $2 WHERE network_id = $3")
+                .bind(serde_json::to_string(&scope.pools).unwrap_or_default())
+                .bind(scope.gateway.map(|x| x.to_string()))
+                .bind(network.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    /// Moves the network (and so its whole subtree) to `father`. Every ancestor in `counters`
+    /// adds (available, used) to its counters
+    pub async fn move_network(
+        &self,
+        id: Uuid,
+        father: Option<Uuid>,
+        counters: &[(Uuid, i64, i64)],
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.begin().await?;
+
+        sqlx::query("UPDATE networks SET father = $1 WHERE id = $2")
+            .bind(father)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (id, available, used) in counters {
+            sqlx::query(
+                "UPDATE networks SET available = MAX(available + $1, 0), used = MAX(used + $2, 0), free = MAX(free + $1 - $2, 0) WHERE id = $3",
+            )
+            .bind(available)
+            .bind(used)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+}
+
+fn placeholders(len: usize, start: usize) -> String {
+    (start..start + len)
+        .map(|x| format!("${}", x))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -46,6 +46,17 @@ pub enum QueryResult<T> {
     Select(Vec<T>),
 }
 
+impl<T> QueryResult<T> {
+    /// The rows inserted, updated or deleted, or the ones found by a select
+    pub fn rows_affected(&self) -> u64 {
+        match self {
+            Self::Insert { row_affect, .. } => *row_affect,
+            Self::Update(e) | Self::Delete(e) => *e,
+            Self::Select(e) => e.len() as u64,
+        }
+    }
+}
+
 impl<S> IntoResponse for QueryResult<S>
 where
     S: serde::Serialize,
@@ -0,0 +1,367 @@
+use crate::models::dns::{DnsAction, DnsUpdate};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hickory_proto::{
+    error::ProtoError,
+    op::{update_message, Message, ResponseCode},
+    rr::{
+        dnssec::{rdata::tsig::TsigAlgorithm, tsig::TSigner},
+        rdata::{A, AAAA, PTR},
+        Name, RData, RecordSet,
+    },
+};
+use std::{env, time::Duration};
+use tokio::net::{lookup_host, UdpSocket};
+
+const DEFAULT_PORT: u16 = 53;
+const FUDGE: u16 = 300;
+const TIMEOUT: u64 = 5;
+const MAX_MESSAGE: usize = 4096;
+
+#[derive(Debug)]
+pub enum DdnsError {
+    Record(String),
+    Proto(ProtoError),
+    Io(std::io::Error),
+    Timeout,
+    Response(ResponseCode),
+}
+
+impl std::fmt::Display for DdnsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Record(e) => write!(f, "Invalid record: {}", e),
+            Self::Proto(e) => write!(f, "Dns error: {}", e),
+            Self::Io(e) => write!(f, "Io error: {}", e),
+            Self::Timeout => write!(f, "The dns server didn't answer"),
+            Self::Response(e) => write!(f, "The dns server answered {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DdnsError {}
+
+impl From<ProtoError> for DdnsError {
+    fn from(value: ProtoError) -> Self {
+        Self::Proto(value)
+    }
+}
+
+impl From<std::io::Error> for DdnsError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Sends RFC 2136 updates to the primary server of the zones, signed with TSIG
+pub struct Client {
+    server: String,
+    signer: Option<TSigner>,
+}
+
+impl Client {
+    /// `DDNS_SERVER` enables the dynamic updates, `DDNS_KEY_NAME` and `DDNS_KEY_SECRET`
+    /// (base64) sign them with the algorithm of `DDNS_KEY_ALGORITHM`
+    pub fn from_env() -> Option<Self> {
+        let server = env::var("DDNS_SERVER").ok()?;
+        let server = if server.contains(':') {
+            server
+        } else {
+            format!("{}:{}", server, DEFAULT_PORT)
+        };
+
+        let signer = match (env::var("DDNS_KEY_NAME"), env::var("DDNS_KEY_SECRET")) {
+            (Ok(name), Ok(secret)) => {
+                let algorithm = env::var("DDNS_KEY_ALGORITHM")
+                    .unwrap_or("hmac-sha256".to_string())
+                    .to_lowercase();
+                let algorithm = match algorithm.as_str() {
+                    "hmac-sha256" => TsigAlgorithm::HmacSha256,
+                    "hmac-sha384" => TsigAlgorithm::HmacSha384,
+                    "hmac-sha512" => TsigAlgorithm::HmacSha512,
+                    e => {
+                        tracing::error!("Unsupported tsig algorithm {}", e);
+                        return None;
+                    }
+                };
+
+                let key = match STANDARD.decode(secret.trim()) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        tracing::error!("DDNS_KEY_SECRET isn't valid base64: {}", e);
+                        return None;
+                    }
+                };
+
+                match Name::from_ascii(&name)
+                    .and_then(|name| TSigner::new(key, algorithm, name, FUDGE))
+                {
+                    Ok(e) => Some(e),
+                    Err(e) => {
+                        tracing::error!("Invalid tsig key {}: {}", name, e);
+                        return None;
+                    }
+                }
+            }
+            _ => {
+                tracing::warn!("Dynamic dns updates to {} aren't signed", server);
+                None
+            }
+        };
+
+        Some(Self { server, signer })
+    }
+
+    pub async fn send(&self, update: &DnsUpdate) -> Result<(), DdnsError> {
+        let mut message = message(update)?;
+
+        let verifier = match &self.signer {
+            Some(signer) => message.finalize(
+                signer,
+                time::OffsetDateTime::now_utc().unix_timestamp() as u32,
+            )?,
+            None => None,
+        };
+        let request = message.to_vec()?;
+
+        let server = lookup_host(&self.server)
+            .await?
+            .next()
+            .ok_or_else(|| DdnsError::Record(format!("Can't resolve {}", self.server)))?;
+        let socket = UdpSocket::bind(if server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        })
+        .await?;
+        socket.connect(server).await?;
+        socket.send(&request).await?;
+
+        let mut buffer = [0; MAX_MESSAGE];
+        let len = tokio::time::timeout(Duration::from_secs(TIMEOUT), socket.recv(&mut buffer))
+            .await
+            .map_err(|_| DdnsError::Timeout)??;
+
+        let code = match verifier {
+            Some(mut verify) => verify(&buffer[..len])?.response_code(),
+            None => Message::from_vec(&buffer[..len])?.response_code(),
+        };
+
+        if code == ResponseCode::NoError {
+            Ok(())
+        } else {
+            Err(DdnsError::Response(code))
+        }
+    }
+}
+
+fn message(update: &DnsUpdate) -> Result<Message, DdnsError> {
+    let record = &update.record;
+    let zone = Name::from_ascii(format!("{}.", record.zone))?;
+    let name = Name::from_ascii(format!("{}.", record.name))?;
+
+    if !zone.zone_of(&name) {
+        return Err(DdnsError::Record(format!(
+            "{} isn't inside the zone {}",
+            record.name, record.zone
+        )));
+    }
+
+    let invalid = || DdnsError::Record(format!("{} {} {}", record.name, record.kind, record.data));
+    let rdata = match record.kind.as_str() {
+        "A" => RData::A(A(record.data.parse().map_err(|_| invalid())?)),
+        "AAAA" => RData::AAAA(AAAA(record.data.parse().map_err(|_| invalid())?)),
+        "PTR" => RData::PTR(PTR(Name::from_ascii(&record.data)?)),
+        _ => return Err(invalid()),
+    };
+
+    let mut rrset = RecordSet::with_ttl(name, rdata.record_type(), record.ttl);
+    rrset.add_rdata(rdata);
+
+    Ok(match update.action {
+        DnsAction::Add => update_message::append(rrset, zone, false, false),
+        DnsAction::Delete => update_message::delete_by_rdata(rrset, zone, false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::dns::DnsRecord;
+    use hickory_proto::{
+        op::{MessageType, OpCode},
+        rr::{
+            dnssec::rdata::tsig::{make_tsig_record, message_tbs, TSIG},
+            DNSClass, RecordType,
+        },
+    };
+    use std::net::SocketAddr;
+    use tokio::task::JoinHandle;
+    use uuid::Uuid;
+
+    fn update(action: DnsAction, kind: &str, name: &str, data: &str) -> DnsUpdate {
+        DnsUpdate {
+            id: Uuid::new_v4(),
+            action,
+            record: DnsRecord {
+                zone: "example.com".to_string(),
+                name: name.to_string(),
+                kind: kind.to_string(),
+                data: data.to_string(),
+                ttl: 600,
+            },
+            attempts: 0,
+            next_attempt: 0,
+            error: None,
+        }
+    }
+
+    fn signer() -> TSigner {
+        TSigner::new(
+            b"a secret of the test".to_vec(),
+            TsigAlgorithm::HmacSha256,
+            Name::from_ascii("ipam.").unwrap(),
+            FUDGE,
+        )
+        .unwrap()
+    }
+
+    /// Answers a single update with `code`, verifying and signing like a server with the key
+    async fn server(
+        signer: Option<TSigner>,
+        code: ResponseCode,
+    ) -> (SocketAddr, JoinHandle<Message>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        let handle = tokio::spawn(async move {
+            let mut buffer = [0; MAX_MESSAGE];
+            let (len, from) = socket.recv_from(&mut buffer).await.unwrap();
+            let request = Message::from_vec(&buffer[..len]).unwrap();
+
+            let mut response = Message::error_msg(request.id(), OpCode::Update, code);
+            if let Some(signer) = signer {
+                let (mac, _, time) = signer
+                    .verify_message_byte(None, &buffer[..len], true)
+                    .expect("the update is signed with the key");
+                let tsig = TSIG::new(
+                    signer.algorithm().clone(),
+                    time,
+                    signer.fudge(),
+                    Vec::new(),
+                    request.id(),
+                    0,
+                    Vec::new(),
+                );
+                let tbs = message_tbs(Some(&mac), &response, &tsig, signer.signer_name()).unwrap();
+                let tsig = tsig.set_mac(signer.sign(&tbs).unwrap());
+                response.add_tsig(make_tsig_record(signer.signer_name().clone(), tsig));
+            }
+
+            socket
+                .send_to(&response.to_vec().unwrap(), from)
+                .await
+                .unwrap();
+            request
+        });
+
+        (addr, handle)
+    }
+
+    #[test]
+    fn message_appends_and_deletes_the_record() {
+        let add = message(&update(DnsAction::Add, "A", "host.example.com", "10.0.0.1")).unwrap();
+        assert_eq!(add.op_code(), OpCode::Update);
+        assert_eq!(add.queries()[0].name().to_ascii(), "example.com.");
+        assert_eq!(add.queries()[0].query_type(), RecordType::SOA);
+        let record = &add.name_servers()[0];
+        assert_eq!(record.name().to_ascii(), "host.example.com.");
+        assert_eq!(record.dns_class(), DNSClass::IN);
+        assert_eq!(record.ttl(), 600);
+        assert_eq!(
+            record.data(),
+            Some(&RData::A(A("10.0.0.1".parse().unwrap())))
+        );
+
+        // Deleting by rdata only removes that record of the name
+        let delete = message(&update(
+            DnsAction::Delete,
+            "PTR",
+            "1.0.0.10.in-addr.arpa",
+            "host.example.com.",
+        ));
+        assert!(matches!(delete, Err(DdnsError::Record(_))));
+
+        let mut ptr = update(
+            DnsAction::Delete,
+            "PTR",
+            "1.0.0.10.in-addr.arpa",
+            "host.example.com.",
+        );
+        ptr.record.zone = "0.0.10.in-addr.arpa".to_string();
+        let delete = message(&ptr).unwrap();
+        let record = &delete.name_servers()[0];
+        assert_eq!(record.dns_class(), DNSClass::NONE);
+        assert_eq!(record.ttl(), 0);
+        assert_eq!(record.record_type(), RecordType::PTR);
+
+        let invalid = message(&update(
+            DnsAction::Add,
+            "AAAA",
+            "host.example.com",
+            "10.0.0.1",
+        ));
+        assert!(matches!(invalid, Err(DdnsError::Record(_))));
+        let invalid = message(&update(DnsAction::Add, "TXT", "host.example.com", "text"));
+        assert!(matches!(invalid, Err(DdnsError::Record(_))));
+    }
+
+    #[tokio::test]
+    async fn send_signs_the_update_and_checks_the_answer() {
+        let (addr, handle) = server(Some(signer()), ResponseCode::NoError).await;
+        let client = Client {
+            server: addr.to_string(),
+            signer: Some(signer()),
+        };
+
+        let update = update(DnsAction::Add, "AAAA", "host.example.com", "2001:db8::1");
+        client.send(&update).await.unwrap();
+
+        let request = handle.await.unwrap();
+        assert_eq!(request.message_type(), MessageType::Query);
+        assert_eq!(request.signature().len(), 1);
+        assert_eq!(
+            request.name_servers()[0].data(),
+            Some(&RData::AAAA(AAAA("2001:db8::1".parse().unwrap())))
+        );
+    }
+
+    #[tokio::test]
+    async fn send_fails_when_the_server_refuses() {
+        let (addr, handle) = server(Some(signer()), ResponseCode::Refused).await;
+        let client = Client {
+            server: addr.to_string(),
+            signer: Some(signer()),
+        };
+
+        let update = update(DnsAction::Delete, "A", "host.example.com", "10.0.0.1");
+        let resp = client.send(&update).await;
+        assert!(matches!(
+            resp,
+            Err(DdnsError::Response(ResponseCode::Refused))
+        ));
+        handle.await.unwrap();
+
+        // Without a key the answer is read as is
+        let (addr, handle) = server(None, ResponseCode::Refused).await;
+        let client = Client {
+            server: addr.to_string(),
+            signer: None,
+        };
+        let resp = client.send(&update).await;
+        assert!(matches!(
+            resp,
+            Err(DdnsError::Response(ResponseCode::Refused))
+        ));
+        assert!(handle.await.unwrap().signature().is_empty());
+    }
+}
@@ -0,0 +1,371 @@
+mod client;
+
+use crate::database::{
+    repository::{error::RepositoryError, Repository},
+    SqliteRepository,
+};
+use crate::events::{self, EventKind};
+use crate::models::{
+    device::Device,
+    dns::{self, DnsAction, DnsDomain, DnsRecord, DnsUpdate, Record, UpdateDnsUpdate},
+    network::Network,
+};
+use client::Client;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{
+    broadcast::error::{RecvError, TryRecvError},
+    Mutex,
+};
+use uuid::Uuid;
+
+const DEFAULT_INTERVAL: u64 = 300;
+const DEFAULT_RETRY_DELAY: u64 = 30;
+const MAX_RETRY_DELAY: i64 = 3600;
+const DEBOUNCE: u64 = 1;
+
+type RepositoryType = Arc<Mutex<SqliteRepository>>;
+
+/// Pushes the records of the named devices to `DDNS_SERVER`. After every change of a
+/// device or network, and every `DDNS_INTERVAL` seconds, the wanted records are reconciled
+/// with the pushed ones; the resulting updates are queued and retried with backoff
+pub fn spawn(db: RepositoryType) {
+    let Some(client) = Client::from_env() else {
+        return;
+    };
+    let period = env::var("DDNS_INTERVAL")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INTERVAL)
+        .max(1);
+    let retry = env::var("DDNS_RETRY_DELAY")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_RETRY_DELAY)
+        .max(1);
+
+    let mut rx = events::subscribe();
+    tokio::spawn(async move {
+        let mut reconciliation = tokio::time::interval(Duration::from_secs(period));
+        let mut retries = tokio::time::interval(Duration::from_secs(retry));
+
+        loop {
+            let changed = tokio::select! {
+                _ = reconciliation.tick() => true,
+                _ = retries.tick() => false,
+                event = rx.recv() => match event {
+                    Ok(event) if !affects(event.event) => continue,
+                    Ok(_) | Err(RecvError::Lagged(_)) => {
+                        // A change usually comes with others, like creating every device
+                        tokio::time::sleep(Duration::from_secs(DEBOUNCE)).await;
+                        while matches!(rx.try_recv(), Ok(_) | Err(TryRecvError::Lagged(_))) {}
+                        true
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            if changed {
+                let state = db.lock().await;
+                if let Err(e) = reconcile(&state).await {
+                    tracing::error!("Dns reconciliation: {}", e);
+                }
+            }
+
+            process(&db, &client, retry).await;
+        }
+    });
+}
+
+fn affects(event: EventKind) -> bool {
+    matches!(
+        event,
+        EventKind::DeviceCreated
+            | EventKind::DeviceUpdated
+            | EventKind::DeviceDeleted
+            | EventKind::NetworkCreated
+            | EventKind::NetworkUpdated
+            | EventKind::NetworkDeleted
+    )
+}
+
+/// The forward and reverse records of every device with a name inside a dns domain
+async fn wanted(state: &SqliteRepository) -> Vec<DnsRecord> {
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+    if domains.is_empty() {
+        return Vec::new();
+    }
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    let devices = state.get::<Device>(None).await.unwrap_or_default();
+
+    let mut resp = Vec::new();
+    for device in devices {
+        let Some(domain) = dns::domain_of(&networks, &domains, Some(device.network_id)) else {
+            continue;
+        };
+        let Some(name) = device.dns_name(Some(&domain.domain)) else {
+            continue;
+        };
+
+        if domain.contains(&name) {
+            resp.push(DnsRecord::from_record(
+                &domain.domain,
+                Record::forward(&name, device.ip),
+                domain.ttl,
+            ));
+        }
+
+        let reverse = networks
+            .iter()
+            .find(|x| x.id == domain.network_id)
+            .and_then(|x| {
+                dns::reverse_zones(x.network)
+                    .into_iter()
+                    .find(|x| x.contains(&device.ip))
+            });
+        if let Some(reverse) = reverse {
+            resp.push(DnsRecord::from_record(
+                &dns::reverse_name(reverse),
+                Record::reverse(&name, device.ip),
+                domain.ttl,
+            ));
+        }
+    }
+
+    resp
+}
+
+/// Queues the updates that take the pushed records to the wanted ones, and drops the
+/// queued updates that aren't needed anymore
+pub async fn reconcile(state: &SqliteRepository) -> Result<(), RepositoryError> {
+    let wanted = wanted(state).await;
+    let wanted = wanted
+        .iter()
+        .map(|x| (x.key(), x))
+        .collect::<HashMap<_, _>>();
+
+    let pushed = state.get::<DnsRecord>(None).await.unwrap_or_default();
+    let pushed = pushed
+        .iter()
+        .map(|x| (x.key(), x))
+        .collect::<HashMap<_, _>>();
+
+    let queued = state.get::<DnsUpdate>(None).await.unwrap_or_default();
+    let mut pending = HashSet::new();
+    for update in &queued {
+        let needed = match update.action {
+            DnsAction::Add => wanted
+                .get(&update.record.key())
+                .is_some_and(|x| x.ttl == update.record.ttl),
+            DnsAction::Delete => !wanted.contains_key(&update.record.key()),
+        };
+
+        if needed {
+            pending.insert((update.action, update.record.key()));
+        } else {
+            state
+                .delete::<DnsUpdate>(Some(HashMap::from([("id", update.id.into())])))
+                .await?;
+        }
+    }
+
+    // Adding a record that already exists only updates its ttl
+    let adds = wanted
+        .iter()
+        .filter(|(key, record)| pushed.get(*key).is_none_or(|x| x.ttl != record.ttl))
+        .map(|(_, record)| (DnsAction::Add, *record));
+    let deletes = pushed
+        .iter()
+        .filter(|(key, _)| !wanted.contains_key(*key))
+        .map(|(_, record)| (DnsAction::Delete, *record));
+
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let updates = adds
+        .chain(deletes)
+        .filter(|(action, x)| !pending.contains(&(*action, x.key())))
+        .map(|(action, record)| DnsUpdate {
+            id: Uuid::new_v4(),
+            action,
+            record: record.clone(),
+            attempts: 0,
+            next_attempt: now,
+            error: None,
+        })
+        .collect::<Vec<_>>();
+
+    if !updates.is_empty() {
+        tracing::info!("Queued {} dns updates", updates.len());
+        state.insert::<DnsUpdate>(updates).await?;
+    }
+
+    Ok(())
+}
+
+/// Sends the queued updates that are due, the lock isn't held while waiting for the server
+async fn process(db: &RepositoryType, client: &Client, retry: u64) {
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let mut updates = db
+        .lock()
+        .await
+        .get::<DnsUpdate>(None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|x| x.next_attempt <= now)
+        .collect::<Vec<_>>();
+    updates.sort_by_key(|x| x.next_attempt);
+
+    for update in updates {
+        let result = client.send(&update).await;
+        let state = db.lock().await;
+
+        let result = match result {
+            Ok(()) => published(&state, update).await,
+            Err(e) => {
+                let attempts = update.attempts.saturating_add(1);
+                let delay = (retry as i64)
+                    .saturating_mul(1 << (attempts - 1).min(16))
+                    .min(MAX_RETRY_DELAY);
+                tracing::warn!(
+                    "Dns update {:?} {} {} {} (attempt {}): {}",
+                    update.action,
+                    update.record.name,
+                    update.record.kind,
+                    update.record.data,
+                    attempts,
+                    e
+                );
+
+                state
+                    .update::<DnsUpdate, _>(
+                        UpdateDnsUpdate {
+                            attempts: Some(attempts),
+                            next_attempt: Some(now + delay),
+                            error: Some(e.to_string()),
+                        },
+                        Some(HashMap::from([("id", update.id.into())])),
+                    )
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!("Dns update queue: {}", e);
+        }
+    }
+}
+
+async fn published(state: &SqliteRepository, update: DnsUpdate) -> Result<(), RepositoryError> {
+    let record = update.record;
+    let key = HashMap::from([
+        ("zone", record.zone.clone().into()),
+        ("name", record.name.clone().into()),
+        ("type", record.kind.clone().into()),
+        ("data", record.data.clone().into()),
+    ]);
+
+    let _ = state.delete::<DnsRecord>(Some(key)).await;
+    if update.action == DnsAction::Add {
+        state.insert::<DnsRecord>(vec![record]).await?;
+    }
+
+    state
+        .delete::<DnsUpdate>(Some(HashMap::from([("id", update.id.into())])))
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::fixture::*, models::device::Status};
+
+    async fn queued(state: &SqliteRepository) -> Vec<(DnsAction, String, String, String)> {
+        let mut resp = state
+            .get::<DnsUpdate>(None)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| (x.action, x.record.zone, x.record.kind, x.record.data))
+            .collect::<Vec<_>>();
+        resp.sort_by(|a, b| (&a.1, &a.2).cmp(&(&b.1, &b.2)));
+        resp
+    }
+
+    #[tokio::test]
+    async fn reconcile_queues_the_changes_of_the_records() {
+        let state = repository().await;
+        let network = network("10.0.0.0/24");
+        let id = network.id;
+        state.insert(vec![network]).await.unwrap();
+        state
+            .insert(vec![DnsDomain {
+                network_id: id,
+                domain: "example.com".to_string(),
+                ttl: 600,
+                nameserver: "ns.example.com".to_string(),
+                hostmaster: "hostmaster.example.com".to_string(),
+            }])
+            .await
+            .unwrap();
+        state
+            .insert(vec![Device {
+                hostname: Some("host".to_string()),
+                ..device("10.0.0.1", id, Status::Online)
+            }])
+            .await
+            .unwrap();
+
+        reconcile(&state).await.unwrap();
+        let add = vec![
+            (
+                DnsAction::Add,
+                "0.0.10.in-addr.arpa".to_string(),
+                "PTR".to_string(),
+                "host.example.com.".to_string(),
+            ),
+            (
+                DnsAction::Add,
+                "example.com".to_string(),
+                "A".to_string(),
+                "10.0.0.1".to_string(),
+            ),
+        ];
+        assert_eq!(queued(&state).await, add);
+
+        // The queued updates aren't queued again
+        reconcile(&state).await.unwrap();
+        assert_eq!(queued(&state).await, add);
+
+        for update in state.get::<DnsUpdate>(None).await.unwrap() {
+            published(&state, update).await.unwrap();
+        }
+        assert!(queued(&state).await.is_empty());
+        assert_eq!(state.get::<DnsRecord>(None).await.unwrap().len(), 2);
+
+        // Nothing to do while the pushed records are the wanted ones
+        reconcile(&state).await.unwrap();
+        assert!(queued(&state).await.is_empty());
+
+        // The records of a deleted device are deleted
+        state
+            .delete::<Device>(Some(HashMap::from([
+                ("ip", "10.0.0.1".parse::<std::net::IpAddr>().unwrap().into()),
+                ("network_id", id.into()),
+            ])))
+            .await
+            .unwrap();
+        reconcile(&state).await.unwrap();
+        assert_eq!(
+            queued(&state).await,
+            add.into_iter()
+                .map(|(_, zone, kind, data)| (DnsAction::Delete, zone, kind, data))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+const CAPACITY: usize = 1024;
+
+static BUS: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EventKind {
+    #[serde(rename = "network.created")]
+    NetworkCreated,
+    #[serde(rename = "network.updated")]
+    NetworkUpdated,
+    #[serde(rename = "network.deleted")]
+    NetworkDeleted,
+    #[serde(rename = "device.created")]
+    DeviceCreated,
+    #[serde(rename = "device.updated")]
+    DeviceUpdated,
+    #[serde(rename = "device.deleted")]
+    DeviceDeleted,
+    #[serde(rename = "device.status_changed")]
+    DeviceStatusChanged,
+    #[serde(rename = "service.created")]
+    ServiceCreated,
+    #[serde(rename = "service.updated")]
+    ServiceUpdated,
+    #[serde(rename = "service.deleted")]
+    ServiceDeleted,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 10] = [
+        Self::NetworkCreated,
+        Self::NetworkUpdated,
+        Self::NetworkDeleted,
+        Self::DeviceCreated,
+        Self::DeviceUpdated,
+        Self::DeviceDeleted,
+        Self::DeviceStatusChanged,
+        Self::ServiceCreated,
+        Self::ServiceUpdated,
+        Self::ServiceDeleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NetworkCreated => "network.created",
+            Self::NetworkUpdated => "network.updated",
+            Self::NetworkDeleted => "network.deleted",
+            Self::DeviceCreated => "device.created",
+            Self::DeviceUpdated => "device.updated",
+            Self::DeviceDeleted => "device.deleted",
+            Self::DeviceStatusChanged => "device.status_changed",
+            Self::ServiceCreated => "service.created",
+            Self::ServiceUpdated => "service.updated",
+            Self::ServiceDeleted => "service.deleted",
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub id: Uuid,
+    pub event: EventKind,
+    pub timestamp: i64,
+    pub data: serde_json::Value,
+}
+
+impl Event {
+    /// Whether the event is about the network, its father or any of its devices and services
+    pub fn touches(&self, network: Uuid) -> bool {
+        ["id", "father", "network_id", "netwok_id"]
+            .iter()
+            .any(|key| {
+                self.data
+                    .get(key)
+                    .and_then(|x| x.as_str())
+                    .and_then(|x| x.parse::<Uuid>().ok())
+                    == Some(network)
+            })
+    }
+}
+
+/// The filters are an event name, a group like `device.*` or `*` for every event
+pub fn matches<T: AsRef<str>>(filters: &[T], event: &str) -> bool {
+    filters.iter().map(AsRef::as_ref).any(|x| {
+        x == "*"
+            || x == event
+            || x.strip_suffix(".*")
+                .is_some_and(|x| event.split('.').next() == Some(x))
+    })
+}
+
+fn bus() -> &'static broadcast::Sender<Event> {
+    BUS.get_or_init(|| broadcast::channel(CAPACITY).0)
+}
+
+/// Publishes the event, it's lost if nobody is subscribed
+pub fn emit<T: Serialize>(event: EventKind, data: &T) {
+    let data = match serde_json::to_value(data) {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::error!("Event {}: {}", event, e);
+            return;
+        }
+    };
+
+    let _ = bus().send(Event {
+        id: Uuid::new_v4(),
+        event,
+        timestamp: time::OffsetDateTime::now_utc().unix_timestamp(),
+        data,
+    });
+}
+
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    bus().subscribe()
+}
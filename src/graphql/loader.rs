@@ -0,0 +1,198 @@
+use crate::{
+    database::{repository::error::RepositoryError, SqliteRepository},
+    models::{
+        device::Device,
+        location::Location,
+        network::Network,
+        office::Office,
+        service::{Service, Services},
+        utils::Table,
+        vrf::Vrf,
+    },
+};
+use async_graphql::dataloader::{DataLoader, HashMapCache, Loader};
+use sqlx::sqlite::SqliteRow;
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData, sync::Arc};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// The uuid columns the resolvers look rows up by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    Id,
+    Father,
+    Vrf,
+    Network,
+    Location,
+    Office,
+    Service,
+}
+
+impl Column {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Father => "father",
+            Self::Vrf => "vrf_id",
+            Self::Network => "network_id",
+            Self::Location => "location",
+            Self::Office => "office_id",
+            Self::Service => "service_id",
+        }
+    }
+}
+
+/// A column and one of its values
+pub type Key = (Column, Uuid);
+
+pub trait Related {
+    fn key(&self, column: Column) -> Option<Uuid>;
+}
+
+/// Loads the rows of `T` whose column is one of the keys, a query per column
+pub struct Rows<T> {
+    db: Arc<Mutex<SqliteRepository>>,
+    row: PhantomData<fn() -> T>,
+}
+
+impl<T> Rows<T> {
+    fn new(db: Arc<Mutex<SqliteRepository>>) -> Self {
+        Self {
+            db,
+            row: PhantomData,
+        }
+    }
+}
+
+impl<T> Loader<Key> for Rows<T>
+where
+    T: Table + Related + From<SqliteRow> + Clone + Send + Sync + Debug + 'static,
+{
+    type Value = Vec<T>;
+    type Error = Arc<RepositoryError>;
+
+    async fn load(&self, keys: &[Key]) -> Result<HashMap<Key, Self::Value>, Self::Error> {
+        let mut columns = HashMap::<Column, Vec<Uuid>>::new();
+        for (column, id) in keys {
+            columns.entry(*column).or_default().push(*id);
+        }
+
+        let state = self.db.lock().await;
+        let mut resp = HashMap::<Key, Vec<T>>::new();
+        for (column, ids) in columns {
+            let rows = state
+                .get_in::<T>(column.name(), ids.into_iter().map(Into::into).collect())
+                .await
+                .map_err(Arc::new)?;
+            for row in rows {
+                if let Some(id) = row.key(column) {
+                    resp.entry((column, id)).or_default().push(row);
+                }
+            }
+        }
+
+        Ok(resp)
+    }
+}
+
+/// The loaders of a request, the cache lives as long as the request
+pub struct Loaders {
+    pub db: Arc<Mutex<SqliteRepository>>,
+    pub networks: DataLoader<Rows<Network>, HashMapCache>,
+    pub devices: DataLoader<Rows<Device>, HashMapCache>,
+    pub services: DataLoader<Rows<Service>, HashMapCache>,
+    pub catalog: DataLoader<Rows<Services>, HashMapCache>,
+    pub offices: DataLoader<Rows<Office>, HashMapCache>,
+    pub locations: DataLoader<Rows<Location>, HashMapCache>,
+    pub vrfs: DataLoader<Rows<Vrf>, HashMapCache>,
+}
+
+impl Loaders {
+    pub fn new(db: Arc<Mutex<SqliteRepository>>) -> Self {
+        Self {
+            networks: loader(&db),
+            devices: loader(&db),
+            services: loader(&db),
+            catalog: loader(&db),
+            offices: loader(&db),
+            locations: loader(&db),
+            vrfs: loader(&db),
+            db,
+        }
+    }
+}
+
+fn loader<T>(db: &Arc<Mutex<SqliteRepository>>) -> DataLoader<Rows<T>, HashMapCache>
+where
+    Rows<T>: Loader<Key>,
+{
+    DataLoader::with_cache(Rows::new(db.clone()), tokio::spawn, HashMapCache::default())
+}
+
+impl Related for Network {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Id => Some(self.id),
+            Column::Father => self.father,
+            Column::Vrf => self.vrf,
+            _ => None,
+        }
+    }
+}
+
+impl Related for Device {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Network => Some(self.network_id),
+            Column::Location => self.location,
+            _ => None,
+        }
+    }
+}
+
+impl Related for Service {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Network => Some(self.netwok_id),
+            Column::Service => Some(self.service_id),
+            _ => None,
+        }
+    }
+}
+
+impl Related for Services {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Id => Some(self.id),
+            _ => None,
+        }
+    }
+}
+
+impl Related for Office {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Id => Some(self.id),
+            _ => None,
+        }
+    }
+}
+
+impl Related for Location {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Id => Some(self.id),
+            Column::Office => self.office_id,
+            _ => None,
+        }
+    }
+}
+
+impl Related for Vrf {
+    fn key(&self, column: Column) -> Option<Uuid> {
+        match column {
+            Column::Id => Some(self.id),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+mod loader;
+mod object;
+
+use crate::{
+    database::{
+        repository::{error::RepositoryError, Repository},
+        SqliteRepository,
+    },
+    models::{device, network, office, service, user, utils::*},
+    services::Claims,
+};
+use async_graphql::{
+    BatchRequest, BatchResponse, Context, EmptyMutation, EmptySubscription, Object, Result, Schema,
+};
+use axum::extract::{Extension, Json, State};
+use loader::{Column, Loaders};
+use object::*;
+use sqlx::sqlite::SqliteRow;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    net::IpAddr,
+    sync::{Arc, LazyLock},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Deep enough for network -> children -> devices -> services -> service and back
+const MAX_DEPTH: usize = 12;
+const MAX_COMPLEXITY: usize = 1000;
+
+static SCHEMA: LazyLock<Schema<Query, EmptyMutation, EmptySubscription>> = LazyLock::new(|| {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// A query or a list of queries, every one with its own errors
+pub async fn execute(
+    State(state): State<Arc<Mutex<SqliteRepository>>>,
+    Extension(claim): Extension<Claims>,
+    Json(request): Json<BatchRequest>,
+) -> Json<BatchResponse> {
+    let request = request.data(claim).data(Arc::new(Loaders::new(state)));
+    Json(SCHEMA.execute_batch(request).await)
+}
+
+/// The schema in SDL
+pub async fn sdl() -> String {
+    SCHEMA.sdl()
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// The networks of the vrf or the subnets of the father, only the ones without a father
+    /// with `roots`
+    async fn networks(
+        &self,
+        ctx: &Context<'_>,
+        vrf: Option<Uuid>,
+        father: Option<Uuid>,
+        #[graphql(default)] roots: bool,
+    ) -> Result<Vec<Network>> {
+        let mut filter = HashMap::new();
+        if let Some(vrf) = vrf {
+            filter.insert("vrf_id", vrf.into());
+        }
+        if let Some(father) = father {
+            filter.insert("father", father.into());
+        } else if roots {
+            filter.insert("father", TypeTable::Null);
+        }
+
+        let mut networks = rows::<network::Network>(ctx, filter).await?;
+        networks.sort_by_key(|x| x.network);
+        Ok(networks.into_iter().map(Network).collect())
+    }
+
+    async fn network(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Network>> {
+        Ok(loaders(ctx)
+            .networks
+            .load_one((Column::Id, id))
+            .await?
+            .and_then(|x| x.into_iter().next())
+            .map(Network))
+    }
+
+    /// The devices of the network, every device without it
+    async fn devices(
+        &self,
+        ctx: &Context<'_>,
+        network: Option<Uuid>,
+        status: Option<Status>,
+    ) -> Result<Vec<Device>> {
+        let mut filter = HashMap::new();
+        if let Some(network) = network {
+            filter.insert("network_id", network.into());
+        }
+        if let Some(status) = status {
+            filter.insert("status", device::Status::from(status).into());
+        }
+
+        let mut devices = rows::<device::Device>(ctx, filter).await?;
+        devices.sort_by_key(|x| (x.network_id, x.ip));
+        Ok(devices.into_iter().map(Device).collect())
+    }
+
+    async fn device(&self, ctx: &Context<'_>, network: Uuid, ip: String) -> Result<Option<Device>> {
+        let ip = ip.parse::<IpAddr>()?;
+        Ok(rows::<device::Device>(
+            ctx,
+            HashMap::from([("network_id", network.into()), ("ip", ip.into())]),
+        )
+        .await?
+        .into_iter()
+        .next()
+        .map(Device))
+    }
+
+    /// The catalog of services
+    async fn services(&self, ctx: &Context<'_>) -> Result<Vec<Services>> {
+        let mut services = rows::<service::Services>(ctx, HashMap::new()).await?;
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(services.into_iter().map(Services).collect())
+    }
+
+    async fn offices(&self, ctx: &Context<'_>) -> Result<Vec<Office>> {
+        let mut offices = rows::<office::Office>(ctx, HashMap::new()).await?;
+        offices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(offices.into_iter().map(Office).collect())
+    }
+
+    async fn office(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Office>> {
+        Ok(loaders(ctx)
+            .offices
+            .load_one((Column::Id, id))
+            .await?
+            .and_then(|x| x.into_iter().next())
+            .map(Office))
+    }
+
+    /// Every user for the admins, the user of the token for the rest
+    async fn users(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        let filter = if is_admin(ctx) {
+            HashMap::new()
+        } else {
+            HashMap::from([("id", ctx.data_unchecked::<Claims>().sub.into())])
+        };
+
+        let mut users = rows::<user::User>(ctx, filter).await?;
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users.into_iter().map(User).collect())
+    }
+}
+
+/// The rows that match every column of the filter, no rows isn't an error
+async fn rows<T>(ctx: &Context<'_>, filter: HashMap<&str, TypeTable>) -> Result<Vec<T>>
+where
+    T: Table + From<SqliteRow> + Send + Debug + Clone,
+{
+    let filter = (!filter.is_empty()).then_some(filter);
+    match loaders(ctx).db.lock().await.get::<T>(filter).await {
+        Ok(rows) => Ok(rows),
+        Err(RepositoryError::RowNotFound) => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,389 @@
+use super::loader::{Column, Key, Loaders, Rows};
+use crate::{
+    database::repository::error::RepositoryError,
+    models::{device, location, network, office, service, user, vrf},
+    services::Claims,
+};
+use async_graphql::{
+    dataloader::{DataLoader, HashMapCache, Loader},
+    Context, Enum, Object, Result, SimpleObject,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn loaders<'a>(ctx: &Context<'a>) -> &'a Loaders {
+    ctx.data_unchecked::<Arc<Loaders>>()
+}
+
+pub fn is_admin(ctx: &Context<'_>) -> bool {
+    ctx.data_unchecked::<Claims>().role == user::Role::Admin
+}
+
+/// The first row of the key
+async fn one<T>(loader: &DataLoader<Rows<T>, HashMapCache>, key: Key) -> Result<Option<T>>
+where
+    Rows<T>: Loader<Key, Value = Vec<T>, Error = Arc<RepositoryError>>,
+{
+    Ok(loader
+        .load_one(key)
+        .await?
+        .and_then(|x| x.into_iter().next()))
+}
+
+/// Every row of the key
+async fn many<T>(loader: &DataLoader<Rows<T>, HashMapCache>, key: Key) -> Result<Vec<T>>
+where
+    Rows<T>: Loader<Key, Value = Vec<T>, Error = Arc<RepositoryError>>,
+{
+    Ok(loader.load_one(key).await?.unwrap_or_default())
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "device::Status")]
+pub enum Status {
+    Reserved,
+    Unknown,
+    Online,
+    Offline,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "user::Role")]
+pub enum Role {
+    Admin,
+    Guest,
+    Operator,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "location::LocationType")]
+pub enum LocationType {
+    Rack,
+    Desk,
+    RackCabinet,
+}
+
+pub struct Network(pub network::Network);
+
+#[Object]
+impl Network {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn network(&self) -> String {
+        self.0.network.to_string()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn vlan(&self) -> Option<u16> {
+        self.0.vlan.as_deref().copied()
+    }
+
+    async fn available(&self) -> u32 {
+        *self.0.available
+    }
+
+    async fn used(&self) -> u32 {
+        *self.0.used
+    }
+
+    async fn free(&self) -> u32 {
+        *self.0.free
+    }
+
+    async fn father(&self, ctx: &Context<'_>) -> Result<Option<Network>> {
+        let Some(father) = self.0.father else {
+            return Ok(None);
+        };
+        Ok(one(&loaders(ctx).networks, (Column::Id, father))
+            .await?
+            .map(Network))
+    }
+
+    async fn vrf(&self, ctx: &Context<'_>) -> Result<Option<Vrf>> {
+        let Some(vrf) = self.0.vrf else {
+            return Ok(None);
+        };
+        Ok(one(&loaders(ctx).vrfs, (Column::Id, vrf)).await?.map(Vrf))
+    }
+
+    /// The subnets, sorted by network
+    async fn children(&self, ctx: &Context<'_>) -> Result<Vec<Network>> {
+        let mut children = many(&loaders(ctx).networks, (Column::Father, self.0.id)).await?;
+        children.sort_by_key(|x| x.network);
+        Ok(children.into_iter().map(Network).collect())
+    }
+
+    /// The devices, sorted by ip
+    async fn devices(&self, ctx: &Context<'_>, status: Option<Status>) -> Result<Vec<Device>> {
+        let mut devices = many(&loaders(ctx).devices, (Column::Network, self.0.id)).await?;
+        devices.sort_by_key(|x| x.ip);
+        Ok(devices
+            .into_iter()
+            .filter(|x| status.is_none_or(|s| x.status == s.into()))
+            .map(Device)
+            .collect())
+    }
+}
+
+pub struct Device(pub device::Device);
+
+#[Object]
+impl Device {
+    async fn ip(&self) -> String {
+        self.0.ip.to_string()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn status(&self) -> Status {
+        self.0.status.clone().into()
+    }
+
+    async fn hostname(&self) -> Option<&str> {
+        self.0.hostname.as_deref()
+    }
+
+    async fn fqdn(&self) -> Option<&str> {
+        self.0.fqdn.as_deref()
+    }
+
+    async fn mac(&self) -> Option<String> {
+        self.0.mac.as_ref().map(ToString::to_string)
+    }
+
+    async fn rack_unit(&self) -> Option<u16> {
+        self.0.rack_unit
+    }
+
+    async fn rack_height(&self) -> Option<u16> {
+        self.0.rack_height
+    }
+
+    /// Unix time of the last time the device answered
+    async fn last_seen(&self) -> Option<i64> {
+        self.0.last_seen
+    }
+
+    /// Only the admins can see it
+    async fn credential(&self, ctx: &Context<'_>) -> Option<Credential> {
+        if !is_admin(ctx) {
+            return None;
+        }
+        self.0.credential.clone().map(|x| Credential {
+            username: x.username,
+            password: x.password,
+        })
+    }
+
+    async fn network(&self, ctx: &Context<'_>) -> Result<Option<Network>> {
+        Ok(one(&loaders(ctx).networks, (Column::Id, self.0.network_id))
+            .await?
+            .map(Network))
+    }
+
+    async fn location(&self, ctx: &Context<'_>) -> Result<Option<Location>> {
+        let Some(location) = self.0.location else {
+            return Ok(None);
+        };
+        Ok(one(&loaders(ctx).locations, (Column::Id, location))
+            .await?
+            .map(Location))
+    }
+
+    async fn services(&self, ctx: &Context<'_>) -> Result<Vec<Service>> {
+        Ok(
+            many(&loaders(ctx).services, (Column::Network, self.0.network_id))
+                .await?
+                .into_iter()
+                .filter(|x| x.ip == self.0.ip)
+                .map(Service)
+                .collect(),
+        )
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Credential {
+    username: String,
+    password: String,
+}
+
+pub struct Service(pub service::Service);
+
+#[Object]
+impl Service {
+    async fn port(&self) -> u16 {
+        *self.0.port
+    }
+
+    async fn ip(&self) -> String {
+        self.0.ip.to_string()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn device(&self, ctx: &Context<'_>) -> Result<Option<Device>> {
+        Ok(
+            many(&loaders(ctx).devices, (Column::Network, self.0.netwok_id))
+                .await?
+                .into_iter()
+                .find(|x| x.ip == self.0.ip)
+                .map(Device),
+        )
+    }
+
+    /// What runs in the port
+    async fn service(&self, ctx: &Context<'_>) -> Result<Option<Services>> {
+        Ok(one(&loaders(ctx).catalog, (Column::Id, self.0.service_id))
+            .await?
+            .map(Services))
+    }
+}
+
+/// An entry of the catalog of services
+pub struct Services(pub service::Services);
+
+#[Object]
+impl Services {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn version(&self) -> Option<&str> {
+        self.0.version.as_deref()
+    }
+
+    /// The ports where it runs
+    async fn instances(&self, ctx: &Context<'_>) -> Result<Vec<Service>> {
+        Ok(many(&loaders(ctx).services, (Column::Service, self.0.id))
+            .await?
+            .into_iter()
+            .map(Service)
+            .collect())
+    }
+}
+
+pub struct Office(pub office::Office);
+
+#[Object]
+impl Office {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn address(&self) -> Option<&str> {
+        self.0.address.as_deref()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn locations(&self, ctx: &Context<'_>) -> Result<Vec<Location>> {
+        Ok(many(&loaders(ctx).locations, (Column::Office, self.0.id))
+            .await?
+            .into_iter()
+            .map(Location)
+            .collect())
+    }
+}
+
+pub struct Location(pub location::Location);
+
+#[Object]
+impl Location {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    #[graphql(name = "type")]
+    async fn location_type(&self) -> LocationType {
+        self.0.location_type.clone().into()
+    }
+
+    async fn label(&self) -> &str {
+        &self.0.label
+    }
+
+    async fn units(&self) -> Option<u16> {
+        self.0.units
+    }
+
+    async fn office(&self, ctx: &Context<'_>) -> Result<Option<Office>> {
+        let Some(office) = self.0.office_id else {
+            return Ok(None);
+        };
+        Ok(one(&loaders(ctx).offices, (Column::Id, office))
+            .await?
+            .map(Office))
+    }
+
+    /// The devices, sorted by rack unit
+    async fn devices(&self, ctx: &Context<'_>) -> Result<Vec<Device>> {
+        let mut devices = many(&loaders(ctx).devices, (Column::Location, self.0.id)).await?;
+        devices.sort_by_key(|x| (x.rack_unit, x.ip));
+        Ok(devices.into_iter().map(Device).collect())
+    }
+}
+
+pub struct Vrf(pub vrf::Vrf);
+
+#[Object]
+impl Vrf {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn rd(&self) -> Option<&str> {
+        self.0.rd.as_deref()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn networks(&self, ctx: &Context<'_>) -> Result<Vec<Network>> {
+        let mut networks = many(&loaders(ctx).networks, (Column::Vrf, self.0.id)).await?;
+        networks.sort_by_key(|x| x.network);
+        Ok(networks.into_iter().map(Network).collect())
+    }
+}
+
+/// The password is never exposed
+pub struct User(pub user::User);
+
+#[Object]
+impl User {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn username(&self) -> &str {
+        &self.0.username
+    }
+
+    async fn role(&self) -> Role {
+        self.0.role.clone().into()
+    }
+}
@@ -0,0 +1,122 @@
+use super::openapi::Failure;
+use super::*;
+use crate::models::backup::{Backup, RestoreReport};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use query_params::{ParamBackup, ParamRestore};
+use response_error::Builder;
+
+#[utoipa::path(
+    get,
+    operation_id = "admin_backup",
+    path = "/admin/backup",
+    tag = "admin",
+    params(ParamBackup),
+    responses(
+        (status = 200, description = "Every table of the database", body = Object),
+        Failure
+    )
+)]
+pub async fn backup(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamBackup {
+        exclude_credentials,
+        exclude_passwords,
+    }): Query<ParamBackup>,
+) -> Result<Response, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let mut backup = state.lock().await.backup().await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+
+    if exclude_credentials {
+        backup = backup.without_credentials();
+    }
+
+    if exclude_passwords {
+        backup = backup.without_passwords();
+    }
+
+    let filename = format!("ipam-backup-{}.json", backup.created_at);
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )],
+        Json(backup),
+    )
+        .into_response())
+}
+
+/// Validates the backup and loads it, nothing is written if any row is wrong
+#[utoipa::path(
+    post,
+    operation_id = "admin_restore",
+    path = "/admin/restore",
+    tag = "admin",
+    params(ParamRestore),
+    request_body(content = Object, description = "A backup made by `/admin/backup`"),
+    responses(
+        (status = 200, description = "What was restored", body = RestoreReport),
+        (status = 422, description = "The rows that aren't valid, nothing is restored", body = RestoreReport),
+        Failure
+    )
+)]
+pub async fn restore(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamRestore { replace, dry_run }): Query<ParamRestore>,
+    Json(backup): Json<Backup>,
+) -> Result<Response, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let report = state
+        .lock()
+        .await
+        .restore(backup, replace, dry_run)
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })?;
+
+    if report.committed {
+        tracing::info!(
+            "Backup restored by {} (replace: {}, skipped users: {:?})",
+            claim.username,
+            replace,
+            report.skipped
+        );
+    }
+
+    let status = if report.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+
+    Ok((status, Json(report)).into_response())
+}
@@ -0,0 +1,403 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::models::{
+    asset::{Asset, Interface, UpdateAsset, UpdateInterface},
+    device::Device,
+};
+use device::normalize_mac;
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "asset_create",
+    path = "/asset",
+    tag = "asset",
+    request_body = models_data_entry::Asset,
+    responses(
+        (status = 201, description = "The asset created", body = Insert<Asset>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(asset): Json<models_data_entry::Asset>,
+) -> Result<QueryResult<Asset>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    tracing::info!("New asset {:?}", asset);
+
+    state
+        .insert::<Asset>(vec![asset.into()])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "asset_get_all",
+    path = "/asset",
+    tag = "asset",
+    responses(
+        (status = 200, description = "The assets", body = Select<Asset>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<Asset>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Asset>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "asset_get_one",
+    path = "/asset/{id}",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    responses(
+        (status = 200, description = "The asset", body = Select<Asset>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Asset>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Asset>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "asset_update",
+    path = "/asset/{id}",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    request_body = UpdateAsset,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(asset): Json<UpdateAsset>,
+) -> Result<QueryResult<Asset>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .update::<Asset, _>(asset, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// The interfaces are deleted with the asset, their devices are kept without interface
+#[utoipa::path(
+    delete,
+    operation_id = "asset_delete",
+    path = "/asset/{id}",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Asset>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Asset>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "asset_get_interfaces",
+    path = "/asset/{id}/interface",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    responses(
+        (status = 200, description = "The interfaces of the asset", body = Select<Interface>),
+        Failure
+    )
+)]
+pub async fn get_interfaces(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Interface>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Interface>(Some(HashMap::from([("asset_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "asset_create_interface",
+    path = "/asset/{id}/interface",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    request_body = models_data_entry::Interface,
+    responses(
+        (status = 201, description = "The interface created", body = Insert<Interface>),
+        Failure
+    )
+)]
+pub async fn create_interface(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(interface): Json<models_data_entry::Interface>,
+) -> Result<QueryResult<Interface>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .get::<Asset>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    state
+        .insert::<Interface>(vec![Interface {
+            id: Uuid::new_v4(),
+            asset_id: id,
+            name: interface.name,
+            mac: interface.mac,
+        }])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "asset_update_interface",
+    path = "/asset/{id}/interface/{interface_id}",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset"), ("interface_id" = Uuid, Path, description = "The id of the interface")),
+    request_body = UpdateInterface,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update_interface(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path((id, interface_id)): Path<(Uuid, Uuid)>,
+    Json(mut interface): Json<UpdateInterface>,
+) -> Result<QueryResult<Interface>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    interface.mac = normalize_mac(interface.mac)
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let state = state.lock().await;
+
+    state
+        .update::<Interface, _>(
+            interface,
+            Some(HashMap::from([
+                ("id", interface_id.into()),
+                ("asset_id", id.into()),
+            ])),
+        )
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "asset_delete_interface",
+    path = "/asset/{id}/interface/{interface_id}",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset"), ("interface_id" = Uuid, Path, description = "The id of the interface")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete_interface(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path((id, interface_id)): Path<(Uuid, Uuid)>,
+) -> Result<QueryResult<Interface>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Interface>(Some(HashMap::from([
+            ("id", interface_id.into()),
+            ("asset_id", id.into()),
+        ])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// Every ip of the asset, through its interfaces
+#[utoipa::path(
+    get,
+    operation_id = "asset_get_devices",
+    path = "/asset/{id}/device",
+    tag = "asset",
+    params(("id" = Uuid, Path, description = "The id of the asset")),
+    responses(
+        (status = 200, description = "The devices of the interfaces of the asset", body = Select<Device>),
+        Failure
+    )
+)]
+pub async fn get_devices(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Device>, ResponseError> {
+    let state = state.lock().await;
+
+    let interfaces = state
+        .get::<Interface>(Some(HashMap::from([("asset_id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    let mut devices = Vec::new();
+    for interface in interfaces {
+        devices.extend(
+            state
+                .get::<Device>(Some(HashMap::from([("interface_id", interface.id.into())])))
+                .await
+                .unwrap_or_default(),
+        );
+    }
+
+    if devices.is_empty() {
+        return Err(ResponseError::builder()
+            .title("Devices not found".to_string())
+            .detail(format!("The asset {} doesn't have any device", id))
+            .status(StatusCode::NOT_FOUND)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    if claim.role != Role::Admin {
+        for dev in devices.iter_mut() {
+            dev.credential = None;
+        }
+    }
+
+    devices.sort_by_key(|x| x.ip);
+
+    Ok(devices.into())
+}
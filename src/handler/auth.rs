@@ -6,7 +6,19 @@ use axum::{
     response::{Redirect, Response},
 };
 use libipam::authentication::{self, create_token, encrypt, verify_passwd};
+use openapi::{Failure, Insert};
 
+#[utoipa::path(
+    post,
+    operation_id = "user_create",
+    path = "/user",
+    tag = "user",
+    request_body = user::User,
+    responses(
+        (status = 201, description = "The user created", body = Insert<user::User>),
+        Failure
+    )
+)]
 #[axum::debug_handler]
 pub async fn create(
     State(state): State<RepositoryType>,
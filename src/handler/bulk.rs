@@ -0,0 +1,963 @@
+use super::*;
+use crate::database::Import;
+use crate::events::{self, EventKind};
+use crate::models::{
+    device::{Device, Status},
+    dhcp::DhcpScope,
+    dns,
+    location::Location,
+    mac::MacAddr,
+    network::{Network, Vlan},
+    vlan::VlanEntry,
+    vrf::Vrf,
+};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use network::{boundaries, is_host};
+use openapi::Failure;
+use query_params::{ParamDeviceExport, ParamImport};
+use response_error::Builder;
+use serde::Serialize;
+use std::{collections::HashSet, net::IpAddr};
+use utoipa::ToSchema;
+
+const NETWORK_COLUMNS: [&str; 9] = [
+    "id",
+    "network",
+    "description",
+    "vlan",
+    "vrf",
+    "father",
+    "available",
+    "used",
+    "free",
+];
+
+const NETWORK_IMPORT: [&str; 4] = ["network", "description", "vlan", "vrf"];
+
+const DEVICE_COLUMNS: [&str; 10] = [
+    "network_id",
+    "network",
+    "ip",
+    "status",
+    "hostname",
+    "fqdn",
+    "mac",
+    "description",
+    "location",
+    "last_seen",
+];
+
+const DEVICE_IMPORT: [&str; 10] = [
+    "network_id",
+    "network",
+    "vrf",
+    "ip",
+    "status",
+    "hostname",
+    "fqdn",
+    "mac",
+    "description",
+    "location",
+];
+
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    /// Columns of the file that aren't imported
+    pub ignored: Vec<String>,
+    pub errors: Vec<RowError>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RowError {
+    pub line: u64,
+    pub column: Option<&'static str>,
+    pub error: String,
+}
+
+struct Row {
+    line: u64,
+    values: HashMap<&'static str, String>,
+}
+
+impl Row {
+    /// The value of a column of the file, `Some("")` if the cell is empty
+    fn get(&self, column: &str) -> Option<&str> {
+        self.values.get(column).map(String::as_str)
+    }
+
+    fn value(&self, column: &str) -> Option<&str> {
+        self.get(column).filter(|x| !x.is_empty())
+    }
+}
+
+impl ImportReport {
+    fn error(&mut self, row: &Row, column: Option<&'static str>, error: String) {
+        self.errors.push(RowError {
+            line: row.line,
+            column,
+            error,
+        });
+    }
+
+    fn into_response(self) -> Response {
+        let status = if self.errors.is_empty() {
+            StatusCode::OK
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+        (status, Json(self)).into_response()
+    }
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "bulk_export_networks",
+    path = "/network/export",
+    tag = "network",
+    responses(
+        (status = 200, description = "The networks", body = String, content_type = "text/csv"),
+        Failure
+    )
+)]
+pub async fn export_networks(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<Response, ResponseError> {
+    let state = state.lock().await;
+
+    let mut networks = state.get::<Network>(None).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+    networks.sort_by_key(|x| (x.vrf, x.network));
+    let vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+
+    let records = networks.into_iter().map(|x| {
+        vec![
+            x.id.to_string(),
+            x.network.to_string(),
+            x.description.unwrap_or_default(),
+            x.vlan.map(|x| x.to_string()).unwrap_or_default(),
+            x.vrf
+                .and_then(|id| vrfs.iter().find(|x| x.id == id))
+                .map(|x| x.name.clone())
+                .unwrap_or_default(),
+            x.father.map(|x| x.to_string()).unwrap_or_default(),
+            x.available.to_string(),
+            x.used.to_string(),
+            x.free.to_string(),
+        ]
+    });
+
+    write_csv(&NETWORK_COLUMNS, records, "networks.csv")
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())
+}
+
+/// The credentials of the devices aren't exported
+#[utoipa::path(
+    get,
+    operation_id = "bulk_export_devices",
+    path = "/device/export",
+    tag = "device",
+    params(ParamDeviceExport),
+    responses(
+        (status = 200, description = "The devices", body = String, content_type = "text/csv"),
+        Failure
+    )
+)]
+pub async fn export_devices(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Query(ParamDeviceExport { network_id }): Query<ParamDeviceExport>,
+) -> Result<Response, ResponseError> {
+    let state = state.lock().await;
+
+    let mut devices = state
+        .get::<Device>(network_id.map(|x| HashMap::from([("network_id", x.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })?;
+    devices.sort_by_key(|x| (x.network_id, x.ip));
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    let locations = state.get::<Location>(None).await.unwrap_or_default();
+
+    let records = devices.into_iter().map(|x| {
+        vec![
+            x.network_id.to_string(),
+            networks
+                .iter()
+                .find(|net| net.id == x.network_id)
+                .map(|net| net.network.to_string())
+                .unwrap_or_default(),
+            x.ip.to_string(),
+            format!("{:?}", x.status),
+            x.hostname.unwrap_or_default(),
+            x.fqdn.unwrap_or_default(),
+            x.mac.map(|x| x.to_string()).unwrap_or_default(),
+            x.description.unwrap_or_default(),
+            x.location
+                .and_then(|id| locations.iter().find(|x| x.id == id))
+                .map(|x| x.label.clone())
+                .unwrap_or_default(),
+            x.last_seen.map(|x| x.to_string()).unwrap_or_default(),
+        ]
+    });
+
+    write_csv(&DEVICE_COLUMNS, records, "devices.csv")
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())
+}
+
+/// Creates the networks of the file, every network is placed under the most specific one
+/// that contains it (in the file or not) inside its vrf. Nothing is created unless every
+/// row is valid
+#[utoipa::path(
+    post,
+    operation_id = "bulk_import_networks",
+    path = "/network/import",
+    tag = "network",
+    params(ParamImport),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, description = "What was imported", body = ImportReport),
+        (status = 422, description = "The rows that aren't valid, nothing is imported", body = ImportReport),
+        Failure
+    )
+)]
+pub async fn import_networks(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamImport { dry_run, map }): Query<ParamImport>,
+    body: String,
+) -> Result<Response, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let (rows, ignored) = read_csv(&body, &NETWORK_IMPORT, map.as_deref())
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())?;
+    let mut report = ImportReport {
+        dry_run,
+        rows: rows.len(),
+        ignored,
+        ..Default::default()
+    };
+
+    let state = state.lock().await;
+    let mut all = state.get::<Network>(None).await.unwrap_or_default();
+    let vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+    let vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
+
+    let mut parsed = Vec::new();
+    for row in &rows {
+        let errors = report.errors.len();
+
+        let network = match row.value("network").map(str::parse::<IpNet>) {
+            Some(Ok(e)) => Some(e.trunc()),
+            Some(Err(e)) => {
+                report.error(row, Some("network"), e.to_string());
+                None
+            }
+            None => {
+                report.error(row, Some("network"), "The network is required".to_string());
+                None
+            }
+        };
+
+        let vlan = match row.value("vlan").map(str::parse::<u16>) {
+            Some(Ok(vid)) => Vlan::new(vid)
+                .map_err(|x| report.error(row, Some("vlan"), x.to_string()))
+                .ok(),
+            Some(Err(e)) => {
+                report.error(row, Some("vlan"), e.to_string());
+                None
+            }
+            None => None,
+        };
+
+        let vrf = match row.value("vrf").map(|x| find_vrf(&vrfs, x)) {
+            Some(Ok(e)) => e,
+            Some(Err(e)) => {
+                report.error(row, Some("vrf"), e);
+                None
+            }
+            None => None,
+        };
+
+        if let (Some(network), true) = (network, report.errors.len() == errors) {
+            let network: Network = models_data_entry::Network {
+                network,
+                description: row.value("description").map(str::to_string),
+                vlan,
+                vlan_id: vlan
+                    .and_then(|vid| VlanEntry::by_vid(&vlans, vid))
+                    .map(|x| x.id),
+                father: None,
+                vrf,
+            }
+            .into();
+            parsed.push((row, network));
+        }
+    }
+
+    // The fathers go before their children
+    parsed.sort_by_key(|(_, x)| x.network.prefix_len());
+
+    let mut import = Import::default();
+    for (row, mut network) in parsed {
+        if let Err(e) = place(&all, &mut network) {
+            report.error(row, Some("network"), e);
+            continue;
+        }
+
+        // Like a moved network, the new one takes its boundaries from every ancestor
+        if let Some(father) = all.iter().find(|x| Some(x.id) == network.father) {
+            let taken = boundaries(&network.network, &father.network);
+            import.count(&all, network.father, -taken, 0);
+        }
+
+        all.push(network.clone());
+        import.networks.push(network);
+    }
+    let created = import.networks.clone();
+
+    report.created = created.len();
+    if !report.errors.is_empty() || dry_run {
+        return Ok(report.into_response());
+    }
+
+    state.import(import).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+    report.committed = true;
+    tracing::info!("Imported {} networks", created.len());
+
+    for network in &created {
+        events::emit(EventKind::NetworkCreated, network);
+    }
+
+    Ok(report.into_response())
+}
+
+/// Creates or updates the devices of the file. The network of a device is the one of the
+/// network_id or network columns, or the most specific one that contains its ip. The
+/// columns of the file replace the values of the device, an empty cell removes it.
+/// Nothing is saved unless every row is valid
+#[utoipa::path(
+    post,
+    operation_id = "bulk_import_devices",
+    path = "/device/import",
+    tag = "device",
+    params(ParamImport),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, description = "What was imported", body = ImportReport),
+        (status = 422, description = "The rows that aren't valid, nothing is imported", body = ImportReport),
+        Failure
+    )
+)]
+pub async fn import_devices(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamImport { dry_run, map }): Query<ParamImport>,
+    body: String,
+) -> Result<Response, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let (rows, ignored) = read_csv(&body, &DEVICE_IMPORT, map.as_deref())
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())?;
+    let mut report = ImportReport {
+        dry_run,
+        rows: rows.len(),
+        ignored,
+        ..Default::default()
+    };
+
+    let state = state.lock().await;
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    let vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+    let locations = state.get::<Location>(None).await.unwrap_or_default();
+    let scopes = state.get::<DhcpScope>(None).await.unwrap_or_default();
+
+    let mut seen = HashSet::new();
+    let mut import = Import::default();
+    let mut kinds = Vec::new();
+    for row in &rows {
+        let errors = report.errors.len();
+
+        let ip = match row.value("ip").map(str::parse::<IpAddr>) {
+            Some(Ok(e)) => e,
+            Some(Err(e)) => {
+                report.error(row, Some("ip"), e.to_string());
+                continue;
+            }
+            None => {
+                report.error(row, Some("ip"), "The ip is required".to_string());
+                continue;
+            }
+        };
+
+        let network = match find_network(&networks, &vrfs, row, ip) {
+            Ok(e) => e,
+            Err((column, e)) => {
+                report.error(row, Some(column), e);
+                continue;
+            }
+        };
+
+        if !is_host(&network.network, &ip) {
+            report.error(
+                row,
+                Some("ip"),
+                format!("The ip {} isn't a host of {}", ip, network.network),
+            );
+            continue;
+        }
+
+        if !seen.insert((ip, network.id)) {
+            report.error(
+                row,
+                Some("ip"),
+                format!("The ip {} is repeated in {}", ip, network.network),
+            );
+            continue;
+        }
+
+        let current = state
+            .get::<Device>(Some(HashMap::from([
+                ("ip", ip.into()),
+                ("network_id", network.id.into()),
+            ])))
+            .await
+            .ok()
+            .map(|mut x| x.remove(0));
+
+        let mut device = current.clone().unwrap_or(Device {
+            ip,
+            description: None,
+            location: None,
+            rack_unit: None,
+            rack_height: None,
+            status: Status::default(),
+            network_id: network.id,
+            credential: None,
+            hostname: None,
+            fqdn: None,
+            mac: None,
+            interface_id: None,
+            last_seen: None,
+        });
+
+        if let Some(status) = row.value("status") {
+            match parse_status(status) {
+                Some(e) => device.status = e,
+                None => report.error(
+                    row,
+                    Some("status"),
+                    format!("The status {} doesn't exist", status),
+                ),
+            }
+        }
+
+        if let Some(hostname) = row.get("hostname") {
+            device.hostname = Some(dns::normalize(hostname)).filter(|x| !x.is_empty());
+            if device
+                .hostname
+                .as_deref()
+                .is_some_and(|x| !dns::is_label(x))
+            {
+                report.error(
+                    row,
+                    Some("hostname"),
+                    format!("The hostname {} must be a single label", hostname),
+                );
+            }
+        }
+
+        if let Some(fqdn) = row.get("fqdn") {
+            device.fqdn = Some(dns::normalize(fqdn)).filter(|x| !x.is_empty());
+            if device
+                .fqdn
+                .as_deref()
+                .is_some_and(|x| !dns::is_domain(x) || !x.contains('.'))
+            {
+                report.error(
+                    row,
+                    Some("fqdn"),
+                    format!("The fqdn {} isn't a valid domain name", fqdn),
+                );
+            }
+        }
+
+        if let Some(mac) = row.get("mac") {
+            device.mac = match Some(mac)
+                .filter(|x| !x.is_empty())
+                .map(str::parse::<MacAddr>)
+            {
+                Some(Ok(e)) => Some(e),
+                Some(Err(e)) => {
+                    report.error(row, Some("mac"), e.to_string());
+                    None
+                }
+                None => None,
+            };
+        }
+
+        if let Some(description) = row.get("description") {
+            device.description = Some(description.to_string()).filter(|x| !x.is_empty());
+        }
+
+        if let Some(location) = row.get("location") {
+            device.location = match Some(location).filter(|x| !x.is_empty()) {
+                Some(location) => {
+                    let found = locations
+                        .iter()
+                        .find(|x| x.label == location || x.id.to_string() == location);
+                    if found.is_none() {
+                        report.error(
+                            row,
+                            Some("location"),
+                            format!("The location {} doesn't exist", location),
+                        );
+                    }
+                    found.map(|x| x.id)
+                }
+                None => None,
+            };
+        }
+
+        // A reserved or online device can't be inside a pool, the server would lease its address
+        if matches!(device.status, Status::Online | Status::Reserved) {
+            if let Some(pool) = scopes
+                .iter()
+                .filter(|x| x.network_id == network.id)
+                .flat_map(|x| &x.pools)
+                .find(|x| x.contains(&ip))
+            {
+                report.error(
+                    row,
+                    Some("ip"),
+                    format!("The address {} is part of the dhcp pool {}", ip, pool),
+                );
+            }
+        }
+
+        if report.errors.len() != errors {
+            continue;
+        }
+
+        // Like a ping or a reservation, a taken address is counted as used by the network and
+        // its ancestors, and it's given back when the device is unknown again
+        let used = device.is_taken() as i64 - current.as_ref().is_some_and(Device::is_taken) as i64;
+        if used != 0 {
+            import.count(&networks, Some(network.id), 0, used);
+        }
+
+        if current.is_some() {
+            report.updated += 1;
+            kinds.push(EventKind::DeviceUpdated);
+        } else {
+            report.created += 1;
+            kinds.push(EventKind::DeviceCreated);
+        }
+        import.devices.push(device);
+    }
+
+    if !report.errors.is_empty() || dry_run {
+        return Ok(report.into_response());
+    }
+
+    let changed = import
+        .devices
+        .iter()
+        .map(|x| x.network_id)
+        .collect::<HashSet<_>>();
+    let devices = import.devices.clone();
+    state.import(import).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+    report.committed = true;
+    tracing::info!(
+        "Imported {} devices ({} updated)",
+        report.created + report.updated,
+        report.updated
+    );
+
+    for (kind, device) in kinds.into_iter().zip(devices) {
+        events::emit(
+            kind,
+            &Device {
+                credential: None,
+                ..device
+            },
+        );
+    }
+    for id in changed {
+        network::emit_updated(&state, id).await;
+    }
+
+    Ok(report.into_response())
+}
+
+/// Reads the rows of the file with the columns in `columns`. The header names are matched
+/// without case, `map` renames them first (`name:hostname,address:ip`)
+fn read_csv(
+    body: &str,
+    columns: &[&'static str],
+    map: Option<&str>,
+) -> Result<(Vec<Row>, Vec<String>), ResponseError> {
+    let invalid = |detail: String| {
+        ResponseError::builder()
+            .title("Invalid csv".to_string())
+            .detail(detail)
+            .status(StatusCode::BAD_REQUEST)
+            .build()
+    };
+
+    let mut mapping = HashMap::new();
+    for pair in map.unwrap_or_default().split(',').filter(|x| !x.is_empty()) {
+        let Some((from, to)) = pair.split_once(':') else {
+            return Err(invalid(format!(
+                "The mapping {} must be like column:field",
+                pair
+            )));
+        };
+        let Some(to) = columns.iter().find(|x| x.eq_ignore_ascii_case(to.trim())) else {
+            return Err(invalid(format!(
+                "The field {} doesn't exist, the fields are {}",
+                to,
+                columns.join(", ")
+            )));
+        };
+        mapping.insert(from.trim().to_lowercase(), *to);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let header = reader
+        .headers()
+        .map_err(|x| invalid(x.to_string()))?
+        .iter()
+        .map(|x| {
+            let name = x.to_lowercase();
+            mapping
+                .get(&name)
+                .copied()
+                .or_else(|| columns.iter().find(|x| **x == name).copied())
+                .ok_or(x.to_string())
+        })
+        .collect::<Vec<_>>();
+    let ignored = header
+        .iter()
+        .filter_map(|x| x.clone().err())
+        .filter(|x| !x.is_empty())
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|x| invalid(x.to_string()))?;
+        let values = header
+            .iter()
+            .zip(record.iter())
+            .filter_map(|(column, value)| {
+                column
+                    .as_ref()
+                    .ok()
+                    .map(|column| (*column, value.to_string()))
+            })
+            .collect();
+
+        rows.push(Row {
+            line: record.position().map(|x| x.line()).unwrap_or_default(),
+            values,
+        });
+    }
+
+    Ok((rows, ignored))
+}
+
+fn write_csv<I>(columns: &[&str], records: I, filename: &str) -> Result<Response, ResponseError>
+where
+    I: Iterator<Item = Vec<String>>,
+{
+    let error = |x: csv::Error| {
+        ResponseError::builder()
+            .title("Csv error".to_string())
+            .detail(x.to_string())
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .build()
+    };
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns).map_err(error)?;
+    for record in records {
+        writer.write_record(&record).map_err(error)?;
+    }
+    let body = writer
+        .into_inner()
+        .map_err(|x| error(x.into_error().into()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Sets the father of a new network to the most specific network of its vrf that contains
+/// it. The network can't exist nor contain networks that are already placed
+pub(super) fn place(all: &[Network], network: &mut Network) -> Result<(), String> {
+    let same_vrf = all
+        .iter()
+        .filter(|x| x.vrf == network.vrf)
+        .collect::<Vec<_>>();
+
+    if same_vrf.iter().any(|x| x.network == network.network) {
+        return Err(format!("The network {} already exists", network.network));
+    }
+
+    network.father = same_vrf
+        .iter()
+        .filter(|x| x.network.contains(&network.network))
+        .max_by_key(|x| x.network.prefix_len())
+        .map(|x| x.id);
+
+    match same_vrf
+        .iter()
+        .find(|x| x.father == network.father && x.overlaps(&network.network))
+    {
+        Some(sibling) => Err(format!(
+            "The network {} overlaps with the network {}",
+            network.network, sibling.network
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A vrf by its name or id, the nil uuid is the global routing table
+fn find_vrf(vrfs: &[Vrf], value: &str) -> Result<Option<Uuid>, String> {
+    if let Ok(id) = value.parse::<Uuid>() {
+        if id.is_nil() {
+            return Ok(None);
+        }
+        if vrfs.iter().any(|x| x.id == id) {
+            return Ok(Some(id));
+        }
+    }
+
+    vrfs.iter()
+        .find(|x| x.name == value)
+        .map(|x| Some(x.id))
+        .ok_or(format!("The vrf {} doesn't exist", value))
+}
+
+fn find_network<'a>(
+    networks: &'a [Network],
+    vrfs: &[Vrf],
+    row: &Row,
+    ip: IpAddr,
+) -> Result<&'a Network, (&'static str, String)> {
+    if let Some(id) = row.value("network_id") {
+        return id
+            .parse::<Uuid>()
+            .ok()
+            .and_then(|id| networks.iter().find(|x| x.id == id))
+            .ok_or(("network_id", format!("The network {} doesn't exist", id)));
+    }
+
+    let vrf = row
+        .value("vrf")
+        .map(|x| find_vrf(vrfs, x))
+        .transpose()
+        .map_err(|x| ("vrf", x))?;
+    let in_vrf = |x: &&Network| vrf.is_none_or(|vrf| x.vrf == vrf);
+
+    let candidates = match row.value("network") {
+        Some(value) => {
+            let network = value
+                .parse::<IpNet>()
+                .map_err(|x| ("network", x.to_string()))?
+                .trunc();
+            networks
+                .iter()
+                .filter(in_vrf)
+                .filter(|x| x.network == network)
+                .collect::<Vec<_>>()
+        }
+        None => network::longest_matches(networks.iter().filter(in_vrf), &ip),
+    };
+
+    match candidates[..] {
+        [network] => Ok(network),
+        [] => Err((
+            "network",
+            format!("There isn't any network for the ip {}", ip),
+        )),
+        _ => Err((
+            "vrf",
+            format!("The ip {} is in several vrfs, the vrf is needed", ip),
+        )),
+    }
+}
+
+fn parse_status(value: &str) -> Option<Status> {
+    match value.to_lowercase().as_str() {
+        "reserved" => Some(Status::Reserved),
+        "unknown" => Some(Status::Unknown),
+        "online" => Some(Status::Online),
+        "offline" => Some(Status::Offline),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::fixture::{admin, counters, hosts, network, state};
+    use crate::models::dhcp::Pool;
+
+    fn params() -> Query<ParamImport> {
+        Query(ParamImport {
+            dry_run: false,
+            map: None,
+        })
+    }
+
+    /// Imports the devices and returns the report
+    async fn devices_import(state: &RepositoryType, devices: &str) -> serde_json::Value {
+        let resp = import_devices(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/device/import"),
+            params(),
+            devices.to_string(),
+        )
+        .await
+        .unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn import_updates_the_counters_of_the_ancestors() {
+        let state = state().await;
+        state
+            .lock()
+            .await
+            .insert(vec![network("10.0.0.0/16")])
+            .await
+            .unwrap();
+
+        let networks = "network\n10.0.1.0/24\n10.0.1.0/26\n";
+        import_networks(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/network/import"),
+            params(),
+            networks.to_string(),
+        )
+        .await
+        .unwrap();
+
+        // The /24 takes 10.0.1.0 and 10.0.1.255 from the root, the /26 only 10.0.1.63 from both
+        let child = hosts("10.0.1.0/24") - 1;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65531, 0, 65531));
+        assert_eq!(counters(&state, "10.0.1.0/24").await, (child, 0, child));
+
+        let mut events = events::subscribe();
+        let devices = "ip,status\n10.0.1.10,Reserved\n10.0.1.100,Online\n10.0.2.1,Offline\n";
+        let report = devices_import(&state, devices).await;
+        assert_eq!(report["committed"], true);
+
+        // An offline device keeps the address it took when it was online
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65531, 3, 65528));
+        assert_eq!(counters(&state, "10.0.1.0/24").await, (child, 2, child - 2));
+        assert_eq!(counters(&state, "10.0.1.0/26").await.1, 1);
+
+        // Only the unknown devices don't take an address
+        devices_import(&state, "ip,status\n10.0.1.10,Unknown\n10.0.2.1,Online\n").await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65531, 2, 65529));
+        assert_eq!(counters(&state, "10.0.1.0/24").await, (child, 1, child - 1));
+        assert_eq!(counters(&state, "10.0.1.0/26").await.1, 0);
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if event.data["ip"] == "10.0.1.10" {
+                kinds.push(event.event);
+            }
+        }
+        assert_eq!(
+            kinds,
+            vec![EventKind::DeviceCreated, EventKind::DeviceUpdated]
+        );
+    }
+
+    #[tokio::test]
+    async fn import_keeps_the_taken_addresses_out_of_the_pools() {
+        let state = state().await;
+        let network = network("10.0.0.0/24");
+        let id = network.id;
+        let scope = DhcpScope {
+            network_id: id,
+            pools: vec![Pool {
+                start: "10.0.0.100".parse().unwrap(),
+                end: "10.0.0.199".parse().unwrap(),
+            }],
+            gateway: None,
+            dns_servers: Vec::new(),
+            lease_time: 3600,
+            options: Vec::new(),
+            subnet_id: 1,
+        };
+        state.lock().await.insert(vec![network]).await.unwrap();
+        state.lock().await.insert(vec![scope]).await.unwrap();
+
+        let devices = "ip,status\n10.0.0.150,Unknown\n10.0.0.160,Reserved\n10.0.0.10,Online\n";
+        let report = devices_import(&state, devices).await;
+        assert_eq!(report["committed"], false);
+        assert_eq!(report["errors"].as_array().unwrap().len(), 1);
+        assert_eq!(report["errors"][0]["line"], 3);
+        assert_eq!(counters(&state, "10.0.0.0/24").await.1, 0);
+    }
+}
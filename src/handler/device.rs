@@ -1,11 +1,25 @@
 use super::response_error::Builder;
 use super::*;
-use crate::models::{device::*, network::*};
+use crate::database::{repository::error::RepositoryError, SqliteRepository};
+use crate::events::{self, EventKind};
+use crate::models::{asset::Interface, device::*, dns, location::*, mac::MacAddr, network::*};
 use libipam::ipam_services::{self, Ping};
+use openapi::{Affected, Failure, Insert, Select};
 use query_params::{ParamDevice, ParamDeviceGet};
 
 use std::{collections::HashSet, net::IpAddr};
 
+#[utoipa::path(
+    post,
+    operation_id = "device_create",
+    path = "/device",
+    tag = "device",
+    request_body = models_data_entry::Device,
+    responses(
+        (status = 201, description = "The device created", body = Insert<Device>),
+        Failure
+    )
+)]
 pub async fn create(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -20,11 +34,168 @@ pub async fn create(
             .build());
     }
 
+    let mut device: Device = device.into();
+    device.hostname = device.hostname.map(|x| dns::normalize(&x));
+    device.fqdn = device.fqdn.map(|x| dns::normalize(&x));
+    verify_names(device.hostname.as_deref(), device.fqdn.as_deref())
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
     let state = state.lock().await;
 
-    Ok(state.insert::<Device>(vec![device.into()]).await?)
+    if device.rack_unit.is_some() {
+        verify_rack_allocation(&state, &device)
+            .await
+            .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+    }
+
+    // The ip is on the interface, the mac is the one of the interface unless it's given
+    if let Some(interface) = device.interface_id {
+        let interface = state
+            .get::<Interface>(Some(HashMap::from([("id", interface.into())])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+            .remove(0);
+        device.mac = device.mac.or(interface.mac);
+    }
+
+    let resp = state.insert::<Device>(vec![device.clone()]).await?;
+    events::emit(
+        EventKind::DeviceCreated,
+        &Device {
+            credential: None,
+            ..device
+        },
+    );
+
+    Ok(resp)
+}
+
+/// Returns the mac in its canonical form, an empty one is kept to remove it
+pub(super) fn normalize_mac(mac: Option<String>) -> Result<Option<String>, ResponseError> {
+    match mac {
+        Some(mac) if !mac.is_empty() => mac
+            .parse::<MacAddr>()
+            .map(|x| Some(x.to_string()))
+            .map_err(|x| {
+                ResponseError::builder()
+                    .title("Invalid mac address".to_string())
+                    .detail(x.to_string())
+                    .status(StatusCode::BAD_REQUEST)
+                    .build()
+            }),
+        mac => Ok(mac),
+    }
+}
+
+fn verify_names(hostname: Option<&str>, fqdn: Option<&str>) -> Result<(), ResponseError> {
+    if hostname.is_some_and(|x| !dns::is_label(x)) {
+        return Err(ResponseError::builder()
+            .title("Invalid hostname".to_string())
+            .detail(format!(
+                "The hostname {} must be a single label of letters, digits and hyphens",
+                hostname.unwrap_or_default()
+            ))
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    }
+
+    if fqdn.is_some_and(|x| !dns::is_domain(x) || !x.contains('.')) {
+        return Err(ResponseError::builder()
+            .title("Invalid fqdn".to_string())
+            .detail(format!(
+                "The fqdn {} isn't a valid domain name",
+                fqdn.unwrap_or_default()
+            ))
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    }
+
+    Ok(())
 }
 
+async fn verify_rack_allocation(
+    state: &SqliteRepository,
+    device: &Device,
+) -> Result<(), ResponseError> {
+    let Some(location) = device.location else {
+        return Err(ResponseError::builder()
+            .title("Invalid rack position".to_string())
+            .detail("A rack unit needs a location of type Rack".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    };
+
+    let location = state
+        .get::<Location>(Some(HashMap::from([("id", location.into())])))
+        .await?
+        .remove(0);
+
+    if location.location_type != LocationType::Rack {
+        return Err(ResponseError::builder()
+            .title("Invalid rack position".to_string())
+            .detail(format!("The location {} isn't a Rack", location.label))
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    }
+
+    let units = device
+        .rack_units()
+        .filter(|x| *x.start() > 0 && *x.end() <= location.rack_units());
+    let Some(units) = units else {
+        return Err(ResponseError::builder()
+            .title("Invalid rack position".to_string())
+            .detail(format!(
+                "The unit {} with height {} doesn't fit in the rack {} (1-{})",
+                device.rack_unit.unwrap_or_default(),
+                device.rack_height.unwrap_or(1).max(1),
+                location.label,
+                location.rack_units()
+            ))
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    };
+
+    let devices = state
+        .get::<Device>(Some(HashMap::from([("location", location.id.into())])))
+        .await
+        .unwrap_or_default();
+
+    for dev in devices {
+        if dev.ip == device.ip && dev.network_id == device.network_id {
+            continue;
+        }
+
+        if let Some(used) = dev.rack_units() {
+            if used.start() <= units.end() && units.start() <= used.end() {
+                return Err(ResponseError::builder()
+                    .title("Conflict".to_string())
+                    .detail(format!(
+                        "The units {}-{} of the rack {} are used by the device {}",
+                        used.start(),
+                        used.end(),
+                        location.label,
+                        dev.ip
+                    ))
+                    .status(StatusCode::CONFLICT)
+                    .build());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "device_create_all_devices",
+    path = "/device/{network_id}",
+    tag = "device",
+    params(("network_id" = Uuid, Path)),
+    responses(
+        (status = 201, description = "The devices of the network that didn't exist", body = Insert<Device>),
+        Failure
+    )
+)]
 pub async fn create_all_devices(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -57,12 +228,22 @@ pub async fn create_all_devices(
                     ip,
                     description: None,
                     location: None,
+                    rack_unit: None,
+                    rack_height: None,
                     status: Status::default(),
                     network_id,
                     credential: None,
+                    hostname: None,
+                    fqdn: None,
+                    mac: None,
+                    interface_id: None,
+                    last_seen: None,
                 })
                 .collect();
-            Ok(state.insert(to_insert).await?)
+            let resp = state.insert(to_insert).await?;
+            events::emit(EventKind::NetworkUpdated, &network);
+
+            Ok(resp)
         } else {
             Err(ResponseError::builder()
                 .title("The devices alreade exist".to_string())
@@ -72,7 +253,12 @@ pub async fn create_all_devices(
         }
     } else {
         match models_data_entry::create_all_devices(network.network, network_id) {
-            Some(e) => Ok(state.insert::<Device>(e).await?),
+            Some(e) => {
+                let resp = state.insert::<Device>(e).await?;
+                events::emit(EventKind::NetworkUpdated, &network);
+
+                Ok(resp)
+            }
             None => Err(ResponseError::builder()
                 .status(StatusCode::NO_CONTENT)
                 .build()),
@@ -80,33 +266,100 @@ pub async fn create_all_devices(
     }
 }
 
+#[utoipa::path(
+    get,
+    operation_id = "device_get",
+    path = "/device",
+    tag = "device",
+    params(ParamDeviceGet),
+    responses(
+        (status = 200, description = "The devices", body = Select<Device>),
+        Failure
+    )
+)]
 pub async fn get(
     State(state): State<RepositoryType>,
     uri: Uri,
-    Query(ParamDeviceGet { ip, network_id }): Query<ParamDeviceGet>,
+    Query(ParamDeviceGet {
+        ip,
+        network_id,
+        location,
+        vrf,
+    }): Query<ParamDeviceGet>,
 ) -> Result<QueryResult<Device>, ResponseError> {
     let state = state.lock().await;
-    let mut condition = HashMap::from([("network_id", network_id.into())]);
+    let mut condition = HashMap::new();
+
+    if let Some(network_id) = network_id {
+        condition.insert("network_id", network_id.into());
+    }
 
     if let Some(ip) = ip {
         condition.insert("ip", ip.into());
     }
 
-    let mut devices = state.get::<Device>(Some(condition)).await.map_err(|x| {
+    if let Some(location) = location {
+        condition.insert("location", location.into());
+    }
+
+    let condition = if condition.is_empty() {
+        None
+    } else {
+        Some(condition)
+    };
+
+    let mut devices = state.get::<Device>(condition).await.map_err(|x| {
         let tmp: Builder = ResponseError::from(x).into();
         tmp.instance(uri.to_string()).build()
     })?;
+
+    // The nil uuid is the global routing table (networks without vrf)
+    if let Some(vrf) = vrf {
+        let networks = state
+            .get::<Network>(Some(HashMap::from([(
+                "vrf_id",
+                Some(vrf).filter(|x| !x.is_nil()).into(),
+            )])))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| x.id)
+            .collect::<HashSet<Uuid>>();
+
+        devices.retain(|x| networks.contains(&x.network_id));
+
+        if devices.is_empty() {
+            return Err(
+                Into::<Builder>::into(ResponseError::from(RepositoryError::RowNotFound))
+                    .instance(uri.to_string())
+                    .build(),
+            );
+        }
+    }
+
     devices.sort_by_key(|x| x.ip);
 
     Ok(devices.into())
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "device_update",
+    path = "/device",
+    tag = "device",
+    params(ParamDevice),
+    request_body = UpdateDevice,
+    responses(
+        (status = 200, description = "The device updated", body = Affected),
+        Failure
+    )
+)]
 pub async fn update(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
     uri: Uri,
     Query(ParamDevice { ip, network_id }): Query<ParamDevice>,
-    Json(device): Json<UpdateDevice>,
+    Json(mut device): Json<UpdateDevice>,
 ) -> Result<QueryResult<Device>, ResponseError> {
     if claim.role != Role::Admin {
         return Err(ResponseError::builder()
@@ -116,8 +369,54 @@ pub async fn update(
             .status(StatusCode::UNAUTHORIZED)
             .build());
     }
+
+    // An empty name removes it
+    device.hostname = device.hostname.map(|x| dns::normalize(&x));
+    device.fqdn = device.fqdn.map(|x| dns::normalize(&x));
+    verify_names(
+        device.hostname.as_deref().filter(|x| !x.is_empty()),
+        device.fqdn.as_deref().filter(|x| !x.is_empty()),
+    )
+    .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+    device.mac = normalize_mac(device.mac)
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
     let state = state.lock().await;
 
+    if let Some(interface) = device.interface_id.filter(|x| !x.is_nil()) {
+        state
+            .get::<Interface>(Some(HashMap::from([("id", interface.into())])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+    }
+
+    if device.location.is_some() || device.rack_unit.is_some() || device.rack_height.is_some() {
+        let mut current = state
+            .get::<Device>(Some(HashMap::from([
+                ("ip", ip.into()),
+                ("network_id", network_id.into()),
+            ])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+            .remove(0);
+
+        if let Some(location) = device.location {
+            current.location = Some(location).filter(|x| !x.is_nil());
+        }
+        if let Some(unit) = device.rack_unit {
+            current.rack_unit = Some(unit).filter(|x| *x != 0);
+        }
+        if let Some(height) = device.rack_height {
+            current.rack_height = Some(height).filter(|x| *x != 0);
+        }
+
+        if current.rack_unit.is_some() {
+            verify_rack_allocation(&state, &current)
+                .await
+                .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+        }
+    }
+
     let network = state
         .get::<Network>(Some(HashMap::from([(
             "id",
@@ -131,6 +430,20 @@ pub async fn update(
         .remove(0);
 
     if device.ip.is_some() || device.network_id.is_some() {
+        let current = state
+            .get::<Device>(Some(HashMap::from([
+                ("ip", ip.into()),
+                ("network_id", network_id.into()),
+            ])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+            .remove(0);
+        if matches!(current.status, Status::Reserved | Status::Online) {
+            dhcp::verify_outside_pools(&state, network.id, &device.ip.unwrap_or(ip))
+                .await
+                .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+        }
+
         if device.ip.as_ref().map(|x| x != &ip).unwrap_or(false)
             || device.network_id.map(|x| x != network_id).unwrap_or(false)
         {
@@ -167,7 +480,11 @@ pub async fn update(
         }
     }
 
-    Ok(state
+    let key = (
+        device.ip.unwrap_or(ip),
+        device.network_id.unwrap_or(network_id),
+    );
+    let resp = state
         .update::<Device, _>(
             device,
             Some(HashMap::from([
@@ -176,7 +493,31 @@ pub async fn update(
             ])),
         )
         .await
-        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?)
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+    if resp.rows_affected() > 0 {
+        emit_device(&state, EventKind::DeviceUpdated, key.0, key.1).await;
+    }
+
+    Ok(resp)
+}
+
+pub(super) async fn emit_device(
+    state: &SqliteRepository,
+    event: EventKind,
+    ip: IpAddr,
+    network_id: Uuid,
+) {
+    if let Ok(mut device) = state
+        .get::<Device>(Some(HashMap::from([
+            ("ip", ip.into()),
+            ("network_id", network_id.into()),
+        ])))
+        .await
+    {
+        let mut device = device.remove(0);
+        device.credential = None;
+        events::emit(event, &device);
+    }
 }
 
 // pub async fn get_one(
@@ -197,10 +538,21 @@ pub async fn update(
 //     })))
 // }
 
+#[utoipa::path(
+    delete,
+    operation_id = "device_delete",
+    path = "/device",
+    tag = "device",
+    params(ParamDevice),
+    responses(
+        (status = 200, description = "The device deleted", body = Affected),
+        Failure
+    )
+)]
 pub async fn delete(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
-    Query((ip, network_id)): Query<(IpAddr, Uuid)>,
+    Query(ParamDevice { ip, network_id }): Query<ParamDevice>,
 ) -> Result<impl IntoResponse, ResponseError> {
     if claim.role != Role::Admin {
         return Err(ResponseError::builder()
@@ -210,14 +562,33 @@ pub async fn delete(
 
     let state = state.lock().await;
 
-    Ok(state
+    let resp = state
         .delete::<Device>(Some(HashMap::from([
             ("ip", ip.into()),
             ("network_id", network_id.into()),
         ])))
-        .await?)
+        .await?;
+    if resp.rows_affected() > 0 {
+        events::emit(
+            EventKind::DeviceDeleted,
+            &serde_json::json!({ "ip": ip, "network_id": network_id }),
+        );
+    }
+
+    Ok(resp)
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "device_ping",
+    path = "/device/ping",
+    tag = "device",
+    params(ParamDevice),
+    responses(
+        (status = 200, description = "If the device answers, its status is updated"),
+        Failure
+    )
+)]
 pub async fn ping(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -249,6 +620,7 @@ pub async fn ping(
                 .map_err(|x| {
                     Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string())
                 })?;
+            emit_device(&state, EventKind::DeviceStatusChanged, ip, network_id).await;
         }
 
         if device.status == Status::Unknown {
@@ -262,26 +634,47 @@ pub async fn ping(
                 })?
                 .remove(0);
 
-            let _ = network.used.add(1).is_err();
-            let _ = network.free.sub(1).is_err();
-
-            let updater = UpdateNetworkCount {
-                used: Some(network.used),
-                free: Some(network.free),
-                available: None,
-            };
-            if state
-                .update::<Network, _>(updater, Some(HashMap::from([("id", network_id.into())])))
-                .await
-                .is_err()
-            {
-                return Err(ResponseError::builder()
-                    .instance(uri.to_string())
-                    .status(StatusCode::NOT_MODIFIED)
-                    .detail("We have been able to modify the host counter in the network, but the device was updated seamlessly".to_string())
-                    .title("We can't changed the network count".to_string())
-                    .build());
+            let mut id_to_update = Some(network.id);
+
+            // The address is used by the network and every ancestor, as a reserved one
+            while let Some(id) = id_to_update {
+                let _ = network.used.add(1).is_err();
+                let _ = network.free.sub(1).is_err();
+
+                let updater = UpdateNetworkCount {
+                    used: Some(network.used.clone()),
+                    free: Some(network.free.clone()),
+                    available: None,
+                };
+                if state
+                    .update::<Network, _>(updater, Some(HashMap::from([("id", id.into())])))
+                    .await
+                    .is_err()
+                {
+                    return Err(ResponseError::builder()
+                        .instance(uri.to_string())
+                        .status(StatusCode::NOT_MODIFIED)
+                        .detail("We have been able to modify the host counter in the network, but the device was updated seamlessly".to_string())
+                        .title("We can't changed the network count".to_string())
+                        .build());
+                }
+
+                id_to_update = match network.father {
+                    Some(father) => {
+                        network = state
+                            .get::<Network>(Some(HashMap::from([("id", father.into())])))
+                            .await
+                            .map_err(|x| {
+                                Into::<Builder>::into(ResponseError::from(x))
+                                    .instance(uri.to_string())
+                            })?
+                            .remove(0);
+                        Some(network.id)
+                    }
+                    None => None,
+                };
             }
+            super::network::emit_updated(&state, network_id).await;
         }
 
         Ok(Ping::Pong)
@@ -299,11 +692,23 @@ pub async fn ping(
                 .map_err(|x| {
                     Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string())
                 })?;
+            emit_device(&state, EventKind::DeviceStatusChanged, ip, network_id).await;
         }
         Ok(Ping::Fail)
     }
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "device_reserve",
+    path = "/device/reserve",
+    tag = "device",
+    params(ParamDevice),
+    responses(
+        (status = 200, description = "The device is reserved", body = Affected),
+        Failure
+    )
+)]
 pub async fn reserve(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -311,6 +716,10 @@ pub async fn reserve(
 ) -> Result<QueryResult<Device>, ResponseError> {
     let state = state.lock().await;
 
+    dhcp::verify_outside_pools(&state, network_id, &ip)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
     let tmp = state
         .update(
             Status::Reserved,
@@ -321,6 +730,7 @@ pub async fn reserve(
         )
         .await
         .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+    emit_device(&state, EventKind::DeviceStatusChanged, ip, network_id).await;
 
     let mut network = state
         .get::<Network>(Some(HashMap::from([("id", network_id.into())])))
@@ -354,6 +764,7 @@ pub async fn reserve(
             id_to_update = None;
         }
     }
+    super::network::emit_updated(&state, network_id).await;
 
     Ok(tmp)
 }
@@ -0,0 +1,786 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::database::SqliteRepository;
+use crate::events::EventKind;
+use crate::models::{
+    device::{Device, Status, UpdateDeviceLease},
+    dhcp::{self, DhcpReservation, DhcpScope, Subnet},
+    dns::{self, DnsDomain},
+    lease::{self, Lease},
+    mac::MacAddr,
+    network::{Network, UpdateNetworkCount},
+};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use network::is_host;
+use query_params::ParamLease;
+use response_error::Builder;
+use serde::Serialize;
+use std::{collections::HashSet, net::IpAddr};
+use utoipa::ToSchema;
+
+#[utoipa::path(
+    get,
+    operation_id = "dhcp_get_scope",
+    path = "/network/{id}/dhcp",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The dhcp scope of the network", body = Select<DhcpScope>),
+        Failure
+    )
+)]
+pub async fn get_scope(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<DhcpScope>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<DhcpScope>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "dhcp_set_scope",
+    path = "/network/{id}/dhcp",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    request_body = models_data_entry::DhcpScope,
+    responses(
+        (status = 201, description = "The scope created", body = Insert<DhcpScope>),
+        (status = 200, description = "The scope updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn set_scope(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(scope): Json<models_data_entry::DhcpScope>,
+) -> Result<QueryResult<DhcpScope>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let network = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    let scopes = state.get::<DhcpScope>(None).await.unwrap_or_default();
+    let current = scopes.iter().find(|x| x.network_id == id);
+
+    let mut pools = scope.pools;
+    pools.sort_by_key(|x| x.start);
+    let scope = DhcpScope {
+        network_id: id,
+        pools,
+        gateway: scope.gateway,
+        dns_servers: scope.dns_servers,
+        lease_time: scope.lease_time.unwrap_or(dhcp::DEFAULT_LEASE_TIME),
+        options: scope.options,
+        subnet_id: current
+            .map(|x| x.subnet_id)
+            .unwrap_or_else(|| dhcp::next_subnet_id(&scopes)),
+    };
+
+    verify_scope(&state, &network, &scope)
+        .await
+        .map_err(|x| x.instance(uri.to_string()).build())?;
+
+    if current.is_some() {
+        state
+            .update::<DhcpScope, _>(scope, Some(HashMap::from([("network_id", id.into())])))
+            .await
+    } else {
+        state.insert::<DhcpScope>(vec![scope]).await
+    }
+    .map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+/// Deleting the scope also deletes its reservations
+#[utoipa::path(
+    delete,
+    operation_id = "dhcp_delete_scope",
+    path = "/network/{id}/dhcp",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The scope and its reservations deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete_scope(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<DhcpScope>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let resp = state
+        .delete::<DhcpScope>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })?;
+
+    let _ = state
+        .delete::<DhcpReservation>(Some(HashMap::from([("network_id", id.into())])))
+        .await;
+
+    Ok(resp)
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "dhcp_get_reservations",
+    path = "/network/{id}/dhcp/reservation",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The reservations of the network", body = Select<DhcpReservation>),
+        Failure
+    )
+)]
+pub async fn get_reservations(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<DhcpReservation>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<DhcpReservation>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+/// A reservation gives the address of an existing device to its mac, outside the pools
+#[utoipa::path(
+    post,
+    operation_id = "dhcp_create_reservation",
+    path = "/network/{id}/dhcp/reservation",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    request_body = models_data_entry::DhcpReservation,
+    responses(
+        (status = 201, description = "The reservation created", body = Insert<DhcpReservation>),
+        Failure
+    )
+)]
+pub async fn create_reservation(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(reservation): Json<models_data_entry::DhcpReservation>,
+) -> Result<QueryResult<DhcpReservation>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let scope = state
+        .get::<DhcpScope>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    let device = state
+        .get::<Device>(Some(HashMap::from([
+            ("ip", reservation.ip.into()),
+            ("network_id", id.into()),
+        ])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    if let Some(pool) = scope.pools.iter().find(|x| x.contains(&reservation.ip)) {
+        return Err(ResponseError::builder()
+            .title("Reservation inside a pool".to_string())
+            .detail(format!(
+                "The address {} is part of the pool {}",
+                reservation.ip, pool
+            ))
+            .status(StatusCode::CONFLICT)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let hostname = reservation
+        .hostname
+        .filter(|x| !x.is_empty())
+        .or(device.hostname)
+        .map(|x| dns::normalize(&x));
+    if hostname.as_deref().is_some_and(|x| !dns::is_label(x)) {
+        return Err(ResponseError::builder()
+            .title("Invalid hostname".to_string())
+            .detail("The hostname must be a single dns label".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let reserved = state
+        .get::<DhcpReservation>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .unwrap_or_default();
+    if let Some(tmp) = reserved
+        .iter()
+        .find(|x| x.mac == reservation.mac || x.ip == reservation.ip)
+    {
+        return Err(ResponseError::builder()
+            .title("Reservation already exists".to_string())
+            .detail(format!("The mac {} is reserved for {}", tmp.mac, tmp.ip))
+            .status(StatusCode::CONFLICT)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    state
+        .insert::<DhcpReservation>(vec![DhcpReservation {
+            network_id: id,
+            ip: reservation.ip,
+            mac: reservation.mac,
+            hostname,
+        }])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "dhcp_delete_reservation",
+    path = "/network/{id}/dhcp/reservation/{mac}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network"), ("mac" = MacAddr, Path)),
+    responses(
+        (status = 200, description = "The reservation deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete_reservation(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path((id, mac)): Path<(Uuid, MacAddr)>,
+) -> Result<QueryResult<DhcpReservation>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<DhcpReservation>(Some(HashMap::from([
+            ("network_id", id.into()),
+            ("mac", mac.into()),
+        ])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// Configuration of the Kea dhcp4 and dhcp6 servers with every scope
+#[utoipa::path(
+    get,
+    operation_id = "dhcp_kea",
+    path = "/dhcp/kea",
+    tag = "dhcp",
+    responses(
+        (status = 200, description = "The Dhcp4 and Dhcp6 configuration", body = Object),
+        Failure
+    )
+)]
+pub async fn kea(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<Json<serde_json::Value>, ResponseError> {
+    let state = state.lock().await;
+
+    let (networks, scopes, reservations, domains) = load(&state)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())?;
+
+    Ok(Json(dhcp::kea(&subnets(
+        &networks,
+        &scopes,
+        &reservations,
+        &domains,
+    ))))
+}
+
+/// Configuration of dnsmasq with every scope, each network is a tag
+#[utoipa::path(
+    get,
+    operation_id = "dhcp_dnsmasq",
+    path = "/dhcp/dnsmasq",
+    tag = "dhcp",
+    responses(
+        (status = 200, description = "The dnsmasq configuration", body = String, content_type = "text/plain"),
+        Failure
+    )
+)]
+pub async fn dnsmasq(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<Response, ResponseError> {
+    let state = state.lock().await;
+
+    let (networks, scopes, reservations, domains) = load(&state)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()).build())?;
+
+    let body = subnets(&networks, &scopes, &reservations, &domains)
+        .iter()
+        .map(Subnet::dnsmasq)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"dnsmasq.conf\"".to_string(),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct LeaseImport {
+    pub leases: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Leases of ips that aren't inside any network
+    #[schema(value_type = Vec<String>)]
+    pub outside: Vec<IpAddr>,
+    /// Leases inside a network whose device doesn't exist
+    #[schema(value_type = Vec<String>)]
+    pub missing: Vec<IpAddr>,
+    /// Leases inside a pool, they are dynamic and their devices keep their status
+    #[schema(value_type = Vec<String>)]
+    pub dynamic: Vec<IpAddr>,
+    pub invalid: Vec<String>,
+}
+
+/// Imports an ISC dhcpd leases file or a Kea lease CSV file. The device of a lease is
+/// the one of its ip in the most specific network that has it, it gets the mac, the
+/// hostname if it doesn't have one, the status and the last time it was seen. A lease
+/// inside a pool doesn't change the status, an online device there would collide with it
+#[utoipa::path(
+    post,
+    operation_id = "dhcp_import_leases",
+    path = "/dhcp/lease",
+    tag = "dhcp",
+    params(ParamLease),
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "What the leases changed", body = LeaseImport),
+        Failure
+    )
+)]
+pub async fn import_leases(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamLease { vrf }): Query<ParamLease>,
+    body: String,
+) -> Result<Json<LeaseImport>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let (leases, invalid) = lease::parse(&body);
+    let mut resp = LeaseImport {
+        leases: leases.len(),
+        invalid,
+        ..Default::default()
+    };
+
+    let state = state.lock().await;
+
+    let mut networks = state.get::<Network>(None).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+
+    // The nil uuid is the global routing table (networks without vrf)
+    if let Some(vrf) = vrf {
+        let vrf = Some(vrf).filter(|x| !x.is_nil());
+        networks.retain(|x| x.vrf == vrf);
+    }
+    networks.sort_by_key(|x| std::cmp::Reverse(x.network.prefix_len()));
+    let scopes = state.get::<DhcpScope>(None).await.unwrap_or_default();
+
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let mut changed = HashSet::new();
+    for lease in leases {
+        let candidates = networks
+            .iter()
+            .filter(|x| x.network.contains(&lease.ip))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            resp.outside.push(lease.ip);
+            continue;
+        }
+
+        let mut device = None;
+        for network in candidates {
+            if let Ok(mut tmp) = state
+                .get::<Device>(Some(HashMap::from([
+                    ("ip", lease.ip.into()),
+                    ("network_id", network.id.into()),
+                ])))
+                .await
+            {
+                device = Some(tmp.remove(0));
+                break;
+            }
+        }
+        let Some(device) = device else {
+            resp.missing.push(lease.ip);
+            continue;
+        };
+
+        let dynamic = scopes
+            .iter()
+            .find(|x| x.network_id == device.network_id)
+            .is_some_and(|x| x.pools.iter().any(|x| x.contains(&device.ip)));
+        if dynamic {
+            resp.dynamic.push(device.ip);
+        }
+
+        let update = lease_update(&device, &lease, dynamic, now);
+        if update.status.is_none()
+            && update.last_seen.is_none()
+            && update.mac.is_none()
+            && update.hostname.is_none()
+        {
+            resp.unchanged += 1;
+            continue;
+        }
+
+        let event = if update.mac.is_none() && update.hostname.is_none() && update.status.is_some()
+        {
+            EventKind::DeviceStatusChanged
+        } else {
+            EventKind::DeviceUpdated
+        };
+        let online = device.status == Status::Unknown && update.status == Some(Status::Online);
+
+        state
+            .update::<Device, _>(
+                update,
+                Some(HashMap::from([
+                    ("ip", device.ip.into()),
+                    ("network_id", device.network_id.into()),
+                ])),
+            )
+            .await
+            .map_err(|x| {
+                Into::<Builder>::into(ResponseError::from(x))
+                    .instance(uri.to_string())
+                    .build()
+            })?;
+        device::emit_device(&state, event, device.ip, device.network_id).await;
+        resp.updated += 1;
+
+        // Like a ping, an unknown device that shows up is counted as used
+        if online {
+            if let Some(network) = networks.iter_mut().find(|x| x.id == device.network_id) {
+                let _ = network.used.add(1);
+                let _ = network.free.sub(1);
+
+                let updater = UpdateNetworkCount {
+                    used: Some(network.used.clone()),
+                    free: Some(network.free.clone()),
+                    available: None,
+                };
+                state
+                    .update::<Network, _>(updater, Some(HashMap::from([("id", network.id.into())])))
+                    .await
+                    .map_err(|x| {
+                        Into::<Builder>::into(ResponseError::from(x))
+                            .instance(uri.to_string())
+                            .build()
+                    })?;
+                changed.insert(network.id);
+            }
+        }
+    }
+
+    for id in changed {
+        network::emit_updated(&state, id).await;
+    }
+
+    tracing::info!(
+        "Imported {} leases, {} devices updated",
+        resp.leases,
+        resp.updated
+    );
+
+    Ok(Json(resp))
+}
+
+/// Only what changes is updated. The reserved devices and the ones inside a pool keep their
+/// status and the names given by hand aren't replaced
+fn lease_update(device: &Device, lease: &Lease, dynamic: bool, now: i64) -> UpdateDeviceLease {
+    let status = if dynamic {
+        None
+    } else if lease.is_active(now) {
+        Some(Status::Online)
+            .filter(|_| device.status != Status::Reserved && device.status != Status::Online)
+    } else {
+        Some(Status::Offline).filter(|_| device.status == Status::Online)
+    };
+
+    let hostname = lease
+        .hostname
+        .as_deref()
+        .map(dns::normalize)
+        .and_then(|x| x.split('.').next().map(str::to_string))
+        .filter(|x| dns::is_label(x) && device.hostname.is_none());
+
+    UpdateDeviceLease {
+        status,
+        last_seen: lease
+            .seen
+            .filter(|x| device.last_seen.is_none_or(|last| *x > last)),
+        mac: lease.mac.filter(|x| device.mac != Some(*x)),
+        hostname,
+    }
+}
+
+type Loaded = (
+    Vec<Network>,
+    Vec<DhcpScope>,
+    Vec<DhcpReservation>,
+    Vec<DnsDomain>,
+);
+
+async fn load(state: &SqliteRepository) -> Result<Loaded, ResponseError> {
+    let networks = state.get::<Network>(None).await?;
+    let scopes = state.get::<DhcpScope>(None).await?;
+    let reservations = state.get::<DhcpReservation>(None).await.unwrap_or_default();
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+
+    Ok((networks, scopes, reservations, domains))
+}
+
+fn subnets<'a>(
+    networks: &'a [Network],
+    scopes: &'a [DhcpScope],
+    reservations: &'a [DhcpReservation],
+    domains: &'a [DnsDomain],
+) -> Vec<Subnet<'a>> {
+    scopes
+        .iter()
+        .filter_map(|scope| {
+            let network = networks.iter().find(|x| x.id == scope.network_id)?;
+            Some(Subnet {
+                network: network.network,
+                scope,
+                reservations: reservations
+                    .iter()
+                    .filter(|x| x.network_id == scope.network_id)
+                    .collect(),
+                domain: dns::domain_of(networks, domains, Some(network.id))
+                    .map(|x| x.domain.as_str()),
+            })
+        })
+        .collect()
+}
+
+/// A reserved or online device can't be inside a pool, the server would lease its address
+pub(super) async fn verify_outside_pools(
+    state: &SqliteRepository,
+    network_id: Uuid,
+    ip: &IpAddr,
+) -> Result<(), ResponseError> {
+    let Ok(mut scope) = state
+        .get::<DhcpScope>(Some(HashMap::from([("network_id", network_id.into())])))
+        .await
+    else {
+        return Ok(());
+    };
+
+    match scope.remove(0).pools.iter().find(|x| x.contains(ip)) {
+        Some(pool) => Err(ResponseError::builder()
+            .title("Pool collision".to_string())
+            .detail(format!(
+                "The address {} is part of the dhcp pool {}",
+                ip, pool
+            ))
+            .status(StatusCode::CONFLICT)
+            .build()),
+        None => Ok(()),
+    }
+}
+
+/// The pools must be disjoint ranges of hosts of the network that leave out the gateway,
+/// the reservations and every reserved or online device
+async fn verify_scope(
+    state: &SqliteRepository,
+    network: &Network,
+    scope: &DhcpScope,
+) -> Result<(), Builder> {
+    let taken = state
+        .get::<Device>(Some(HashMap::from([("network_id", network.id.into())])))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|x| matches!(x.status, Status::Reserved | Status::Online))
+        .map(|x| x.ip)
+        .chain(
+            state
+                .get::<DhcpReservation>(Some(HashMap::from([("network_id", network.id.into())])))
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.ip),
+        )
+        .collect::<Vec<IpAddr>>();
+
+    check_scope(&network.network, scope, &taken)
+}
+
+/// The checks of `verify_scope` with the reserved, online and reserved by dhcp addresses of
+/// the network already known, like after a resize
+pub(super) fn check_scope(net: &IpNet, scope: &DhcpScope, taken: &[IpAddr]) -> Result<(), Builder> {
+    let invalid = |detail: String| {
+        ResponseError::builder()
+            .title("Invalid dhcp scope".to_string())
+            .detail(detail)
+            .status(StatusCode::BAD_REQUEST)
+    };
+
+    if scope.lease_time == 0 {
+        return Err(invalid("The lease time can't be 0".to_string()));
+    }
+
+    for pool in &scope.pools {
+        if !is_host(net, &pool.start) || !is_host(net, &pool.end) || pool.start > pool.end {
+            return Err(invalid(format!(
+                "The pool {} isn't a range of hosts of {}",
+                pool, net
+            )));
+        }
+    }
+
+    if let Some(pair) = scope.pools.windows(2).find(|x| x[0].overlaps(&x[1])) {
+        return Err(invalid(format!(
+            "The pools {} and {} overlap",
+            pair[0], pair[1]
+        )));
+    }
+
+    if let Some(gateway) = scope.gateway {
+        if !is_host(net, &gateway) || scope.pools.iter().any(|x| x.contains(&gateway)) {
+            return Err(invalid(format!(
+                "The gateway {} must be a host of {} outside the pools",
+                gateway, net
+            )));
+        }
+    }
+
+    if let Some(server) = scope
+        .dns_servers
+        .iter()
+        .find(|x| x.is_ipv4() != net.addr().is_ipv4())
+    {
+        return Err(invalid(format!(
+            "The dns server {} isn't of the family of {}",
+            server, net
+        )));
+    }
+
+    let collisions = scope
+        .pools
+        .iter()
+        .flat_map(|pool| taken.iter().filter(|x| pool.contains(x)))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if !collisions.is_empty() {
+        return Err(ResponseError::builder()
+            .title("Pool collision".to_string())
+            .detail(format!(
+                "The pools contain reserved or online addresses: {}",
+                collisions.join(", ")
+            ))
+            .status(StatusCode::CONFLICT));
+    }
+
+    Ok(())
+}
@@ -1,9 +1,14 @@
-use super::{office::Office, query_params::ParamPKServiceGet, utils::TypeTable};
+use super::{
+    network::get_by_vlan_name,
+    query_params::{ParamNetworkGet, ParamPKServiceGet},
+    utils::TypeTable,
+};
 use axum::{
     extract::{Path, Query, Request, State},
     response::{Html, IntoResponse},
     Extension,
 };
+use serde::Serialize;
 use std::{collections::HashMap, sync::LazyLock};
 use tera::{Context, Tera};
 use tokio::sync::Mutex;
@@ -14,9 +19,13 @@ use crate::{
     database::repository::Repository,
     models::{
         device::Device,
+        location::{Location, LocationType},
         network::Network,
+        office::Office,
         service::{Service, Services},
         user::Role,
+        vlan::VlanEntry,
+        vrf::Vrf,
     },
     services::Claims,
 };
@@ -38,6 +47,13 @@ pub async fn login() -> impl IntoResponse {
     Html(tera.render("login.html", &context).unwrap()).into_response()
 }
 
+/// Redoc over the OpenAPI document of the api
+pub async fn docs() -> impl IntoResponse {
+    let tera = TEMPLATES.lock().await;
+
+    Html(tera.render("docs.html", &Context::new()).unwrap()).into_response()
+}
+
 #[instrument]
 pub async fn fallback(req: Request) -> impl IntoResponse {
     let tera = TEMPLATES.lock().await;
@@ -51,17 +67,27 @@ pub async fn fallback(req: Request) -> impl IntoResponse {
 pub async fn http_view_network(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
+    Query(ParamNetworkGet { vlan }): Query<ParamNetworkGet>,
 ) -> impl IntoResponse {
     let state = state.lock().await;
+    let vlan = vlan.filter(|x| !x.is_empty());
 
-    let networks = state
-        .get::<Network>(Some(HashMap::from([("father", TypeTable::Null)])))
-        .await
-        .unwrap_or_default();
+    let networks = match &vlan {
+        Some(name) => get_by_vlan_name(&state, name.clone()).await,
+        None => {
+            state
+                .get::<Network>(Some(HashMap::from([("father", TypeTable::Null)])))
+                .await
+        }
+    }
+    .unwrap_or_default();
+    let vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
 
     let mut cont = Context::new();
     cont.insert("block", "network");
     cont.insert("networks", &networks);
+    cont.insert("vlans", &vlans);
+    cont.insert("vlan_filter", &vlan);
     cont.insert("role", &claim.role);
     cont.insert("user_id", &claim.sub);
     cont.insert("username", &claim.username);
@@ -96,6 +122,17 @@ pub async fn http_view_devices(
         devices.sort_by_key(|x| x.ip);
     }
 
+    let locations = state.get::<Location>(None).await.unwrap_or_default();
+    let vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
+    let vrf = match network.first().and_then(|x| x.vrf) {
+        Some(id) => state
+            .get::<Vrf>(Some(HashMap::from([("id", id.into())])))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop()),
+        None => None,
+    };
+
     if claim.role != Role::Admin {
         for dev in devices.iter_mut() {
             dev.credential = None;
@@ -107,6 +144,9 @@ pub async fn http_view_devices(
     con.insert("network", &network.first());
     con.insert("devices", &devices);
     con.insert("subnet", &network_chiled);
+    con.insert("locations", &locations);
+    con.insert("vlans", &vlans);
+    con.insert("vrf", &vrf);
     con.insert("user_id", &claim.sub);
     con.insert("username", &claim.username);
     con.insert("role", &claim.role);
@@ -129,11 +169,88 @@ pub async fn offices(
 
     let state = state.lock().await;
     let ofs = state.get::<Office>(None).await.unwrap_or_default();
+    let locations = state.get::<Location>(None).await.unwrap_or_default();
     cont.insert("offices", &ofs);
+    cont.insert("locations", &locations);
     let tera = TEMPLATES.lock().await;
     Html(tera.render("index.html", &cont).unwrap()).into_response()
 }
 
+#[derive(Serialize)]
+struct RackSlot<'a> {
+    unit: u16,
+    height: u16,
+    device: Option<&'a Device>,
+}
+
+pub async fn rack(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    Path(location_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let state = state.lock().await;
+
+    let location = state
+        .get::<Location>(Some(HashMap::from([("id", location_id.into())])))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|x| x.location_type == LocationType::Rack);
+
+    let mut devices = state
+        .get::<Device>(Some(HashMap::from([("location", location_id.into())])))
+        .await
+        .unwrap_or_default();
+
+    if claim.role != Role::Admin {
+        for dev in devices.iter_mut() {
+            dev.credential = None;
+        }
+    }
+
+    let mut slots = Vec::new();
+    if let Some(location) = &location {
+        let mut unit = location.rack_units();
+        while unit > 0 {
+            let device = devices
+                .iter()
+                .find(|x| x.rack_units().is_some_and(|units| units.contains(&unit)));
+
+            match device.and_then(|x| x.rack_units()) {
+                Some(units) => {
+                    let height = unit - units.start() + 1;
+                    slots.push(RackSlot {
+                        unit,
+                        height,
+                        device,
+                    });
+                    unit -= height;
+                }
+                None => {
+                    slots.push(RackSlot {
+                        unit,
+                        height: 1,
+                        device: None,
+                    });
+                    unit -= 1;
+                }
+            }
+        }
+    }
+
+    let mut ctx = Context::new();
+    ctx.insert("block", "rack");
+    ctx.insert("location", &location);
+    ctx.insert("units", &location.as_ref().map(Location::rack_units));
+    ctx.insert("slots", &slots);
+    ctx.insert("user_id", &claim.sub);
+    ctx.insert("role", &claim.role);
+    ctx.insert("username", &claim.username);
+
+    let tera = TEMPLATES.lock().await;
+    Html(tera.render("index.html", &ctx).unwrap()).into_response()
+}
+
 pub async fn services(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -0,0 +1,240 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::models::{
+    device::Device,
+    location::{Location, LocationType, UpdateLocation},
+};
+use query_params::ParamLocation;
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "location_create",
+    path = "/location",
+    tag = "location",
+    request_body = models_data_entry::Location,
+    responses(
+        (status = 201, description = "The location created", body = Insert<Location>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(location): Json<models_data_entry::Location>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    tracing::info!("New location {:?}", location);
+
+    state
+        .insert::<Location>(vec![location.into()])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "location_get_all",
+    path = "/location",
+    tag = "location",
+    params(ParamLocation),
+    responses(
+        (status = 200, description = "The locations", body = Select<Location>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Query(ParamLocation { office_id }): Query<ParamLocation>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Location>(office_id.map(|x| HashMap::from([("office_id", x.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "location_get_one",
+    path = "/location/{id}",
+    tag = "location",
+    params(("id" = Uuid, Path, description = "The id of the location")),
+    responses(
+        (status = 200, description = "The location", body = Select<Location>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Location>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "location_update",
+    path = "/location/{id}",
+    tag = "location",
+    params(("id" = Uuid, Path, description = "The id of the location")),
+    request_body = UpdateLocation,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(location): Json<UpdateLocation>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    // Only a rack has units to mount the devices on
+    let unracked = location
+        .location_type
+        .as_ref()
+        .is_some_and(|x| *x != LocationType::Rack);
+    if location.units.is_some() || unracked {
+        let current = state
+            .get::<Location>(Some(HashMap::from([("id", id.into())])))
+            .await
+            .map_err(|x| {
+                Into::<Builder>::into(ResponseError::from(x))
+                    .instance(uri.to_string())
+                    .build()
+            })?
+            .remove(0);
+
+        let devices = state
+            .get::<Device>(Some(HashMap::from([("location", id.into())])))
+            .await
+            .unwrap_or_default();
+
+        if unracked && current.location_type == LocationType::Rack {
+            if let Some(dev) = devices.iter().find(|x| x.rack_unit.is_some()) {
+                return Err(ResponseError::builder()
+                    .title("Conflict".to_string())
+                    .detail(format!(
+                        "The device {} is mounted in the rack, the location must stay a rack",
+                        dev.ip
+                    ))
+                    .status(StatusCode::CONFLICT)
+                    .instance(uri.to_string())
+                    .build());
+            }
+        }
+
+        if let Some(units) = location.units {
+            let units = Location {
+                units: Some(units).filter(|x| *x != 0),
+                ..current
+            }
+            .rack_units();
+
+            if let Some(dev) = devices
+                .iter()
+                .find(|x| x.rack_units().is_some_and(|x| *x.end() > units))
+            {
+                return Err(ResponseError::builder()
+                    .title("Conflict".to_string())
+                    .detail(format!(
+                        "The device {} uses units past {}, the rack can't be shrunk",
+                        dev.ip, units
+                    ))
+                    .status(StatusCode::CONFLICT)
+                    .instance(uri.to_string())
+                    .build());
+            }
+        }
+    }
+
+    state
+        .update::<Location, _>(location, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "location_delete",
+    path = "/location/{id}",
+    tag = "location",
+    params(("id" = Uuid, Path, description = "The id of the location")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Location>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
@@ -0,0 +1,179 @@
+use super::openapi::{Failure, Select};
+use super::*;
+use crate::models::{
+    device::Device,
+    mac::MacAddr,
+    network::{Network, Vlan},
+    service::Service,
+    vlan::VlanEntry,
+    vrf::Vrf,
+};
+use query_params::ParamLookup;
+use serde::Serialize;
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Vendor {
+    pub mac: MacAddr,
+    #[schema(value_type = Option<String>)]
+    pub vendor: Option<&'static str>,
+    pub local: bool,
+}
+
+/// Vendor of a mac address from its oui
+#[utoipa::path(
+    get,
+    operation_id = "lookup_vendor",
+    path = "/oui/{mac}",
+    tag = "search",
+    params(("mac" = String, Path, description = "The mac address")),
+    responses(
+        (status = 200, description = "The vendor of the mac address", body = Vendor),
+        Failure
+    )
+)]
+pub async fn vendor(Path(mac): Path<MacAddr>) -> Json<Vendor> {
+    Json(Vendor {
+        mac,
+        vendor: mac.vendor(),
+        local: mac.is_local(),
+    })
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Lookup {
+    #[schema(value_type = String)]
+    pub ip: IpAddr,
+    pub network: Network,
+    pub ancestors: Vec<Network>,
+    pub vrf: Option<Vrf>,
+    /// The vid of the nearest network with a vlan, the network or one of its ancestors
+    pub vid: Option<Vlan>,
+    pub vlan: Option<VlanEntry>,
+    pub device: Option<Device>,
+    pub services: Vec<Service>,
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "lookup",
+    path = "/lookup/{ip}",
+    tag = "search",
+    params(("ip" = String, Path, description = "The ip to look up"), ParamLookup),
+    responses(
+        (status = 200, description = "The network of the ip, its ancestors, vlan, device and services", body = Select<Lookup>),
+        Failure
+    )
+)]
+pub async fn lookup(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(ip): Path<IpAddr>,
+    Query(ParamLookup { vrf }): Query<ParamLookup>,
+) -> Result<QueryResult<Lookup>, ResponseError> {
+    let state = state.lock().await;
+
+    let mut networks = state.get::<Network>(None).await?;
+
+    // The nil uuid is the global routing table (networks without vrf)
+    if let Some(vrf) = vrf {
+        let vrf = Some(vrf).filter(|x| !x.is_nil());
+        networks.retain(|x| x.vrf == vrf);
+    }
+
+    let network = match network::longest_matches(&networks, &ip)[..] {
+        [network] => network.clone(),
+        [] => {
+            return Err(ResponseError::builder()
+                .title("Network not found".to_string())
+                .detail(format!(
+                    "There isn't any network that contains the ip {}",
+                    ip
+                ))
+                .status(StatusCode::NOT_FOUND)
+                .instance(uri.to_string())
+                .build())
+        }
+        ref best => {
+            return Err(ResponseError::builder()
+                .title("Ambiguous lookup".to_string())
+                .detail(format!(
+                    "The ip {} belongs to {} networks in different vrfs, use the vrf parameter",
+                    ip,
+                    best.len()
+                ))
+                .status(StatusCode::CONFLICT)
+                .instance(uri.to_string())
+                .build())
+        }
+    };
+
+    let mut ancestors = Vec::new();
+    let mut father = network.father;
+    while let Some(id) = father {
+        let Some(tmp) = networks.iter().find(|x| x.id == id) else {
+            break;
+        };
+        father = tmp.father;
+        ancestors.push(tmp.clone());
+    }
+
+    let tagged = std::iter::once(&network)
+        .chain(ancestors.iter())
+        .find(|x| x.vlan.is_some());
+    let vid = tagged.and_then(|x| x.vlan);
+
+    let vlan = match tagged.and_then(|x| x.vlan_id) {
+        Some(id) => state
+            .get::<VlanEntry>(Some(HashMap::from([("id", id.into())])))
+            .await
+            .ok()
+            .and_then(|x| x.into_iter().next()),
+        None => None,
+    };
+
+    let vrf = match network.vrf {
+        Some(id) => state
+            .get::<Vrf>(Some(HashMap::from([("id", id.into())])))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop()),
+        None => None,
+    };
+
+    let device = state
+        .get::<Device>(Some(HashMap::from([
+            ("ip", ip.into()),
+            ("network_id", network.id.into()),
+        ])))
+        .await
+        .ok()
+        .and_then(|mut x| x.pop())
+        .map(|mut x| {
+            if claim.role != Role::Admin {
+                x.credential = None;
+            }
+            x
+        });
+
+    let services = state
+        .get::<Service>(Some(HashMap::from([
+            ("ip", ip.into()),
+            ("network_id", network.id.into()),
+        ])))
+        .await
+        .unwrap_or_default();
+
+    Ok(QueryResult::Select(vec![Lookup {
+        ip,
+        network,
+        ancestors,
+        vrf,
+        vid,
+        vlan,
+        device,
+        services,
+    }]))
+}
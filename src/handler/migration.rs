@@ -0,0 +1,715 @@
+use super::openapi::Failure;
+use super::*;
+use crate::database::Import;
+use crate::events::{self, EventKind};
+use crate::models::{
+    device::{Device, Status},
+    dns,
+    mac::MacAddr,
+    migration::{self, Export, Fields, Reference, Source},
+    network::{Network, Vlan},
+    office::Office,
+    vlan::VlanEntry,
+    vrf::Vrf,
+};
+use axum::response::{IntoResponse, Response};
+use bulk::place;
+use network::{boundaries, is_host};
+use query_params::ParamMerge;
+use response_error::Builder;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MigrationReport {
+    pub source: Source,
+    pub dry_run: bool,
+    pub committed: bool,
+    #[schema(value_type = BTreeMap<String, usize>)]
+    pub created: BTreeMap<&'static str, usize>,
+    /// Objects of the export that are already in the database, the devices are updated
+    #[schema(value_type = BTreeMap<String, usize>)]
+    pub existing: BTreeMap<&'static str, usize>,
+    /// Fields of the export that aren't imported and how many objects have them
+    pub unsupported: BTreeMap<String, usize>,
+    /// Objects and values of the export that are left out
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl MigrationReport {
+    fn unsupported(&mut self, section: &str, fields: &Fields) {
+        for field in fields.unsupported() {
+            *self
+                .unsupported
+                .entry(format!("{}.{}", section, field))
+                .or_default() += 1;
+        }
+    }
+
+    fn created(&mut self, kind: &'static str) {
+        *self.created.entry(kind).or_default() += 1;
+    }
+
+    fn existing(&mut self, kind: &'static str) {
+        *self.existing.entry(kind).or_default() += 1;
+    }
+}
+
+/// Imports the vrfs, sites (as offices), vlans, prefixes (as networks) and addresses (as
+/// devices) of a phpIPAM or NetBox export. What is already in the database is reused, the
+/// hierarchy of the networks is rebuilt from the prefixes and nothing is saved unless every
+/// object can be imported
+#[utoipa::path(
+    post,
+    operation_id = "migration_import",
+    path = "/import/{source}",
+    tag = "admin",
+    params(("source" = Source, Path, description = "The system the export comes from"), ParamMerge),
+    request_body(content = Object, description = "The sections vrfs, sites, vlans, prefixes and addresses, as csv or json"),
+    responses(
+        (status = 200, description = "What was imported", body = MigrationReport),
+        (status = 422, description = "The objects that can't be imported, nothing is imported", body = MigrationReport),
+        Failure
+    )
+)]
+pub async fn import(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(source): Path<Source>,
+    Query(ParamMerge { dry_run }): Query<ParamMerge>,
+    Json(export): Json<Export>,
+) -> Result<Response, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let read = |name: &str, section: &Option<Value>| {
+        section
+            .as_ref()
+            .map(|x| migration::rows(source, x))
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(|x| {
+                ResponseError::builder()
+                    .title("Invalid export".to_string())
+                    .detail(format!("The section {} can't be read: {}", name, x))
+                    .status(StatusCode::BAD_REQUEST)
+                    .instance(uri.to_string())
+                    .build()
+            })
+    };
+    let vrf_rows = read("vrfs", &export.vrfs)?;
+    let site_rows = read("sites", &export.sites)?;
+    let vlan_rows = read("vlans", &export.vlans)?;
+    let prefix_rows = read("prefixes", &export.prefixes)?;
+    let address_rows = read("addresses", &export.addresses)?;
+
+    let mut report = MigrationReport {
+        source,
+        dry_run,
+        committed: false,
+        created: BTreeMap::new(),
+        existing: BTreeMap::new(),
+        unsupported: BTreeMap::new(),
+        skipped: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    let state = state.lock().await;
+    let mut vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+    let mut offices = state.get::<Office>(None).await.unwrap_or_default();
+    let mut vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
+    let mut all = state.get::<Network>(None).await.unwrap_or_default();
+    let mut import = Import::default();
+
+    let mut vrf_ids = HashMap::new();
+    for mut row in vrf_rows {
+        let vrf = match row.vrf() {
+            Ok(e) => e,
+            Err(e) => {
+                report.errors.push(format!("vrfs: {}", e));
+                continue;
+            }
+        };
+        report.unsupported("vrfs", &row);
+
+        let id = match vrfs.iter().find(|x| x.name == vrf.name) {
+            Some(x) => {
+                report.existing("vrfs");
+                x.id
+            }
+            None => {
+                let rd = vrf.rd.filter(|rd| {
+                    let used = vrfs.iter().any(|x| x.rd.as_ref() == Some(rd));
+                    if used {
+                        report.skipped.push(format!(
+                            "vrfs {}: the rd {} is used by another vrf",
+                            vrf.name, rd
+                        ));
+                    }
+                    !used
+                });
+                let new = Vrf {
+                    id: Uuid::new_v4(),
+                    name: vrf.name,
+                    rd,
+                    description: vrf.description,
+                };
+                report.created("vrfs");
+                let id = new.id;
+                vrfs.push(new.clone());
+                import.vrfs.push(new);
+                id
+            }
+        };
+        if let Some(source_id) = vrf.id {
+            vrf_ids.insert(source_id, id);
+        }
+    }
+
+    let mut office_ids = HashMap::new();
+    for mut row in site_rows {
+        let site = match row.site() {
+            Ok(e) => e,
+            Err(e) => {
+                report.errors.push(format!("sites: {}", e));
+                continue;
+            }
+        };
+        report.unsupported("sites", &row);
+
+        let id = match offices.iter().find(|x| x.name == site.name) {
+            Some(x) => {
+                report.existing("sites");
+                x.id
+            }
+            None => {
+                let address = site.address.filter(|address| {
+                    let used = offices.iter().any(|x| x.address.as_ref() == Some(address));
+                    if used {
+                        report.skipped.push(format!(
+                            "sites {}: the address {} is used by another office",
+                            site.name, address
+                        ));
+                    }
+                    !used
+                });
+                let new = Office {
+                    id: Uuid::new_v4(),
+                    name: site.name,
+                    address,
+                    description: site.description,
+                };
+                report.created("sites");
+                let id = new.id;
+                offices.push(new.clone());
+                import.offices.push(new);
+                id
+            }
+        };
+        if let Some(source_id) = site.id {
+            office_ids.insert(source_id, id);
+        }
+    }
+
+    let mut vlan_ids = HashMap::new();
+    for mut row in vlan_rows {
+        let vlan = match row.vlan() {
+            Ok(e) => e,
+            Err(e) => {
+                report.errors.push(format!("vlans: {}", e));
+                continue;
+            }
+        };
+        report.unsupported("vlans", &row);
+
+        let vid = match Vlan::new(vlan.vid) {
+            Ok(e) => e,
+            Err(e) => {
+                report.errors.push(format!("vlans {}: {}", vlan.name, e));
+                continue;
+            }
+        };
+
+        let office_id = resolve(&vlan.site, &office_ids, |name| {
+            offices.iter().find(|x| x.name == name).map(|x| x.id)
+        });
+        if office_id.is_none() && !vlan.site.is_empty() {
+            report.skipped.push(format!(
+                "vlans {}: the site {} isn't in the export",
+                vlan.name, vlan.site
+            ));
+        }
+        // A vlan belongs to an office or to a group
+        let vlan_group = vlan.group.filter(|group| {
+            if office_id.is_some() {
+                report.skipped.push(format!(
+                    "vlans {}: the group {} is left out, the vlan is in a site",
+                    vlan.name, group
+                ));
+            }
+            office_id.is_none()
+        });
+
+        let same_scope = |x: &&VlanEntry| x.office_id == office_id && x.vlan_group == vlan_group;
+        let id = if let Some(existing) = vlans.iter().filter(same_scope).find(|x| x.vid == vid) {
+            report.existing("vlans");
+            existing.id
+        } else if vlans.iter().filter(same_scope).any(|x| x.name == vlan.name) {
+            report.errors.push(format!(
+                "vlans {}: another vlan has the name {}",
+                *vid, vlan.name
+            ));
+            continue;
+        } else {
+            let new = VlanEntry {
+                id: Uuid::new_v4(),
+                vid,
+                name: vlan.name.clone(),
+                description: vlan.description,
+                office_id,
+                vlan_group,
+            };
+            let id = new.id;
+            report.created("vlans");
+            vlans.push(new.clone());
+            import.vlans.push(new);
+            id
+        };
+        if let Some(source_id) = vlan.id {
+            vlan_ids.insert(source_id, (vid, id));
+        }
+    }
+
+    let mut prefixes = Vec::new();
+    for mut row in prefix_rows {
+        match row.prefix() {
+            Ok(Some(e)) => prefixes.push(e),
+            Ok(None) => report.skipped.push(format!(
+                "prefixes {}: the folders aren't imported",
+                row.get(&["id"]).unwrap_or_default()
+            )),
+            Err(e) => {
+                report.errors.push(format!("prefixes: {}", e));
+                continue;
+            }
+        }
+        report.unsupported("prefixes", &row);
+    }
+    // The fathers go before their children
+    prefixes.sort_by_key(|x| x.network.prefix_len());
+
+    let mut prefix_ids = HashMap::new();
+    let mut created = HashSet::new();
+    for prefix in prefixes {
+        let vrf = resolve(&prefix.vrf, &vrf_ids, |name| {
+            vrfs.iter().find(|x| x.name == name).map(|x| x.id)
+        });
+        if vrf.is_none() && !prefix.vrf.is_empty() {
+            report.errors.push(format!(
+                "prefixes {}: the vrf {} doesn't exist",
+                prefix.network, prefix.vrf
+            ));
+            continue;
+        }
+
+        // A bare vid is only linked to the entry that has it when no other scope has the same vid
+        let vlan = match prefix.vid {
+            Some(vid) => Vlan::new(vid)
+                .ok()
+                .map(|vid| (vid, VlanEntry::by_vid(&vlans, vid).map(|x| x.id))),
+            None => resolve(&prefix.vlan, &vlan_ids, |name| {
+                vlans.iter().find(|x| x.name == name).map(|x| (x.vid, x.id))
+            })
+            .map(|(vid, id)| (vid, Some(id))),
+        };
+        if vlan.is_none() && (prefix.vid.is_some() || !prefix.vlan.is_empty()) {
+            report.skipped.push(format!(
+                "prefixes {}: the vlan {} isn't in the export",
+                prefix.network, prefix.vlan
+            ));
+        }
+
+        if let Some(network) = all
+            .iter()
+            .find(|x| x.vrf == vrf && x.network == prefix.network)
+        {
+            report.existing("prefixes");
+            if let Some(source_id) = prefix.id {
+                prefix_ids.insert(source_id, network.id);
+            }
+            continue;
+        }
+
+        let mut network: Network = models_data_entry::Network {
+            network: prefix.network,
+            description: prefix.description,
+            vlan: vlan.map(|(vid, _)| vid),
+            vlan_id: vlan.and_then(|(_, id)| id),
+            father: None,
+            vrf,
+        }
+        .into();
+        if let Err(e) = place(&all, &mut network) {
+            report
+                .errors
+                .push(format!("prefixes {}: {}", prefix.network, e));
+            continue;
+        }
+
+        // Like a moved network, the new one takes its boundaries from every ancestor
+        if let Some(father) = all.iter().find(|x| Some(x.id) == network.father) {
+            let taken = boundaries(&network.network, &father.network);
+            import.count(&all, network.father, -taken, 0);
+        }
+
+        report.created("prefixes");
+        if let Some(source_id) = prefix.id {
+            prefix_ids.insert(source_id, network.id);
+        }
+        created.insert(network.id);
+        all.push(network.clone());
+        import.networks.push(network);
+    }
+
+    let mut seen = HashSet::new();
+    for mut row in address_rows {
+        let address = match row.address() {
+            Ok(e) => e,
+            Err(e) => {
+                report.errors.push(format!("addresses: {}", e));
+                continue;
+            }
+        };
+        report.unsupported("addresses", &row);
+
+        let vrf = resolve(&address.vrf, &vrf_ids, |name| {
+            vrfs.iter().find(|x| x.name == name).map(|x| x.id)
+        });
+        let network = match &address.subnet {
+            Some(id) => prefix_ids
+                .get(id)
+                .and_then(|id| all.iter().find(|x| x.id == *id)),
+            None => all
+                .iter()
+                .filter(|x| x.vrf == vrf && x.network.contains(&address.ip))
+                .max_by_key(|x| x.network.prefix_len()),
+        };
+        let Some(network) = network else {
+            report.errors.push(format!(
+                "addresses {}: there isn't any prefix for the address",
+                address.ip
+            ));
+            continue;
+        };
+
+        if !is_host(&network.network, &address.ip) {
+            report.errors.push(format!(
+                "addresses {}: isn't a host of {}",
+                address.ip, network.network
+            ));
+            continue;
+        }
+
+        if !seen.insert((address.ip, network.id)) {
+            report.errors.push(format!(
+                "addresses {}: is repeated in {}",
+                address.ip, network.network
+            ));
+            continue;
+        }
+
+        let current = if created.contains(&network.id) {
+            None
+        } else {
+            state
+                .get::<Device>(Some(HashMap::from([
+                    ("ip", address.ip.into()),
+                    ("network_id", network.id.into()),
+                ])))
+                .await
+                .ok()
+                .map(|mut x| x.remove(0))
+        };
+
+        let mut device = current.clone().unwrap_or(Device {
+            ip: address.ip,
+            description: None,
+            location: None,
+            rack_unit: None,
+            rack_height: None,
+            status: Status::default(),
+            network_id: network.id,
+            credential: None,
+            hostname: None,
+            fqdn: None,
+            mac: None,
+            interface_id: None,
+            last_seen: None,
+        });
+
+        if let Some(status) = address.status {
+            device.status = status;
+        }
+
+        if let Some(description) = address.description {
+            device.description = Some(description);
+        }
+
+        // The names of the sources can be a hostname or a fqdn
+        if let Some(name) = address.dns_name.as_deref().map(dns::normalize) {
+            if name.contains('.') && dns::is_domain(&name) {
+                device.fqdn = Some(name);
+            } else if dns::is_label(&name) {
+                device.hostname = Some(name);
+            } else {
+                report.skipped.push(format!(
+                    "addresses {}: the name {} isn't valid",
+                    address.ip, name
+                ));
+            }
+        }
+
+        if let Some(mac) = address.mac {
+            match mac.parse::<MacAddr>() {
+                Ok(e) => device.mac = Some(e),
+                Err(_) => report.skipped.push(format!(
+                    "addresses {}: the mac {} isn't valid",
+                    address.ip, mac
+                )),
+            }
+        }
+
+        // A taken address is counted by the network and its ancestors, like in a bulk import
+        let used = device.is_taken() as i64 - current.as_ref().is_some_and(Device::is_taken) as i64;
+        if used != 0 {
+            import.count(&all, Some(network.id), 0, used);
+        }
+
+        if current.is_some() {
+            report.existing("addresses");
+        } else {
+            report.created("addresses");
+        }
+        import.devices.push(device);
+    }
+
+    let status = if report.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    if !report.errors.is_empty() || dry_run {
+        return Ok((status, Json(report)).into_response());
+    }
+
+    let networks = import.networks.clone();
+    let changed = import
+        .devices
+        .iter()
+        .map(|x| x.network_id)
+        .collect::<HashSet<_>>();
+
+    state.import(import).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })?;
+    report.committed = true;
+    tracing::info!("Imported from {:?}: {:?}", source, report.created);
+
+    for network in &networks {
+        events::emit(EventKind::NetworkCreated, network);
+    }
+    for id in changed {
+        network::emit_updated(&state, id).await;
+    }
+
+    Ok((status, Json(report)).into_response())
+}
+
+/// The object of the database of a reference of the export, by the id it has in the export
+/// or by its name
+fn resolve<T, F>(reference: &Reference, ids: &HashMap<String, T>, by_name: F) -> Option<T>
+where
+    T: Copy,
+    F: Fn(&str) -> Option<T>,
+{
+    reference
+        .id
+        .as_ref()
+        .and_then(|x| ids.get(x).copied())
+        .or_else(|| reference.name.as_deref().and_then(by_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::fixture::{admin, counters, get_network, network, state};
+    use serde_json::json;
+    use std::net::IpAddr;
+
+    /// Imports the export and returns the report
+    async fn run(state: &RepositoryType, source: Source, export: Value) -> Value {
+        let resp = import(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/import"),
+            Path(source),
+            Query(ParamMerge { dry_run: false }),
+            Json(serde_json::from_value(export).unwrap()),
+        )
+        .await
+        .unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn import_a_phpipam_export() {
+        let state = state().await;
+        let export = json!({
+            "vrfs": {"code": 200, "success": true, "data": [
+                {"vrfId": "1", "name": "prod", "rd": "65000:1", "description": "Production"}
+            ]},
+            "locations": {"code": 200, "success": true, "data": [
+                {"id": "1", "name": "HQ", "address": "Main street 1", "lat": null}
+            ]},
+            "vlans": {"code": 200, "success": true, "data": [
+                {"vlanId": "3", "domainId": "1", "name": "servers", "number": "10", "description": null}
+            ]},
+            "subnets": {"code": 200, "success": true, "data": [
+                {"id": "7", "subnet": "10.1.0.0", "mask": "16", "sectionId": "1", "description": "Campus",
+                 "vrfId": "1", "masterSubnetId": "0", "vlanId": "0", "isFolder": "0"},
+                {"id": "8", "subnet": "10.1.1.0", "mask": "24", "sectionId": "1", "description": "Servers",
+                 "vrfId": "1", "masterSubnetId": "7", "vlanId": "3", "isFolder": "0"},
+                {"id": "10", "subnet": "10.1.1.192", "mask": "26", "sectionId": "1", "description": null,
+                 "vrfId": "1", "masterSubnetId": "8", "vlanId": "0", "isFolder": "0"},
+                {"id": "9", "subnet": null, "mask": null, "sectionId": "1", "description": "Branches",
+                 "isFolder": "1"}
+            ]},
+            "addresses": {"code": 200, "success": true, "data": [
+                {"id": "1", "subnetId": "8", "ip": "10.1.1.10", "hostname": "web01.example.com",
+                 "mac": "00:11:22:33:44:55", "tag": "2", "description": "Web"},
+                {"id": "2", "subnetId": "8", "ip": "10.1.1.11", "hostname": "web02", "tag": "3"},
+                {"id": "3", "subnetId": "7", "ip": "10.1.2.1", "tag": "1"},
+                {"id": "4", "subnetId": "7", "ip": "10.1.2.2", "tag": "4"}
+            ]}
+        });
+
+        let report = run(&state, Source::PhpIpam, export).await;
+        assert_eq!(report["errors"], json!([]));
+        assert_eq!(report["committed"], json!(true));
+        assert_eq!(
+            report["created"],
+            json!({"addresses": 4, "prefixes": 3, "sites": 1, "vlans": 1, "vrfs": 1})
+        );
+
+        let vrf = state.lock().await.get::<Vrf>(None).await.unwrap().remove(0);
+        let servers = get_network(&state, "10.1.1.0/24").await;
+        assert_eq!(servers.vrf, Some(vrf.id));
+        assert_eq!(
+            servers.father,
+            Some(get_network(&state, "10.1.0.0/16").await.id)
+        );
+        assert_eq!(servers.vlan.map(|x| *x), Some(10));
+
+        let device = state
+            .lock()
+            .await
+            .get::<Device>(Some(HashMap::from([(
+                "ip",
+                "10.1.1.10".parse::<IpAddr>().unwrap().into(),
+            )])))
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(device.status, Status::Online);
+        assert_eq!(device.fqdn.as_deref(), Some("web01.example.com"));
+        assert!(device.mac.is_some());
+
+        // The addresses that aren't unknown are used by their network and its ancestors, the
+        // /26 only takes 10.1.1.192 because 10.1.1.255 is the broadcast of the /24
+        assert_eq!(counters(&state, "10.1.0.0/16").await, (65531, 3, 65528));
+        assert_eq!(counters(&state, "10.1.1.0/24").await, (253, 2, 251));
+    }
+
+    #[tokio::test]
+    async fn import_a_netbox_export_into_existing_networks() {
+        let state = state().await;
+        state
+            .lock()
+            .await
+            .insert(vec![network("192.168.0.0/16")])
+            .await
+            .unwrap();
+
+        let export = |status: &str| {
+            json!({
+                "sites": {"count": 1, "next": null, "previous": null, "results": [
+                    {"id": 1, "url": "http://netbox/api/dcim/sites/1/", "name": "DC",
+                     "physical_address": "Rack street 2", "status": {"value": "active", "label": "Active"}}
+                ]},
+                "vlans": {"count": 1, "next": null, "previous": null, "results": [
+                    {"id": 5, "vid": 100, "name": "mgmt", "site": {"id": 1, "name": "DC"}, "group": null}
+                ]},
+                "prefixes": {"count": 2, "next": null, "previous": null, "results": [
+                    {"id": 1, "prefix": "192.168.0.0/16", "vrf": null, "vlan": null, "description": ""},
+                    {"id": 2, "prefix": "192.168.10.0/24", "vrf": null,
+                     "vlan": {"id": 5, "vid": 100, "name": "mgmt"}, "description": "Management",
+                     "_depth": 1, "children": 0}
+                ]},
+                "ip-addresses": {"count": 2, "next": null, "previous": null, "results": [
+                    {"id": 1, "address": "192.168.10.5/24", "vrf": null,
+                     "status": {"value": status, "label": status}, "dns_name": "db01"},
+                    {"id": 2, "address": "192.168.20.1/16", "vrf": null,
+                     "status": {"value": "reserved", "label": "Reserved"}, "dns_name": ""}
+                ]}
+            })
+        };
+
+        let report = run(&state, Source::NetBox, export("active")).await;
+        assert_eq!(report["errors"], json!([]));
+        assert_eq!(report["existing"], json!({"prefixes": 1}));
+        assert_eq!(
+            report["created"],
+            json!({"addresses": 2, "prefixes": 1, "sites": 1, "vlans": 1})
+        );
+
+        let mgmt = get_network(&state, "192.168.10.0/24").await;
+        assert_eq!(
+            mgmt.father,
+            Some(get_network(&state, "192.168.0.0/16").await.id)
+        );
+        assert!(mgmt.vlan_id.is_some());
+
+        assert_eq!(counters(&state, "192.168.0.0/16").await, (65532, 2, 65530));
+        assert_eq!(counters(&state, "192.168.10.0/24").await, (254, 1, 253));
+
+        // Like a device that stops answering a ping, a deprecated address is still used
+        let report = run(&state, Source::NetBox, export("deprecated")).await;
+        assert_eq!(report["errors"], json!([]));
+        assert_eq!(report["existing"]["addresses"], json!(2));
+
+        assert_eq!(counters(&state, "192.168.0.0/16").await, (65532, 2, 65530));
+        assert_eq!(counters(&state, "192.168.10.0/24").await, (254, 1, 253));
+
+        // An address handed out by dhcp is unknown and gives it back
+        let report = run(&state, Source::NetBox, export("dhcp")).await;
+        assert_eq!(report["errors"], json!([]));
+
+        assert_eq!(counters(&state, "192.168.0.0/16").await, (65532, 1, 65531));
+        assert_eq!(counters(&state, "192.168.10.0/24").await, (254, 0, 254));
+    }
+}
@@ -1,12 +1,29 @@
+pub mod admin;
+pub mod asset;
 pub mod auth;
+pub mod bulk;
 pub mod device;
+pub mod dhcp;
 pub mod error;
 pub mod http;
+pub mod location;
+pub mod lookup;
+pub mod migration;
 mod models_data_entry;
+pub mod netbox;
 pub mod network;
+pub mod office;
+pub mod openapi;
 mod query_params;
+pub mod search;
 pub mod service;
 pub mod services;
+pub mod sse;
+pub mod threshold;
+pub mod vlan;
+pub mod vrf;
+pub mod webhook;
+pub mod zone;
 
 use crate::database::{
     repository::{QueryResult, Repository},
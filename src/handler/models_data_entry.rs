@@ -1,8 +1,9 @@
-use crate::models::{device, network};
+use crate::models::{asset, device, dhcp, location, mac, network, office, vlan, vrf, webhook};
 use ipnet::IpNet;
 use libipam::type_net::host_count::{HostCount, Prefix};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -11,12 +12,16 @@ pub struct User {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(as = NewNetwork)]
 pub struct Network {
+    #[schema(value_type = String)]
     pub network: IpNet,
     pub description: Option<String>,
     pub vlan: Option<network::Vlan>,
+    pub vlan_id: Option<uuid::Uuid>,
     pub father: Option<uuid::Uuid>,
+    pub vrf: Option<uuid::Uuid>,
 }
 
 impl From<Network> for network::Network {
@@ -36,19 +41,79 @@ impl From<Network> for network::Network {
             available: HostCount::new(Prefix::from(ip)),
             used: 0.into(),
             vlan: value.vlan,
+            vlan_id: value.vlan_id,
             father: value.father,
+            vrf: value.vrf,
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct NetworkMerge {
+    pub networks: Vec<Uuid>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct NetworkMove {
+    pub father: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(as = NewThreshold)]
+pub struct Threshold {
+    pub warning: u16,
+    pub critical: u16,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(as = NewDnsDomain)]
+pub struct DnsDomain {
+    pub domain: String,
+    pub ttl: Option<u32>,
+    pub nameserver: String,
+    pub hostmaster: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(as = NewDhcpScope)]
+pub struct DhcpScope {
+    pub pools: Vec<dhcp::Pool>,
+    #[schema(value_type = Option<String>)]
+    pub gateway: Option<IpAddr>,
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub dns_servers: Vec<IpAddr>,
+    pub lease_time: Option<u32>,
+    #[serde(default)]
+    pub options: Vec<dhcp::DhcpOption>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[schema(as = NewDhcpReservation)]
+pub struct DhcpReservation {
+    #[schema(value_type = String)]
+    pub ip: IpAddr,
+    pub mac: mac::MacAddr,
+    pub hostname: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewDevice)]
 pub struct Device {
+    #[schema(value_type = String)]
     pub ip: IpAddr,
     pub description: Option<String>,
     pub location: Option<Uuid>,
+    pub rack_unit: Option<u16>,
+    pub rack_height: Option<u16>,
     pub status: Option<device::Status>,
     pub network_id: uuid::Uuid,
     pub credential: Option<device::Credential>,
+    pub hostname: Option<String>,
+    pub fqdn: Option<String>,
+    pub mac: Option<mac::MacAddr>,
+    pub interface_id: Option<Uuid>,
 }
 
 impl From<Device> for device::Device {
@@ -58,8 +123,122 @@ impl From<Device> for device::Device {
             ip: value.ip,
             description: value.description,
             location: value.location,
+            rack_unit: value.rack_unit,
+            rack_height: value.rack_height,
             network_id: value.network_id,
             credential: value.credential,
+            hostname: value.hostname,
+            fqdn: value.fqdn,
+            mac: value.mac,
+            interface_id: value.interface_id,
+            last_seen: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewAsset)]
+pub struct Asset {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl From<Asset> for asset::Asset {
+    fn from(value: Asset) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            description: value.description,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewInterface)]
+pub struct Interface {
+    pub name: String,
+    pub mac: Option<mac::MacAddr>,
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewLocation)]
+pub struct Location {
+    #[serde(rename = "type")]
+    pub location_type: location::LocationType,
+    pub label: String,
+    pub units: Option<u16>,
+    pub office_id: Option<Uuid>,
+}
+
+impl From<Location> for location::Location {
+    fn from(value: Location) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            location_type: value.location_type,
+            label: value.label,
+            units: value.units,
+            office_id: value.office_id,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewOffice)]
+pub struct Office {
+    pub name: String,
+    pub address: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<Office> for office::Office {
+    fn from(value: Office) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            address: value.address,
+            description: value.description,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewVlan)]
+pub struct Vlan {
+    pub vid: network::Vlan,
+    pub name: String,
+    pub description: Option<String>,
+    pub office_id: Option<Uuid>,
+    pub vlan_group: Option<String>,
+}
+
+impl From<Vlan> for vlan::VlanEntry {
+    fn from(value: Vlan) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            vid: value.vid,
+            name: value.name,
+            description: value.description,
+            office_id: value.office_id,
+            vlan_group: value.vlan_group,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewVrf)]
+pub struct Vrf {
+    pub name: String,
+    pub rd: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<Vrf> for vrf::Vrf {
+    fn from(value: Vrf) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            rd: value.rd,
+            description: value.description,
         }
     }
 }
@@ -76,9 +255,16 @@ pub fn create_all_devices(network: IpNet, id: Uuid) -> Option<Vec<device::Device
             ip,
             description: None,
             location: None,
+            rack_unit: None,
+            rack_height: None,
             status: device::Status::default(),
             network_id: id,
             credential: None,
+            hostname: None,
+            fqdn: None,
+            mac: None,
+            interface_id: None,
+            last_seen: None,
         });
     }
 
@@ -88,3 +274,24 @@ pub fn create_all_devices(network: IpNet, id: Uuid) -> Option<Vec<device::Device
         None
     }
 }
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[schema(as = NewWebhook)]
+pub struct Webhook {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl From<Webhook> for webhook::Webhook {
+    fn from(value: Webhook) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            url: value.url,
+            secret: value.secret,
+            events: value.events,
+            description: value.description,
+        }
+    }
+}
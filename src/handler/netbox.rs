@@ -0,0 +1,495 @@
+use super::*;
+use crate::models::{
+    device::{Device, Status},
+    dns::{self, DnsDomain},
+    netbox::{self, *},
+    network::Network,
+    office::Office,
+    vlan::VlanEntry,
+    vrf::Vrf,
+};
+use axum::{
+    extract::{OriginalUri, Request},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use libipam::authentication;
+use query_params::ParamNetBox;
+use std::net::IpAddr;
+
+/// The clients of NetBox send `Authorization: Token <token>`, the session of the browser
+/// is also accepted
+pub async fn verify_token(
+    libipam::Token(token): libipam::Token,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let header = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.strip_prefix("Token ").or(x.strip_prefix("Bearer ")))
+        .map(|x| x.trim().to_string());
+
+    match header
+        .or(token.ok())
+        .map(authentication::verify_token::<Claims, _>)
+    {
+        Some(Ok(claims)) => {
+            req.extensions_mut().insert(claims);
+            next.run(req).await
+        }
+        _ => (
+            StatusCode::FORBIDDEN,
+            Json(Detail {
+                detail: "Invalid token.",
+            }),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn prefixes(
+    State(state): State<RepositoryType>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<ParamNetBox>,
+) -> Json<Page<Prefix>> {
+    let base = base(&headers);
+    let resp = get_prefixes(&*state.lock().await, &base, &params).await;
+    Json(page(resp, &base, &uri, &params))
+}
+
+pub async fn prefix(
+    State(state): State<RepositoryType>,
+    headers: HeaderMap,
+    Path(id): Path<u32>,
+) -> Response {
+    let params = ParamNetBox {
+        id: Some(id),
+        ..Default::default()
+    };
+    detail(get_prefixes(&*state.lock().await, &base(&headers), &params).await)
+}
+
+pub async fn ip_addresses(
+    State(state): State<RepositoryType>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<ParamNetBox>,
+) -> Json<Page<IpAddress>> {
+    let base = base(&headers);
+    let resp = get_addresses(&*state.lock().await, &base, &params).await;
+    Json(page(resp, &base, &uri, &params))
+}
+
+pub async fn ip_address(
+    State(state): State<RepositoryType>,
+    headers: HeaderMap,
+    Path(id): Path<u32>,
+) -> Response {
+    let params = ParamNetBox {
+        id: Some(id),
+        ..Default::default()
+    };
+    detail(get_addresses(&*state.lock().await, &base(&headers), &params).await)
+}
+
+pub async fn vlans(
+    State(state): State<RepositoryType>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<ParamNetBox>,
+) -> Json<Page<netbox::Vlan>> {
+    let base = base(&headers);
+    let resp = get_vlans(&*state.lock().await, &base, &params).await;
+    Json(page(resp, &base, &uri, &params))
+}
+
+pub async fn vlan(
+    State(state): State<RepositoryType>,
+    headers: HeaderMap,
+    Path(id): Path<u32>,
+) -> Response {
+    let params = ParamNetBox {
+        id: Some(id),
+        ..Default::default()
+    };
+    detail(get_vlans(&*state.lock().await, &base(&headers), &params).await)
+}
+
+async fn get_prefixes(state: &SqliteRepository, base: &str, params: &ParamNetBox) -> Vec<Prefix> {
+    let mut networks = state.get::<Network>(None).await.unwrap_or_default();
+    let vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+    let vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
+    networks.sort_by_key(|x| (x.vrf, x.network));
+
+    let fathers = networks
+        .iter()
+        .map(|x| (x.id, x.father))
+        .collect::<HashMap<_, _>>();
+    let mut depth = HashMap::<Uuid, usize>::new();
+    let mut children = HashMap::<Uuid, usize>::new();
+    for network in &networks {
+        let mut father = network.father;
+        while let Some(id) = father {
+            *depth.entry(network.id).or_default() += 1;
+            *children.entry(id).or_default() += 1;
+            father = fathers.get(&id).copied().flatten();
+        }
+    }
+
+    networks
+        .iter()
+        .filter(|x| {
+            params.id.is_none_or(|id| id == netbox::id(x.id.as_bytes()))
+                && params.prefix.as_deref().is_none_or(|prefix| {
+                    prefix
+                        .parse::<IpNet>()
+                        .is_ok_and(|prefix| prefix.trunc() == x.network)
+                })
+                && params.within.as_deref().is_none_or(|within| {
+                    within
+                        .parse::<IpNet>()
+                        .is_ok_and(|within| within != x.network && within.contains(&x.network))
+                })
+                && params
+                    .family
+                    .is_none_or(|family| family == family_of(&x.network.addr()))
+                && params
+                    .vlan_vid
+                    .is_none_or(|vid| x.vlan.is_some_and(|vlan| *vlan == vid))
+                && params
+                    .status
+                    .as_deref()
+                    .is_none_or(|status| status == ACTIVE.value)
+                && params.q.as_deref().is_none_or(|q| {
+                    contains(&x.network.to_string(), q) || contains_opt(&x.description, q)
+                })
+                && in_vrf(params, x.vrf, &vrfs)
+        })
+        .map(|x| {
+            let id = netbox::id(x.id.as_bytes());
+            Prefix {
+                id,
+                url: format!("{}/api/ipam/prefixes/{}/", base, id),
+                display: x.network.to_string(),
+                family: Family::of(x.network.addr().is_ipv4()),
+                prefix: x.network.to_string(),
+                vrf: x
+                    .vrf
+                    .and_then(|id| vrfs.iter().find(|vrf| vrf.id == id))
+                    .map(|vrf| nested_vrf(base, vrf)),
+                vlan: x.vlan.map(|vid| nested_vlan(base, &vlans, *vid, x.vlan_id)),
+                status: ACTIVE,
+                is_pool: false,
+                mark_utilized: false,
+                description: x.description.clone().unwrap_or_default(),
+                children: children.get(&x.id).copied().unwrap_or_default(),
+                depth: depth.get(&x.id).copied().unwrap_or_default(),
+                unsupported: Default::default(),
+            }
+        })
+        .collect()
+}
+
+async fn get_addresses(
+    state: &SqliteRepository,
+    base: &str,
+    params: &ParamNetBox,
+) -> Vec<IpAddress> {
+    let devices = state.get::<Device>(None).await.unwrap_or_default();
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    let vrfs = state.get::<Vrf>(None).await.unwrap_or_default();
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+
+    let address = params
+        .address
+        .as_deref()
+        .map(|x| x.split('/').next().unwrap_or_default().parse::<IpAddr>());
+    let parent = params.parent.as_deref().map(str::parse::<IpNet>);
+
+    // The addresses that nobody has touched are only placeholders of the free space
+    let mut devices = devices
+        .into_iter()
+        .filter(|x| !x.is_placeholder())
+        .filter_map(|x| {
+            let network = networks.iter().find(|n| n.id == x.network_id)?;
+            Some((x, network))
+        })
+        .collect::<Vec<_>>();
+    devices.sort_by_key(|(x, network)| (network.vrf, x.ip));
+
+    devices
+        .into_iter()
+        .map(|(x, network)| {
+            let mut key = network.id.as_bytes().to_vec();
+            key.extend(x.ip.to_string().as_bytes());
+            let domain =
+                dns::domain_of(&networks, &domains, Some(network.id)).map(|x| x.domain.as_str());
+            (
+                netbox::id(&key),
+                x.dns_name(domain).unwrap_or_default(),
+                x,
+                network,
+            )
+        })
+        .filter(|(id, dns_name, x, network)| {
+            params.id.is_none_or(|y| y == *id)
+                && address
+                    .as_ref()
+                    .is_none_or(|address| address.as_ref().is_ok_and(|ip| *ip == x.ip))
+                && parent
+                    .as_ref()
+                    .is_none_or(|parent| parent.as_ref().is_ok_and(|net| net.contains(&x.ip)))
+                && params
+                    .family
+                    .is_none_or(|family| family == family_of(&x.ip))
+                && params
+                    .status
+                    .as_deref()
+                    .is_none_or(|status| status == status_of(&x.status).value)
+                && params
+                    .dns_name
+                    .as_deref()
+                    .is_none_or(|name| name.eq_ignore_ascii_case(dns_name))
+                && params.q.as_deref().is_none_or(|q| {
+                    contains(&x.ip.to_string(), q)
+                        || contains(dns_name, q)
+                        || contains_opt(&x.description, q)
+                })
+                && in_vrf(params, network.vrf, &vrfs)
+        })
+        .map(|(id, dns_name, x, network)| {
+            let address = format!("{}/{}", x.ip, network.network.prefix_len());
+            IpAddress {
+                id,
+                url: format!("{}/api/ipam/ip-addresses/{}/", base, id),
+                display: address.clone(),
+                family: Family::of(x.ip.is_ipv4()),
+                address,
+                vrf: network
+                    .vrf
+                    .and_then(|id| vrfs.iter().find(|vrf| vrf.id == id))
+                    .map(|vrf| nested_vrf(base, vrf)),
+                status: status_of(&x.status),
+                assigned_object_type: None,
+                assigned_object_id: None,
+                assigned_object: None,
+                nat_inside: None,
+                nat_outside: Vec::new(),
+                dns_name,
+                description: x.description.unwrap_or_default(),
+                unsupported: Default::default(),
+            }
+        })
+        .collect()
+}
+
+async fn get_vlans(
+    state: &SqliteRepository,
+    base: &str,
+    params: &ParamNetBox,
+) -> Vec<netbox::Vlan> {
+    let mut vlans = state.get::<VlanEntry>(None).await.unwrap_or_default();
+    let offices = state.get::<Office>(None).await.unwrap_or_default();
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    vlans.sort_by(|a, b| {
+        (a.office_id, &a.vlan_group, *a.vid).cmp(&(b.office_id, &b.vlan_group, *b.vid))
+    });
+
+    vlans
+        .into_iter()
+        .map(|x| {
+            let site = x
+                .office_id
+                .and_then(|id| offices.iter().find(|office| office.id == id))
+                .map(|office| {
+                    let id = netbox::id(office.id.as_bytes());
+                    NestedObject {
+                        id,
+                        url: format!("{}/api/dcim/sites/{}/", base, id),
+                        display: office.name.clone(),
+                        name: office.name.clone(),
+                        slug: slug(&office.name),
+                    }
+                });
+            let group = x.vlan_group.as_ref().map(|group| {
+                let id = netbox::id(group.as_bytes());
+                NestedObject {
+                    id,
+                    url: format!("{}/api/ipam/vlan-groups/{}/", base, id),
+                    display: group.clone(),
+                    name: group.clone(),
+                    slug: slug(group),
+                }
+            });
+            (x, site, group)
+        })
+        .filter(|(x, site, group)| {
+            params.id.is_none_or(|id| id == netbox::id(x.id.as_bytes()))
+                && params.vid.is_none_or(|vid| vid == *x.vid)
+                && params.name.as_deref().is_none_or(|name| name == x.name)
+                && params
+                    .site_id
+                    .is_none_or(|id| site.as_ref().is_some_and(|site| site.id == id))
+                && params
+                    .site
+                    .as_deref()
+                    .is_none_or(|name| site.as_ref().is_some_and(|site| matches(site, name)))
+                && params
+                    .group
+                    .as_deref()
+                    .is_none_or(|name| group.as_ref().is_some_and(|group| matches(group, name)))
+                && params
+                    .status
+                    .as_deref()
+                    .is_none_or(|status| status == ACTIVE.value)
+                && params.q.as_deref().is_none_or(|q| {
+                    contains(&x.name, q)
+                        || contains(&x.vid.to_string(), q)
+                        || contains_opt(&x.description, q)
+                })
+        })
+        .map(|(x, site, group)| {
+            let id = netbox::id(x.id.as_bytes());
+            netbox::Vlan {
+                id,
+                url: format!("{}/api/ipam/vlans/{}/", base, id),
+                display: format!("{} ({})", x.name, *x.vid),
+                site,
+                group,
+                vid: *x.vid,
+                status: ACTIVE,
+                description: x.description.unwrap_or_default(),
+                prefix_count: networks
+                    .iter()
+                    .filter(|network| network.vlan_id == Some(x.id))
+                    .count(),
+                name: x.name,
+                unsupported: Default::default(),
+            }
+        })
+        .collect()
+}
+
+/// The scheme and host of the links, as the client sees them
+fn base(headers: &HeaderMap) -> String {
+    let header = |name| headers.get(name).and_then(|x| x.to_str().ok());
+    format!(
+        "{}://{}",
+        header("x-forwarded-proto").unwrap_or("http"),
+        header(header::HOST.as_str()).unwrap_or("localhost")
+    )
+}
+
+/// The links to the next and previous pages keep the filters of the query
+fn page<T>(results: Vec<T>, base: &str, uri: &Uri, params: &ParamNetBox) -> Page<T> {
+    let limit = match params.limit {
+        None => PAGE_SIZE,
+        Some(0) => MAX_PAGE_SIZE,
+        Some(limit) => limit.min(MAX_PAGE_SIZE),
+    };
+    let offset = params.offset.unwrap_or_default();
+    let count = results.len();
+
+    let filters = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|x| !x.is_empty() && !x.starts_with("limit=") && !x.starts_with("offset="))
+        .collect::<Vec<_>>();
+    let link = |offset: usize| {
+        let mut query = filters.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        query.push(format!("limit={}", limit));
+        if offset > 0 {
+            query.push(format!("offset={}", offset));
+        }
+        format!("{}{}?{}", base, uri.path(), query.join("&"))
+    };
+
+    Page {
+        count,
+        next: (offset + limit < count).then(|| link(offset + limit)),
+        previous: (offset > 0).then(|| link(offset.saturating_sub(limit))),
+        results: results.into_iter().skip(offset).take(limit).collect(),
+    }
+}
+
+fn detail<T: serde::Serialize>(mut resp: Vec<T>) -> Response {
+    match resp.pop() {
+        Some(x) => Json(x).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(Detail {
+                detail: "No matches the given query.",
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// `vrf_id=null` is the global table
+fn in_vrf(params: &ParamNetBox, vrf: Option<Uuid>, vrfs: &[Vrf]) -> bool {
+    let vrf = vrf.and_then(|id| vrfs.iter().find(|x| x.id == id));
+    params.vrf_id.as_deref().is_none_or(|id| match id {
+        "null" => vrf.is_none(),
+        id => vrf.is_some_and(|vrf| id.parse() == Ok(netbox::id(vrf.id.as_bytes()))),
+    }) && params.vrf.as_deref().is_none_or(|name| match name {
+        "null" => vrf.is_none(),
+        name => vrf.is_some_and(|vrf| vrf.name == name || vrf.rd.as_deref() == Some(name)),
+    })
+}
+
+fn nested_vrf(base: &str, vrf: &Vrf) -> NestedVrf {
+    let id = netbox::id(vrf.id.as_bytes());
+    NestedVrf {
+        id,
+        url: format!("{}/api/ipam/vrfs/{}/", base, id),
+        display: vrf.name.clone(),
+        name: vrf.name.clone(),
+        rd: vrf.rd.clone(),
+    }
+}
+
+/// A network without an entry of the registry only has the number of its vlan
+fn nested_vlan(base: &str, vlans: &[VlanEntry], vid: u16, vlan_id: Option<Uuid>) -> NestedVlan {
+    let entry = vlan_id.and_then(|id| vlans.iter().find(|x| x.id == id));
+    let (id, name) = match entry {
+        Some(x) => (netbox::id(x.id.as_bytes()), x.name.clone()),
+        None => (netbox::id(&vid.to_be_bytes()), format!("VLAN {}", vid)),
+    };
+    NestedVlan {
+        id,
+        url: format!("{}/api/ipam/vlans/{}/", base, id),
+        display: format!("{} ({})", name, vid),
+        vid,
+        name,
+    }
+}
+
+fn status_of(status: &Status) -> Choice {
+    match status {
+        Status::Reserved => RESERVED,
+        Status::Offline => DEPRECATED,
+        Status::Online | Status::Unknown => ACTIVE,
+    }
+}
+
+fn family_of(ip: &IpAddr) -> u8 {
+    Family::of(ip.is_ipv4()).value
+}
+
+fn matches(object: &NestedObject, name: &str) -> bool {
+    object.slug == name || object.name == name
+}
+
+fn contains(value: &str, q: &str) -> bool {
+    value.to_lowercase().contains(&q.to_lowercase())
+}
+
+fn contains_opt(value: &Option<String>, q: &str) -> bool {
+    value.as_deref().is_some_and(|x| contains(x, q))
+}
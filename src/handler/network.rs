@@ -1,11 +1,36 @@
 use super::*;
-use crate::models::{device::Device, network::*};
-use axum::http::Uri;
+use crate::database::{repository::error::RepositoryError, SqliteRepository};
+use crate::events::{self, EventKind};
+use crate::models::{
+    alert::Threshold,
+    device::{Device, Status},
+    dhcp::{DhcpReservation, DhcpScope, Pool},
+    dns::DnsDomain,
+    network::*,
+    service::Service,
+    vlan::VlanEntry,
+};
+use axum::{http::Uri, response::Response};
 use ipnet::IpNet;
 use libipam::{ipam_services::subnetting, type_net::host_count::HostCount};
-use query_params::ParamSubnetting;
+use openapi::{Affected, Failure, Insert, Select};
+use query_params::{ParamFather, ParamMerge, ParamNetworkGet, ParamNetworkUpdate, ParamSubnetting};
 use response_error::Builder;
+use serde::Serialize;
+use std::{collections::HashSet, net::IpAddr};
+use utoipa::ToSchema;
 
+#[utoipa::path(
+    post,
+    operation_id = "network_create",
+    path = "/network",
+    tag = "network",
+    request_body = models_data_entry::Network,
+    responses(
+        (status = 201, description = "The network created", body = Insert<Network>),
+        Failure
+    )
+)]
 pub async fn create(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -23,9 +48,141 @@ pub async fn create(
 
     let state = state.lock().await;
     tracing::info!("New network {:?}", netw);
-    Ok(state.insert::<Network>(vec![netw.into()]).await?)
+    let mut netw: Network = netw.into();
+    (netw.vlan, netw.vlan_id) = resolve_vlan(&state, netw.vlan, netw.vlan_id)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    if let Some(father) = netw.father {
+        let father = state
+            .get::<Network>(Some(HashMap::from([("id", father.into())])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+            .remove(0);
+
+        if !father.network.contains(&netw.network) {
+            return Err(ResponseError::builder()
+                .title("Invalid network".to_string())
+                .detail(format!(
+                    "The network {} don't belong to the network {}",
+                    netw.network, father.network
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .instance(uri.to_string())
+                .build());
+        }
+
+        netw.vrf = father.vrf;
+    }
+
+    verify_overlap(&state, netw.father, netw.vrf, &[netw.network], None)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let resp = state.insert::<Network>(vec![netw]).await?;
+    emit_created(&resp);
+
+    Ok(resp)
 }
 
+/// With `vlan_id` the vid is the one of the entry, a bare vid is linked to the entry that has it
+/// when no other scope has the same vid
+pub(super) async fn resolve_vlan(
+    state: &SqliteRepository,
+    vlan: Option<Vlan>,
+    vlan_id: Option<Uuid>,
+) -> Result<(Option<Vlan>, Option<Uuid>), ResponseError> {
+    match (vlan, vlan_id.filter(|x| !x.is_nil())) {
+        (vlan, Some(id)) => {
+            let entry = state
+                .get::<VlanEntry>(Some(HashMap::from([("id", id.into())])))
+                .await?
+                .remove(0);
+
+            if vlan.is_some_and(|x| x != entry.vid) {
+                return Err(ResponseError::builder()
+                    .title("Invalid vlan".to_string())
+                    .detail(format!(
+                        "The vlan {} has the vid {}",
+                        entry.name, *entry.vid
+                    ))
+                    .status(StatusCode::BAD_REQUEST)
+                    .build());
+            }
+
+            Ok((Some(entry.vid), Some(entry.id)))
+        }
+        (Some(vid), None) => {
+            let entries = state
+                .get::<VlanEntry>(Some(HashMap::from([("vid", vid.into())])))
+                .await
+                .unwrap_or_default();
+
+            Ok((Some(vid), VlanEntry::by_vid(&entries, vid).map(|x| x.id)))
+        }
+        (None, None) => Ok((None, None)),
+    }
+}
+
+/// The most specific network of every vrf that contains the ip
+pub(super) fn longest_matches<'a>(
+    networks: impl IntoIterator<Item = &'a Network>,
+    ip: &IpAddr,
+) -> Vec<&'a Network> {
+    let mut best = HashMap::<Option<Uuid>, &Network>::new();
+    for network in networks.into_iter().filter(|x| x.network.contains(ip)) {
+        let entry = best.entry(network.vrf).or_insert(network);
+        if network.network.prefix_len() > entry.network.prefix_len() {
+            *entry = network;
+        }
+    }
+
+    best.into_values().collect()
+}
+
+// Networks only have to be unique inside their VRF, so the same prefix can exist once per VRF
+pub(super) async fn verify_overlap(
+    state: &SqliteRepository,
+    father: Option<Uuid>,
+    vrf: Option<Uuid>,
+    networks: &[IpNet],
+    skip: Option<Uuid>,
+) -> Result<(), ResponseError> {
+    let siblings = state
+        .get::<Network>(Some(HashMap::from([
+            ("father", father.into()),
+            ("vrf_id", vrf.into()),
+        ])))
+        .await
+        .unwrap_or_default();
+
+    for sibling in siblings.into_iter().filter(|x| Some(x.id) != skip) {
+        if let Some(network) = networks.iter().find(|x| sibling.overlaps(x)) {
+            return Err(ResponseError::builder()
+                .title("Conflict".to_string())
+                .detail(format!(
+                    "The network {} overlaps with the network {}",
+                    network, sibling.network
+                ))
+                .status(StatusCode::CONFLICT)
+                .build());
+        }
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "network_get_one",
+    path = "/network/{id}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The network", body = Select<Network>),
+        Failure
+    )
+)]
 pub async fn get_one(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -44,13 +201,26 @@ pub async fn get_one(
         .into())
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "network_update",
+    path = "/network/{id}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network"), ParamNetworkUpdate),
+    request_body = UpdateNetwork,
+    responses(
+        (status = 200, description = "The rows updated, or how the devices move when the prefix changes", body = Select<Resize>),
+        Failure
+    )
+)]
 pub async fn update(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
     uri: Uri,
     Path(id): Path<Uuid>,
-    Json(network): Json<UpdateNetwork>,
-) -> Result<impl IntoResponse, ResponseError> {
+    Query(ParamNetworkUpdate { renumber, dry_run }): Query<ParamNetworkUpdate>,
+    Json(mut network): Json<UpdateNetwork>,
+) -> Result<Response, ResponseError> {
     if claim.role != Role::Admin {
         return Err(ResponseError::builder()
             .status(StatusCode::UNAUTHORIZED)
@@ -62,39 +232,437 @@ pub async fn update(
 
     let state = state.lock().await;
 
-    if network.network.is_some() {
+    if network.vlan.is_some() || network.vlan_id.is_some() {
+        (network.vlan, network.vlan_id) = resolve_vlan(&state, network.vlan, network.vlan_id)
+            .await
+            .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+    }
+
+    let Some(prefix) = network.network.take() else {
+        let resp = state
+            .update::<Network, _>(network, Some(HashMap::from([("id", id.into())])))
+            .await?;
+        if resp.rows_affected() > 0 {
+            emit_updated(&state, id).await;
+        }
+
+        return Ok(resp.into_response());
+    };
+
+    let mut resize = prepare_resize(&state, id, prefix, renumber)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+    if let Some(description) = network.description {
+        resize.network.description = Some(description).filter(|x| !x.is_empty());
+    }
+    if network.vlan.is_some() {
+        resize.network.vlan = network.vlan;
+        resize.network.vlan_id = network.vlan_id;
+    }
+
+    if dry_run {
+        return Ok(QueryResult::Select(vec![resize]).into_response());
+    }
+
+    tracing::info!(
+        "Resize network {} to {} ({} devices renumbered)",
+        id,
+        resize.network.network,
+        resize.devices.len()
+    );
+    state
+        .resize_network(
+            &resize.network,
+            &resize
+                .devices
+                .iter()
+                .map(|x| (x.from, x.to))
+                .collect::<Vec<_>>(),
+            &resize.removed,
+            resize.created.clone(),
+            &resize.ancestors,
+            resize.scope.as_ref(),
+        )
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    emit_updated(&state, id).await;
+    for dev in &resize.devices {
+        super::device::emit_device(&state, EventKind::DeviceUpdated, dev.to, id).await;
+    }
+
+    Ok(QueryResult::Select(vec![resize]).into_response())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Renumbered {
+    #[schema(value_type = String)]
+    pub from: IpAddr,
+    #[schema(value_type = String)]
+    pub to: IpAddr,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Resize {
+    pub network: Network,
+    pub devices: Vec<Renumbered>,
+    /// The placeholders of the old prefix, replaced by the ones of `created`
+    #[serde(skip)]
+    pub removed: Vec<IpAddr>,
+    #[serde(skip)]
+    pub created: Vec<Device>,
+    /// What every ancestor adds to its available and free addresses
+    #[serde(skip)]
+    pub ancestors: Vec<(Uuid, i64)>,
+    /// The dhcp scope with the pools and the gateway renumbered
+    #[serde(skip)]
+    pub scope: Option<DhcpScope>,
+}
+
+// Without renumber the devices keep their ip, so the new prefix has to contain all of them.
+// With renumber every device keeps its offset from the start of the network. The placeholders
+// (`Unknown` devices nobody filled in) aren't moved, they are created again for the new prefix
+async fn prepare_resize(
+    state: &SqliteRepository,
+    id: Uuid,
+    prefix: IpNet,
+    renumber: bool,
+) -> Result<Resize, ResponseError> {
+    let current = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await?
+        .remove(0);
+    let prefix = prefix.trunc();
+
+    if prefix == current.network || prefix.addr().is_ipv4() != current.network.addr().is_ipv4() {
         return Err(ResponseError::builder()
-            .title("Update not allowed".to_string())
-            .instance(uri.to_string())
-            .detail(
-                "The function update_network still doesn't support the update for network"
-                    .to_string(),
-            )
-            .status(StatusCode::NOT_IMPLEMENTED)
+            .title("Invalid network".to_string())
+            .detail(format!(
+                "The network {} can't be changed to {}",
+                current.network, prefix
+            ))
+            .status(StatusCode::BAD_REQUEST)
             .build());
     }
 
-    Ok(state
-        .update::<Network, _>(network, Some(HashMap::from([("id", id.into())])))
-        .await?)
+    let chain = ancestors(state, current.father).await?;
+    if let Some(father) = chain.first() {
+        if !father.network.contains(&prefix) {
+            return Err(ResponseError::builder()
+                .title("Invalid network".to_string())
+                .detail(format!(
+                    "The network {} don't belong to the network {}",
+                    prefix, father.network
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .build());
+        }
+    }
+
+    verify_overlap(state, current.father, current.vrf, &[prefix], Some(id)).await?;
+
+    let children = state
+        .get::<Network>(Some(HashMap::from([("father", id.into())])))
+        .await
+        .unwrap_or_default();
+
+    if let Some(child) = children
+        .iter()
+        .find(|x| renumber || !prefix.contains(&x.network))
+    {
+        return Err(ResponseError::builder()
+            .title("Conflict".to_string())
+            .detail(format!(
+                "The subnet {} doesn't fit in the network {}",
+                child.network, prefix
+            ))
+            .status(StatusCode::CONFLICT)
+            .build());
+    }
+
+    let reservations = state
+        .get::<DhcpReservation>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .unwrap_or_default();
+    // A device with services or a dhcp reservation isn't a placeholder
+    let mut referenced = state
+        .get::<Service>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| x.ip)
+        .collect::<HashSet<_>>();
+    referenced.extend(reservations.iter().map(|x| x.ip));
+
+    let mut devices = Vec::new();
+    let mut invalid = Vec::new();
+    let mut removed = Vec::new();
+    let mut taken = HashSet::new();
+    // The addresses the dhcp pools must leave out after the resize
+    let mut busy = Vec::new();
+    for dev in state
+        .get::<Device>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .unwrap_or_default()
+    {
+        if dev.is_placeholder() && !referenced.contains(&dev.ip) {
+            removed.push(dev.ip);
+            continue;
+        }
+
+        let to = if renumber {
+            renumber_ip(&current.network, &prefix, dev.ip)
+        } else {
+            Some(dev.ip)
+        };
+
+        match to.filter(|x| is_host(&prefix, x)) {
+            Some(to) => {
+                taken.insert(to);
+                if matches!(dev.status, Status::Reserved | Status::Online)
+                    || reservations.iter().any(|x| x.ip == dev.ip)
+                {
+                    busy.push(to);
+                }
+                if to != dev.ip {
+                    devices.push(Renumbered { from: dev.ip, to });
+                }
+            }
+            None => invalid.push(dev.ip.to_string()),
+        }
+    }
+
+    if !invalid.is_empty() {
+        return Err(ResponseError::builder()
+            .title("Conflict".to_string())
+            .detail(format!(
+                "The devices {} don't fit in the network {}",
+                invalid.join(", "),
+                prefix
+            ))
+            .status(StatusCode::CONFLICT)
+            .build());
+    }
+
+    // With renumber the pools and the gateway keep their offset like the devices
+    let shift = |ip: IpAddr| {
+        if renumber {
+            renumber_ip(&current.network, &prefix, ip).unwrap_or(ip)
+        } else {
+            ip
+        }
+    };
+    let scope = match state
+        .get::<DhcpScope>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+    {
+        Ok(mut scopes) => {
+            let scope = scopes.remove(0);
+            let scope = DhcpScope {
+                pools: scope
+                    .pools
+                    .iter()
+                    .map(|x| Pool {
+                        start: shift(x.start),
+                        end: shift(x.end),
+                    })
+                    .collect(),
+                gateway: scope.gateway.map(shift),
+                ..scope
+            };
+            super::dhcp::check_scope(&prefix, &scope, &busy).map_err(|x| x.build())?;
+            Some(scope).filter(|_| renumber)
+        }
+        Err(_) => None,
+    };
+
+    let created = if removed.is_empty() {
+        Vec::new()
+    } else {
+        models_data_entry::create_all_devices(prefix, id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|x| !taken.contains(&x.ip))
+            .collect()
+    };
+
+    let delta = chain.first().map_or(0, |x| {
+        boundaries(&current.network, &x.network) - boundaries(&prefix, &x.network)
+    });
+    let ancestors = chain
+        .iter()
+        .map(|x| (x.id, delta))
+        .filter(|(_, delta)| *delta != 0)
+        .collect();
+
+    // The addresses lost by the subnets are kept
+    let lost = (*HostCount::new((&current.network).into())).saturating_sub(*current.available);
+    let available = (*HostCount::new((&prefix).into())).saturating_sub(lost);
+
+    Ok(Resize {
+        network: Network {
+            network: prefix,
+            available: available.into(),
+            free: available.saturating_sub(*current.used).into(),
+            ..current
+        },
+        devices,
+        removed,
+        created,
+        ancestors,
+        scope,
+    })
+}
+
+fn renumber_ip(from: &IpNet, to: &IpNet, ip: IpAddr) -> Option<IpAddr> {
+    match (from.network(), to.network(), ip) {
+        (IpAddr::V4(from), IpAddr::V4(to), IpAddr::V4(ip)) => u32::from(ip)
+            .checked_sub(u32::from(from))
+            .and_then(|x| u32::from(to).checked_add(x))
+            .map(|x| IpAddr::V4(x.into())),
+        (IpAddr::V6(from), IpAddr::V6(to), IpAddr::V6(ip)) => u128::from(ip)
+            .checked_sub(u128::from(from))
+            .and_then(|x| u128::from(to).checked_add(x))
+            .map(|x| IpAddr::V6(x.into())),
+        _ => None,
+    }
 }
 
+/// The network and broadcast addresses of `network` that are hosts of its father. A subnet
+/// takes them from every ancestor, the other ones are already lost by the father
+pub(super) fn boundaries(network: &IpNet, father: &IpNet) -> i64 {
+    [network.network(), network.broadcast()]
+        .into_iter()
+        .filter(|x| !is_host(network, x) && is_host(father, x))
+        .count() as i64
+}
+
+pub(super) fn is_host(network: &IpNet, ip: &IpAddr) -> bool {
+    network.contains(ip)
+        && (ip.is_ipv6()
+            || network.prefix_len() >= 31
+            || (*ip != network.network() && *ip != network.broadcast()))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "network_get_all",
+    path = "/network",
+    tag = "network",
+    params(ParamNetworkGet),
+    responses(
+        (status = 200, description = "The networks", body = Select<Network>),
+        Failure
+    )
+)]
 pub async fn get_all(
     State(state): State<RepositoryType>,
     uri: Uri,
+    Query(ParamNetworkGet { vlan }): Query<ParamNetworkGet>,
 ) -> Result<QueryResult<Network>, ResponseError> {
     let state = state.lock().await;
 
+    match vlan.filter(|x| !x.is_empty()) {
+        Some(vlan) => get_by_vlan_name(&state, vlan).await,
+        None => {
+            state
+                .get::<Network>(Some(HashMap::from([("father", None::<Uuid>.into())])))
+                .await
+        }
+    }
+    .map(QueryResult::from)
+    .map_err(|x| {
+        let tmp: Builder = ResponseError::from(x).into();
+        tmp.instance(uri.to_string()).build()
+    })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "network_get_forest",
+    path = "/network/tree",
+    tag = "network",
+    responses(
+        (status = 200, description = "The trees of networks with their utilization", body = Select<NetworkTree>),
+        Failure
+    )
+)]
+pub async fn get_forest(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<NetworkTree>, ResponseError> {
+    let state = state.lock().await;
+
     state
-        .get::<Network>(Some(HashMap::from([("father", None::<Uuid>.into())])))
+        .get::<Network>(None)
         .await
-        .map(QueryResult::from)
+        .map(|x| QueryResult::Select(NetworkTree::build(x)))
         .map_err(|x| {
             let tmp: Builder = ResponseError::from(x).into();
             tmp.instance(uri.to_string()).build()
         })
 }
 
+#[utoipa::path(
+    get,
+    operation_id = "network_get_tree",
+    path = "/network/{id}/tree",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The tree of the network", body = Select<NetworkTree>),
+        Failure
+    )
+)]
+pub async fn get_tree(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<NetworkTree>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get_subtree(id)
+        .await
+        .map(|x| QueryResult::Select(NetworkTree::build(x)))
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+pub(super) async fn get_by_vlan_name(
+    state: &SqliteRepository,
+    name: String,
+) -> Result<Vec<Network>, RepositoryError> {
+    let ids = state
+        .get::<VlanEntry>(Some(HashMap::from([("name", name.into())])))
+        .await?
+        .into_iter()
+        .map(|x| x.id.into())
+        .collect::<Vec<_>>();
+
+    let mut networks = state.get_in::<Network>("vlan_id", ids).await?;
+
+    if networks.is_empty() {
+        return Err(RepositoryError::RowNotFound);
+    }
+
+    networks.sort_by_key(|x| x.network);
+    Ok(networks)
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "network_delete",
+    path = "/network/{id}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The network and its children are deleted", body = Affected),
+        Failure
+    )
+)]
 pub async fn delete(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -108,28 +676,39 @@ pub async fn delete(
     }
 
     let state = state.lock().await;
-    let mut to_delete = Vec::new();
-    to_delete.push(id);
-    let mut pos = 0;
-    while let Ok(children) = state
-        .get::<Network>(Some(HashMap::from([("father", to_delete[pos].into())])))
-        .await
-    {
-        for i in children {
-            to_delete.push(i.id);
-        }
-        pos += 1;
-    }
+    // The subnets are deleted with the network (ON DELETE CASCADE)
+    let subtree = state.get_subtree(id).await.unwrap_or_default();
 
-    state
+    let resp = state
         .delete::<Network>(Some(HashMap::from([("id", id.into())])))
         .await
         .map_err(|x| {
             let tmp: Builder = ResponseError::from(x).into();
             tmp.instance(uri.to_string()).build()
-        })
+        })?;
+    if resp.rows_affected() > 0 {
+        for network in subtree {
+            events::emit(
+                EventKind::NetworkDeleted,
+                &serde_json::json!({ "id": network.id, "father": network.father }),
+            );
+        }
+    }
+
+    Ok(resp)
 }
 
+#[utoipa::path(
+    post,
+    operation_id = "network_create_network_child",
+    path = "/network/subnet",
+    tag = "network",
+    params(ParamSubnetting),
+    responses(
+        (status = 201, description = "The subnets of the father", body = Insert<Network>),
+        Failure
+    )
+)]
 pub async fn create_network_child(
     State(state): State<RepositoryType>,
     Extension(claim): Extension<Claims>,
@@ -157,12 +736,18 @@ pub async fn create_network_child(
     if network.network.addr().is_ipv4() {
         match subnetting(network.network, prefix) {
             Ok(e) => {
+                verify_overlap(&state, Some(network.id), network.vrf, &e, None)
+                    .await
+                    .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
                 let new_networks = e
                     .into_iter()
                     .map(|x| Network {
                         id: uuid::Uuid::new_v4(),
                         father: Some(network.id),
+                        vrf: network.vrf,
                         vlan: None,
+                        vlan_id: None,
                         network: x,
                         description: None,
                         available: HostCount::new((&x).into()),
@@ -175,6 +760,7 @@ pub async fn create_network_child(
 
                 match state.insert::<Network>(new_networks).await {
                     Ok(e) => {
+                        emit_created(&e);
                         let mut id_to_update = Some(network.id);
                         while let Some(network_to_update) = id_to_update {
                             let resp_sub = network.available.sub(len as u32);
@@ -239,10 +825,16 @@ pub async fn create_network_child(
                 .build());
         }
 
+        verify_overlap(&state, Some(network.id), network.vrf, &[ip], None)
+            .await
+            .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
         let new_network = Network {
             id: uuid::Uuid::new_v4(),
             father: Some(network.id),
+            vrf: network.vrf,
             vlan: None,
+            vlan_id: None,
             network: ip,
             description: None,
             available: HostCount::new((&ip).into()),
@@ -258,6 +850,7 @@ pub async fn create_network_child(
                     .instance(uri.to_string())
                     .build()
             })?;
+        emit_created(&new);
 
         let avl_to_subtract = 2u128.pow((prefix - prefix_len) as u32);
         let new_avl = 2u128.pow((max_prefix_len - prefix_len) as u32) - avl_to_subtract + 2;
@@ -276,15 +869,26 @@ pub async fn create_network_child(
     }
 }
 
+#[utoipa::path(
+    get,
+    operation_id = "network_get_all_with_father",
+    path = "/network/subnet",
+    tag = "network",
+    params(ParamFather),
+    responses(
+        (status = 200, description = "The children of the father", body = Select<Network>),
+        Failure
+    )
+)]
 pub async fn get_all_with_father(
     State(state): State<RepositoryType>,
     uri: Uri,
-    Path(id): Path<Uuid>,
+    Query(ParamFather { father_id }): Query<ParamFather>,
 ) -> Result<QueryResult<Network>, ResponseError> {
     let state = state.lock().await;
 
     state
-        .get::<Network>(Some(HashMap::from([("father", id.into())])))
+        .get::<Network>(Some(HashMap::from([("father", father_id.into())])))
         .await
         .map(QueryResult::from)
         .map_err(|x| {
@@ -293,6 +897,17 @@ pub async fn get_all_with_father(
         })
 }
 
+#[utoipa::path(
+    delete,
+    operation_id = "network_clean",
+    path = "/network/clean/{id}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The devices and children of the network are deleted", body = Affected),
+        Failure
+    )
+)]
 pub async fn clean(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -344,7 +959,679 @@ pub async fn clean(
         state
             .update::<Network, _>(updater, Some(HashMap::from([("id", network.id.into())])))
             .await?;
+        emit_updated(&state, id).await;
     }
 
     Ok(QueryResult::Delete(count))
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Merge {
+    pub network: Network,
+    pub merged: Vec<Network>,
+    pub children: usize,
+    pub devices: usize,
+    pub services: usize,
+    /// The merged networks whose dhcp scope, and its reservations, is deleted with them
+    pub dhcp_scopes: Vec<Uuid>,
+    /// The merged networks whose dns domain is deleted with them
+    pub dns_domains: Vec<Uuid>,
+    /// The merged networks whose threshold is deleted with them
+    pub thresholds: Vec<Uuid>,
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "network_merge",
+    path = "/network/merge",
+    tag = "network",
+    params(ParamMerge),
+    request_body = models_data_entry::NetworkMerge,
+    responses(
+        (status = 201, description = "The network that replaces the merged ones", body = Insert<Merge>),
+        (status = 200, description = "The result of a dry run", body = Select<Merge>),
+        Failure
+    )
+)]
+pub async fn merge(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamMerge { dry_run }): Query<ParamMerge>,
+    Json(models_data_entry::NetworkMerge {
+        networks,
+        description,
+    }): Json<models_data_entry::NetworkMerge>,
+) -> Result<QueryResult<Merge>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let ids = networks.into_iter().collect::<HashSet<_>>();
+    if ids.len() < 2 {
+        return Err(ResponseError::builder()
+            .title("Invalid merge".to_string())
+            .detail("At least two different networks are needed".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let mut merged = Vec::new();
+    for id in ids {
+        merged.push(
+            state
+                .get::<Network>(Some(HashMap::from([("id", id.into())])))
+                .await
+                .map_err(|x| {
+                    Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string())
+                })?
+                .remove(0),
+        );
+    }
+    merged.sort_by_key(|x| x.network);
+
+    let first = &merged[0];
+    if merged
+        .iter()
+        .any(|x| x.father != first.father || x.vrf != first.vrf)
+    {
+        return Err(ResponseError::builder()
+            .title("Invalid merge".to_string())
+            .detail("Only sibling networks of the same vrf can be merged".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let prefixes = merged.iter().map(|x| x.network).collect::<Vec<_>>();
+    let summary = match IpNet::aggregate(&prefixes)[..] {
+        [summary] => summary,
+        _ => {
+            return Err(ResponseError::builder()
+                .title("Invalid merge".to_string())
+                .detail(format!(
+                    "The networks {} aren't contiguous and aligned to a single prefix",
+                    prefixes
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .instance(uri.to_string())
+                .build())
+        }
+    };
+
+    let mut ancestors = Vec::new();
+    let mut father = first.father;
+    while let Some(id) = father {
+        let network = state
+            .get::<Network>(Some(HashMap::from([("id", id.into())])))
+            .await?
+            .remove(0);
+
+        if ancestors.is_empty() && network.network == summary {
+            return Err(ResponseError::builder()
+                .title("Invalid merge".to_string())
+                .detail(format!(
+                    "The result would be the father network {}, use clean instead",
+                    summary
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .instance(uri.to_string())
+                .build());
+        }
+
+        father = network.father;
+        ancestors.push(network);
+    }
+
+    // The ancestors get back the boundaries of the merged networks and lose the ones of the
+    // summary
+    let delta = ancestors.first().map_or(0, |father| {
+        merged
+            .iter()
+            .map(|x| boundaries(&x.network, &father.network))
+            .sum::<i64>()
+            - boundaries(&summary, &father.network)
+    });
+    let ancestors = ancestors
+        .iter()
+        .map(|x| (x.id, delta))
+        .filter(|(_, delta)| *delta != 0)
+        .collect::<Vec<_>>();
+
+    let mut children = 0;
+    let mut devices = 0;
+    let mut services = 0;
+    let mut dhcp_scopes = Vec::new();
+    let mut dns_domains = Vec::new();
+    let mut thresholds = Vec::new();
+    // The addresses the merged networks lost to their subnets, and the boundaries of the
+    // subnets that become hosts of the summary
+    let mut lost = 0;
+    for i in &merged {
+        let subnets = state
+            .get::<Network>(Some(HashMap::from([("father", i.id.into())])))
+            .await
+            .unwrap_or_default();
+        children += subnets.len();
+        lost += (*HostCount::new((&i.network).into())).saturating_sub(*i.available) as i64;
+        lost += subnets
+            .iter()
+            .map(|x| boundaries(&x.network, &summary) - boundaries(&x.network, &i.network))
+            .sum::<i64>();
+        devices += state
+            .get::<Device>(Some(HashMap::from([("network_id", i.id.into())])))
+            .await
+            .map(|x| x.len())
+            .unwrap_or_default();
+        services += state
+            .get::<Service>(Some(HashMap::from([("network_id", i.id.into())])))
+            .await
+            .map(|x| x.len())
+            .unwrap_or_default();
+
+        let filter = || Some(HashMap::from([("network_id", i.id.into())]));
+        if state.get::<DhcpScope>(filter()).await.is_ok() {
+            dhcp_scopes.push(i.id);
+        }
+        if state.get::<DnsDomain>(filter()).await.is_ok() {
+            dns_domains.push(i.id);
+        }
+        if state.get::<Threshold>(filter()).await.is_ok() {
+            thresholds.push(i.id);
+        }
+    }
+
+    let available = (*HostCount::new((&summary).into()) as i64 - lost).max(0) as u64;
+    let used = merged.iter().map(|x| *x.used as u64).sum::<u64>();
+    let count = |total: u64| HostCount::from(total.min(HostCount::MAX as u64) as u32);
+
+    let network = Network {
+        id: uuid::Uuid::new_v4(),
+        father: first.father,
+        vrf: first.vrf,
+        vlan: first
+            .vlan
+            .filter(|vlan| merged.iter().all(|x| x.vlan == Some(*vlan))),
+        vlan_id: first
+            .vlan_id
+            .filter(|id| merged.iter().all(|x| x.vlan_id == Some(*id))),
+        network: summary,
+        description,
+        available: count(available),
+        used: count(used),
+        free: count(available.saturating_sub(used)),
+    };
+
+    let resp = Merge {
+        network,
+        merged,
+        children,
+        devices,
+        services,
+        dhcp_scopes,
+        dns_domains,
+        thresholds,
+    };
+
+    if dry_run {
+        return Ok(QueryResult::Select(vec![resp]));
+    }
+
+    tracing::info!("Merge networks {:?} into {}", prefixes, summary);
+    state
+        .merge_networks(
+            resp.network.clone(),
+            &resp.merged.iter().map(|x| x.id).collect::<Vec<_>>(),
+            &ancestors,
+        )
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    events::emit(EventKind::NetworkCreated, &resp.network);
+    for i in &resp.merged {
+        events::emit(
+            EventKind::NetworkDeleted,
+            &serde_json::json!({ "id": i.id, "father": i.father }),
+        );
+    }
+
+    Ok(QueryResult::Insert {
+        row_affect: 1,
+        data: vec![resp],
+    })
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "network_move_network",
+    path = "/network/{id}/move",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    request_body = models_data_entry::NetworkMove,
+    responses(
+        (status = 200, description = "The network moved", body = Select<Network>),
+        Failure
+    )
+)]
+pub async fn move_network(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(models_data_entry::NetworkMove { father }): Json<models_data_entry::NetworkMove>,
+) -> Result<QueryResult<Network>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    let father = father.filter(|x| !x.is_nil());
+
+    let mut network = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    if network.father == father {
+        return Err(ResponseError::builder()
+            .title("Invalid move".to_string())
+            .detail(format!(
+                "The network {} already has that father",
+                network.network
+            ))
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let old_chain = ancestors(&state, network.father).await?;
+    let new_chain = ancestors(&state, father).await?;
+
+    if let Some(new_father) = new_chain.first() {
+        if new_chain.iter().any(|x| x.id == id) {
+            return Err(ResponseError::builder()
+                .title("Invalid move".to_string())
+                .detail(format!(
+                    "The network {} belongs to the subtree of {}",
+                    new_father.network, network.network
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .instance(uri.to_string())
+                .build());
+        }
+
+        if new_father.network == network.network
+            || !new_father.network.contains(&network.network)
+            || new_father.vrf != network.vrf
+        {
+            return Err(ResponseError::builder()
+                .title("Invalid move".to_string())
+                .detail(format!(
+                    "The network {} don't belong to the network {}",
+                    network.network, new_father.network
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .instance(uri.to_string())
+                .build());
+        }
+    }
+
+    verify_overlap(&state, father, network.vrf, &[network.network], Some(id))
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    // The old ancestors recover the boundaries taken through the old father and the addresses
+    // used by the network, the new ones lose the boundaries taken through the new father and
+    // the used addresses. The ones in both chains only change their boundaries
+    let used = *network.used as i64;
+    let released = old_chain
+        .first()
+        .map_or(0, |x| boundaries(&network.network, &x.network));
+    let acquired = new_chain
+        .first()
+        .map_or(0, |x| boundaries(&network.network, &x.network));
+    let mut counters = HashMap::<Uuid, (i64, i64)>::new();
+    for x in &old_chain {
+        let counter = counters.entry(x.id).or_default();
+        counter.0 += released;
+        counter.1 -= used;
+    }
+    for x in &new_chain {
+        let counter = counters.entry(x.id).or_default();
+        counter.0 -= acquired;
+        counter.1 += used;
+    }
+    let counters = counters
+        .into_iter()
+        .filter(|(_, x)| *x != (0, 0))
+        .map(|(id, (available, used))| (id, available, used))
+        .collect::<Vec<_>>();
+
+    tracing::info!(
+        "Move network {} to the father {:?}",
+        network.network,
+        father
+    );
+    state
+        .move_network(id, father, &counters)
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    network.father = father;
+    events::emit(EventKind::NetworkUpdated, &network);
+
+    Ok(QueryResult::Select(vec![network]))
+}
+
+// The network `id` followed by all its ancestors
+async fn ancestors(
+    state: &SqliteRepository,
+    mut id: Option<Uuid>,
+) -> Result<Vec<Network>, ResponseError> {
+    let mut chain = Vec::new();
+    while let Some(tmp) = id {
+        let network = state
+            .get::<Network>(Some(HashMap::from([("id", tmp.into())])))
+            .await?
+            .remove(0);
+        id = network.father;
+        chain.push(network);
+    }
+
+    Ok(chain)
+}
+
+fn emit_created(resp: &QueryResult<Network>) {
+    if let QueryResult::Insert { data, .. } = resp {
+        for network in data {
+            events::emit(EventKind::NetworkCreated, network);
+        }
+    }
+}
+
+pub(super) async fn emit_updated(state: &SqliteRepository, id: Uuid) {
+    if let Ok(mut network) = state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await
+    {
+        events::emit(EventKind::NetworkUpdated, &network.remove(0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::fixture::{admin, counters, network, state};
+
+    /// Inserts the subnet under `father` with the given counters
+    async fn subnet(
+        state: &RepositoryType,
+        father: Uuid,
+        prefix: &str,
+        (available, used): (u32, u32),
+    ) -> Uuid {
+        let network = Network {
+            father: Some(father),
+            available: available.into(),
+            used: used.into(),
+            free: (available - used).into(),
+            ..network(prefix)
+        };
+        let id = network.id;
+        state.lock().await.insert(vec![network]).await.unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn move_only_takes_the_boundaries_that_are_hosts_of_the_new_father() {
+        let state = state().await;
+        // The 10.0.0.128/25 and 10.0.1.0/31 were left under the root, which lost the boundaries
+        // of the /24s and the /25, 10.0.0.255 twice
+        let root = Network {
+            available: 65529.into(),
+            used: 3.into(),
+            free: 65526.into(),
+            ..network("10.0.0.0/16")
+        };
+        let root_id = root.id;
+        state.lock().await.insert(vec![root]).await.unwrap();
+
+        let servers = subnet(&state, root_id, "10.0.0.0/24", (254, 0)).await;
+        let half = subnet(&state, root_id, "10.0.0.128/25", (126, 1)).await;
+        let links = subnet(&state, root_id, "10.0.1.0/24", (254, 0)).await;
+        let link = subnet(&state, root_id, "10.0.1.0/31", (2, 2)).await;
+
+        for (id, father) in [(half, servers), (link, links)] {
+            move_network(
+                State(state.clone()),
+                Extension(admin()),
+                Uri::from_static("/network/move"),
+                Path(id),
+                Json(models_data_entry::NetworkMove {
+                    father: Some(father),
+                }),
+            )
+            .await
+            .unwrap();
+        }
+
+        // The broadcast of the /25 is the broadcast of the /24, only 10.0.0.128 is taken
+        assert_eq!(counters(&state, "10.0.0.0/24").await, (253, 1, 252));
+        // Every address of a /31 is a host
+        assert_eq!(counters(&state, "10.0.1.0/24").await, (254, 2, 252));
+        // The root keeps the used addresses and gets back the broadcast of the /25
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65530, 3, 65527));
+    }
+
+    async fn merge_networks(state: &RepositoryType, networks: Vec<Uuid>) {
+        merge(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/network/merge"),
+            Query(ParamMerge { dry_run: false }),
+            Json(models_data_entry::NetworkMerge {
+                networks,
+                description: None,
+            }),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn merge_gives_back_only_the_boundaries_that_become_hosts() {
+        let state = state().await;
+        // 10.0.0.127, 10.0.0.128 and 10.0.0.255 are taken by the /25
+        let root = Network {
+            available: 65531.into(),
+            used: 1.into(),
+            free: 65530.into(),
+            ..network("10.0.0.0/16")
+        };
+        let root_id = root.id;
+        state.lock().await.insert(vec![root]).await.unwrap();
+
+        let low = subnet(&state, root_id, "10.0.0.0/25", (126, 1)).await;
+        let high = subnet(&state, root_id, "10.0.0.128/25", (125, 0)).await;
+        subnet(&state, high, "10.0.0.128/26", (62, 0)).await;
+        let first = subnet(&state, root_id, "10.0.1.0/31", (2, 0)).await;
+        let second = subnet(&state, root_id, "10.0.1.2/31", (2, 0)).await;
+
+        // 10.0.0.127 and 10.0.0.128 are given back, 10.0.0.255 is the broadcast of the /24
+        merge_networks(&state, vec![low, high]).await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65533, 1, 65532));
+        // 10.0.0.128 and 10.0.0.191 are still taken by the /26
+        assert_eq!(counters(&state, "10.0.0.0/24").await, (252, 1, 251));
+
+        // Every address of a /31 is a host, the /30 takes its boundaries from the root
+        merge_networks(&state, vec![first, second]).await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65531, 1, 65530));
+        assert_eq!(counters(&state, "10.0.1.0/30").await, (2, 0, 2));
+    }
+
+    async fn resize(state: &RepositoryType, id: Uuid, prefix: &str) {
+        update(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/network"),
+            Path(id),
+            Query(ParamNetworkUpdate {
+                renumber: false,
+                dry_run: false,
+            }),
+            Json(UpdateNetwork {
+                network: Some(prefix.parse().unwrap()),
+                description: None,
+                vlan: None,
+                vlan_id: None,
+            }),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn resize_changes_the_boundaries_taken_from_the_ancestors() {
+        let state = state().await;
+        // The /24 takes 10.0.1.0 and 10.0.1.255, the /26 takes 10.0.1.63
+        let root = Network {
+            available: 65531.into(),
+            free: 65531.into(),
+            ..network("10.0.0.0/16")
+        };
+        let root_id = root.id;
+        state.lock().await.insert(vec![root]).await.unwrap();
+        let father = subnet(&state, root_id, "10.0.1.0/24", (253, 0)).await;
+        let id = subnet(&state, father, "10.0.1.0/26", (62, 0)).await;
+
+        // 10.0.1.0 was already lost by the root through the /24
+        resize(&state, id, "10.0.1.64/26").await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65530, 0, 65530));
+        assert_eq!(counters(&state, "10.0.1.0/24").await, (252, 0, 252));
+
+        // 10.0.0.0 isn't a host of the root, the /23 keeps the addresses taken by the /26
+        resize(&state, father, "10.0.0.0/23").await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65531, 0, 65531));
+        assert_eq!(counters(&state, "10.0.0.0/23").await, (508, 0, 508));
+
+        // Every address of a /31 is a host
+        resize(&state, id, "10.0.1.64/31").await;
+        assert_eq!(counters(&state, "10.0.0.0/16").await, (65533, 0, 65533));
+        assert_eq!(counters(&state, "10.0.0.0/23").await, (510, 0, 510));
+        assert_eq!(counters(&state, "10.0.1.64/31").await, (2, 0, 2));
+    }
+
+    #[tokio::test]
+    async fn resize_keeps_the_dhcp_pools_inside_the_network() {
+        let state = state().await;
+        let root = network("10.0.0.0/24");
+        let id = root.id;
+        state.lock().await.insert(vec![root]).await.unwrap();
+        let scope = DhcpScope {
+            network_id: id,
+            pools: vec![Pool {
+                start: "10.0.0.100".parse().unwrap(),
+                end: "10.0.0.200".parse().unwrap(),
+            }],
+            gateway: Some("10.0.0.1".parse().unwrap()),
+            dns_servers: Vec::new(),
+            lease_time: 3600,
+            options: Vec::new(),
+            subnet_id: 1,
+        };
+        state.lock().await.insert(vec![scope]).await.unwrap();
+
+        let resize = |prefix: &str, renumber: bool| {
+            update(
+                State(state.clone()),
+                Extension(admin()),
+                Uri::from_static("/network"),
+                Path(id),
+                Query(ParamNetworkUpdate {
+                    renumber,
+                    dry_run: false,
+                }),
+                Json(UpdateNetwork {
+                    network: Some(prefix.parse().unwrap()),
+                    description: None,
+                    vlan: None,
+                    vlan_id: None,
+                }),
+            )
+        };
+
+        // 10.0.0.200 isn't a host of the /25
+        assert!(resize("10.0.0.0/25", false).await.is_err());
+
+        // The pools and the gateway keep their offset
+        resize("10.0.1.0/24", true).await.unwrap();
+        let scope = state
+            .lock()
+            .await
+            .get::<DhcpScope>(None)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(scope.pools[0].to_string(), "10.0.1.100 - 10.0.1.200");
+        assert_eq!(scope.gateway, Some("10.0.1.1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn merge_lists_what_is_deleted_with_the_merged_networks() {
+        let state = state().await;
+        let low = network("10.0.0.0/25");
+        let high = network("10.0.0.128/25");
+        let (low_id, high_id) = (low.id, high.id);
+        state.lock().await.insert(vec![low, high]).await.unwrap();
+        state
+            .lock()
+            .await
+            .insert(vec![DnsDomain {
+                network_id: high_id,
+                domain: "example.com".to_string(),
+                ttl: 3600,
+                nameserver: "ns.example.com".to_string(),
+                hostmaster: "hostmaster.example.com".to_string(),
+            }])
+            .await
+            .unwrap();
+
+        let resp = merge(
+            State(state.clone()),
+            Extension(admin()),
+            Uri::from_static("/network/merge"),
+            Query(ParamMerge { dry_run: true }),
+            Json(models_data_entry::NetworkMerge {
+                networks: vec![low_id, high_id],
+                description: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let QueryResult::Select(resp) = resp else {
+            panic!("a dry run selects the merge");
+        };
+        assert_eq!(resp[0].dns_domains, vec![high_id]);
+        assert!(resp[0].dhcp_scopes.is_empty());
+        assert!(resp[0].thresholds.is_empty());
+    }
+}
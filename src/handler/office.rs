@@ -0,0 +1,257 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::models::{
+    device::Device,
+    location::Location,
+    office::{Office, UpdateOffice},
+};
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "office_create",
+    path = "/office",
+    tag = "office",
+    request_body = models_data_entry::Office,
+    responses(
+        (status = 201, description = "The office created", body = Insert<Office>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(office): Json<models_data_entry::Office>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    tracing::info!("New office {:?}", office);
+
+    state
+        .insert::<Office>(vec![office.into()])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "office_get_all",
+    path = "/office",
+    tag = "office",
+    responses(
+        (status = 200, description = "The offices", body = Select<Office>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Office>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "office_get_one",
+    path = "/office/{id}",
+    tag = "office",
+    params(("id" = Uuid, Path, description = "The id of the office")),
+    responses(
+        (status = 200, description = "The office", body = Select<Office>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Office>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "office_update",
+    path = "/office/{id}",
+    tag = "office",
+    params(("id" = Uuid, Path, description = "The id of the office")),
+    request_body = UpdateOffice,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(office): Json<UpdateOffice>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .update::<Office, _>(office, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// The locations of the office are removed by the database (ON DELETE CASCADE),
+/// and the devices placed in them keep existing without location (ON DELETE SET NULL)
+#[utoipa::path(
+    delete,
+    operation_id = "office_delete",
+    path = "/office/{id}",
+    tag = "office",
+    params(("id" = Uuid, Path, description = "The id of the office")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Office>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Office>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "office_get_locations",
+    path = "/office/{id}/location",
+    tag = "office",
+    params(("id" = Uuid, Path, description = "The id of the office")),
+    responses(
+        (status = 200, description = "The locations of the office", body = Select<Location>),
+        Failure
+    )
+)]
+pub async fn get_locations(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Location>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Location>(Some(HashMap::from([("office_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "office_get_devices",
+    path = "/office/{id}/device",
+    tag = "office",
+    params(("id" = Uuid, Path, description = "The id of the office")),
+    responses(
+        (status = 200, description = "The devices placed in the locations of the office", body = Select<Device>),
+        Failure
+    )
+)]
+pub async fn get_devices(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Device>, ResponseError> {
+    let state = state.lock().await;
+
+    let locations = state
+        .get::<Location>(Some(HashMap::from([("office_id", id.into())])))
+        .await
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })?;
+
+    let mut devices = Vec::new();
+    for location in locations {
+        if let Ok(devs) = state
+            .get::<Device>(Some(HashMap::from([("location", location.id.into())])))
+            .await
+        {
+            devices.extend(devs);
+        }
+    }
+
+    if claim.role != Role::Admin {
+        for dev in devices.iter_mut() {
+            dev.credential = None;
+        }
+    }
+
+    devices.sort_by_key(|x| x.ip);
+
+    Ok(devices.into())
+}
@@ -0,0 +1,317 @@
+use super::*;
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    IntoResponses, Modify, OpenApi, ToSchema,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "ipam_rs",
+        description = "The paths are relative to `/api/v1`, the token is the cookie of `/login`"
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        network::create,
+        network::get_all,
+        network::get_one,
+        network::update,
+        network::delete,
+        network::clean,
+        network::get_forest,
+        network::get_tree,
+        network::merge,
+        network::move_network,
+        network::create_network_child,
+        network::get_all_with_father,
+        bulk::export_networks,
+        bulk::import_networks,
+        threshold::get_one,
+        threshold::set,
+        threshold::delete,
+        zone::get_domain,
+        zone::set_domain,
+        zone::delete_domain,
+        zone::get_zones,
+        zone::export,
+        dhcp::get_scope,
+        dhcp::set_scope,
+        dhcp::delete_scope,
+        dhcp::get_reservations,
+        dhcp::create_reservation,
+        dhcp::delete_reservation,
+        device::create,
+        device::get,
+        device::update,
+        device::delete,
+        device::create_all_devices,
+        device::ping,
+        device::reserve,
+        bulk::export_devices,
+        bulk::import_devices,
+        service::create,
+        service::get,
+        service::update,
+        service::delete,
+        services::create,
+        services::get,
+        services::update,
+        services::delete,
+        threshold::get_all,
+        zone::get_records,
+        zone::get_updates,
+        dhcp::kea,
+        dhcp::dnsmasq,
+        dhcp::import_leases,
+        vlan::create,
+        vlan::get_all,
+        vlan::get_one,
+        vlan::update,
+        vlan::delete,
+        vrf::create,
+        vrf::get_all,
+        vrf::get_one,
+        vrf::update,
+        vrf::delete,
+        vrf::get_networks,
+        office::create,
+        office::get_all,
+        office::get_one,
+        office::update,
+        office::delete,
+        office::get_locations,
+        office::get_devices,
+        location::create,
+        location::get_all,
+        location::get_one,
+        location::update,
+        location::delete,
+        asset::create,
+        asset::get_all,
+        asset::get_one,
+        asset::update,
+        asset::delete,
+        asset::get_interfaces,
+        asset::create_interface,
+        asset::update_interface,
+        asset::delete_interface,
+        asset::get_devices,
+        webhook::create,
+        webhook::get_all,
+        webhook::get_one,
+        webhook::update,
+        webhook::delete,
+        webhook::get_deliveries,
+        webhook::redeliver,
+        admin::backup,
+        admin::restore,
+        migration::import,
+        search::search,
+        lookup::lookup,
+        lookup::vendor,
+        sse::events,
+        auth::create,
+    ),
+    components(schemas(Problem)),
+    modifiers(&Token),
+    security(("token" = [])),
+    tags(
+        (name = "network", description = "Networks and their thresholds, dns and dhcp"),
+        (name = "device", description = "The addresses of the networks"),
+        (name = "service", description = "The services that run in a device"),
+        (name = "services", description = "The catalog of services"),
+        (name = "vlan", description = "The registry of vlans"),
+        (name = "vrf"),
+        (name = "office", description = "The offices and what is placed in them"),
+        (name = "location", description = "The racks and desks of the offices"),
+        (name = "asset", description = "The hardware and its interfaces"),
+        (name = "webhook", description = "The webhooks and their deliveries"),
+        (name = "dns", description = "The dynamic updates of the dns server"),
+        (name = "dhcp", description = "The configuration of the dhcp servers and their leases"),
+        (name = "search", description = "Search and lookup of addresses"),
+        (name = "events", description = "The change events as server-sent events"),
+        (name = "admin", description = "Backups and migrations from other systems"),
+        (name = "user"),
+    )
+)]
+pub struct ApiDoc;
+
+struct Token;
+
+impl Modify for Token {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "token",
+                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(
+                    libipam::cookie::Cookie::TOKEN.to_string(),
+                ))),
+            );
+    }
+}
+
+pub async fn spec() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+// The types below only describe the bodies that `QueryResult` and `ResponseError` write
+
+/// `QueryResult::Select`
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct Select<T> {
+    pub status: u16,
+    pub length: usize,
+    pub data: Vec<T>,
+}
+
+/// `QueryResult::Insert`
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct Insert<T> {
+    pub status: u16,
+    pub row_affect: u64,
+    pub data: Vec<T>,
+}
+
+/// `QueryResult::Update` and `QueryResult::Delete`
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct Affected {
+    pub status: u16,
+    pub row_affect: u64,
+}
+
+/// The body of the errors, as RFC 7807 describes
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct Problem {
+    pub title: Option<String>,
+    pub status: u16,
+    pub detail: Option<String>,
+    pub instance: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(IntoResponses)]
+pub enum Failure {
+    /// The request isn't valid, the user isn't authorized or what it references doesn't exist
+    #[response(status = "4XX")]
+    Client(Problem),
+    /// The database failed
+    #[response(status = "5XX")]
+    Server(Problem),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request},
+        Router,
+    };
+    use std::collections::BTreeSet;
+    use tower::ServiceExt;
+
+    /// Routes that describe themselves: the spec, its page and the schema of graphql
+    const UNDOCUMENTED: [&str; 3] = ["/openapi.json", "/docs", "/graphql"];
+
+    /// The paths `api` routes, in the format of the spec
+    fn routed_paths() -> BTreeSet<String> {
+        crate::api_routes()
+            .into_iter()
+            .flat_map(|(prefix, routes)| {
+                routes.into_iter().map(move |(route, _)| match route {
+                    "/" if !prefix.is_empty() => prefix.to_string(),
+                    _ => format!("{}{}", prefix, route),
+                })
+            })
+            .map(|path| {
+                path.split('/')
+                    .map(|x| match x.strip_prefix(':') {
+                        Some(param) => format!("{{{}}}", param),
+                        None => x.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect()
+    }
+
+    fn sample(path: &str) -> String {
+        path.split('/')
+            .map(|x| match x {
+                "{mac}" => "00:11:22:33:44:55".to_string(),
+                "{zone}" => "example.com".to_string(),
+                x if x.starts_with('{') => Uuid::nil().to_string(),
+                x => x.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Every route is documented and the other way around. No route answers TRACE, so the
+    /// router responds 405 with the methods of the path
+    #[tokio::test]
+    async fn spec_matches_routes() {
+        let app = Router::new()
+            .nest("/api/v1", crate::api())
+            .with_state(crate::database::fixture::state().await);
+
+        let paths = ApiDoc::openapi().paths.paths;
+        let mut errors = routed_paths()
+            .into_iter()
+            .filter(|x| !paths.contains_key(x) && !UNDOCUMENTED.contains(&x.as_str()))
+            .map(|x| format!("{}: routed but not documented", x))
+            .collect::<Vec<_>>();
+
+        for (path, item) in paths {
+            let documented = [
+                ("GET", &item.get),
+                ("POST", &item.post),
+                ("PUT", &item.put),
+                ("PATCH", &item.patch),
+                ("DELETE", &item.delete),
+            ]
+            .into_iter()
+            .filter(|(_, x)| x.is_some())
+            .map(|(x, _)| x.to_string())
+            .collect::<BTreeSet<_>>();
+
+            let resp = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::TRACE)
+                        .uri(format!("/api/v1{}", sample(&path)))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let routed = resp
+                .headers()
+                .get(header::ALLOW)
+                .and_then(|x| x.to_str().ok())
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty() && *x != "HEAD")
+                .map(str::to_string)
+                .collect::<BTreeSet<_>>();
+
+            if routed != documented {
+                errors.push(format!(
+                    "{}: documented {:?}, routed {:?}",
+                    path, documented, routed
+                ));
+            }
+        }
+
+        assert!(errors.is_empty(), "{}", errors.join("\n"));
+    }
+}
@@ -1,36 +1,176 @@
 use super::Uuid;
+use crate::models::network::Vlan;
 use libipam::type_net::port::Port;
 use serde::Deserialize;
 use std::net::IpAddr;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ParamDevice {
+    #[param(value_type = String)]
     pub ip: IpAddr,
     pub network_id: Uuid,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ParamSubnetting {
     pub father_id: uuid::Uuid,
     pub prefix: u8,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ParamPKService {
+    #[param(value_type = u16)]
     pub port: Port,
+    #[param(value_type = String)]
     pub ip: IpAddr,
     pub network_id: Uuid,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ParamPKServiceGet {
+    #[param(value_type = Option<u16>)]
     pub port: Option<Port>,
+    #[param(value_type = String)]
     pub ip: IpAddr,
     pub network_id: Uuid,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamFather {
+    pub father_id: Uuid,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ParamDeviceGet {
+    #[param(value_type = Option<String>)]
     pub ip: Option<IpAddr>,
-    pub network_id: Uuid,
+    pub network_id: Option<Uuid>,
+    pub location: Option<Uuid>,
+    pub vrf: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamVlan {
+    pub vid: Option<Vlan>,
+    pub office_id: Option<Uuid>,
+    pub vlan_group: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamNetworkGet {
+    pub vlan: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamNetworkUpdate {
+    #[serde(default)]
+    pub renumber: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamSearch {
+    pub q: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamLookup {
+    pub vrf: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamLocation {
+    pub office_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamMerge {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamEvents {
+    pub events: Option<String>,
+    pub network_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamLease {
+    pub vrf: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamImport {
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Columns of the file renamed to a field, `column:field,column:field`
+    pub map: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamDeviceExport {
+    pub network_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamBackup {
+    #[serde(default)]
+    pub exclude_credentials: bool,
+    #[serde(default)]
+    pub exclude_passwords: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ParamRestore {
+    #[serde(default)]
+    pub replace: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// The filters of the NetBox api that are supported, each endpoint uses its own
+#[derive(Deserialize, Default)]
+pub struct ParamNetBox {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub id: Option<u32>,
+    pub q: Option<String>,
+    pub family: Option<u8>,
+    pub status: Option<String>,
+    /// `null` is the global table
+    pub vrf_id: Option<String>,
+    pub vrf: Option<String>,
+    pub prefix: Option<String>,
+    pub within: Option<String>,
+    pub vlan_vid: Option<u16>,
+    pub address: Option<String>,
+    pub parent: Option<String>,
+    pub dns_name: Option<String>,
+    pub vid: Option<u16>,
+    pub name: Option<String>,
+    pub site_id: Option<u32>,
+    pub site: Option<String>,
+    pub group: Option<String>,
 }
@@ -0,0 +1,295 @@
+use super::openapi::{Failure, Select};
+use super::*;
+use crate::database::SqliteRepository;
+use crate::models::{
+    device::Device,
+    mac::MacAddr,
+    network::{Network, Vlan},
+    service::{Service, Services},
+    vlan::VlanEntry,
+    vrf::Vrf,
+};
+use ipnet::IpNet;
+use query_params::ParamSearch;
+use serde::Serialize;
+use std::{collections::HashSet, net::IpAddr};
+use utoipa::ToSchema;
+
+const MAX_HITS: usize = 100;
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+pub enum Hit {
+    Network(Network),
+    Device(Device),
+    Service(Service),
+    Services(Services),
+    Vlan(VlanEntry),
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHit {
+    pub score: u16,
+    pub vrf: Option<String>,
+    #[serde(flatten)]
+    pub hit: Hit,
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "search",
+    path = "/search",
+    tag = "search",
+    params(ParamSearch),
+    responses(
+        (status = 200, description = "The best matches first", body = Select<SearchHit>),
+        Failure
+    )
+)]
+pub async fn search(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Query(ParamSearch { q }): Query<ParamSearch>,
+) -> Result<QueryResult<SearchHit>, ResponseError> {
+    let q = q.trim();
+    if q.is_empty() {
+        return Err(ResponseError::builder()
+            .title("Invalid search".to_string())
+            .detail("The query parameter q can't be empty".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    let networks = state.get::<Network>(None).await?;
+    let vrfs = state.get::<Vrf>(None).await?;
+
+    let mut hits = if let Ok(ip) = q.parse::<IpAddr>() {
+        search_ip(&state, &networks, ip).await
+    } else if let Ok(net) = q.parse::<IpNet>() {
+        search_network(&networks, net)
+    } else if let Ok(mac) = q.parse::<MacAddr>() {
+        search_mac(&state, mac).await
+    } else {
+        let mut hits = search_text(&state, &networks, q).await?;
+        if let Some(vlan) = q.parse::<u16>().ok().and_then(|x| Vlan::new(x).ok()) {
+            hits.extend(search_vlan(&state, &networks, vlan).await);
+        }
+        hits
+    };
+
+    let mut seen = HashSet::new();
+    hits.sort_by_key(|x| std::cmp::Reverse(x.score));
+    hits.retain(|x| seen.insert(x.key()));
+    hits.truncate(MAX_HITS);
+
+    for hit in hits.iter_mut() {
+        let vrf = match &hit.hit {
+            Hit::Network(e) => e.vrf,
+            Hit::Device(Device { network_id, .. })
+            | Hit::Service(Service {
+                netwok_id: network_id,
+                ..
+            }) => networks
+                .iter()
+                .find(|x| &x.id == network_id)
+                .and_then(|x| x.vrf),
+            _ => None,
+        };
+        hit.vrf = vrf.and_then(|id| vrfs.iter().find(|x| x.id == id).map(|x| x.name.clone()));
+
+        if claim.role != Role::Admin {
+            if let Hit::Device(dev) = &mut hit.hit {
+                dev.credential = None;
+            }
+        }
+    }
+
+    Ok(QueryResult::Select(hits))
+}
+
+impl SearchHit {
+    fn new(score: u16, hit: Hit) -> Self {
+        Self {
+            score,
+            vrf: None,
+            hit,
+        }
+    }
+
+    fn key(&self) -> String {
+        match &self.hit {
+            Hit::Network(e) => format!("network/{}", e.id),
+            Hit::Device(e) => format!("device/{}/{}", e.network_id, e.ip),
+            Hit::Service(e) => format!("service/{}/{}/{}", e.netwok_id, e.ip, *e.port),
+            Hit::Services(e) => format!("services/{}", e.id),
+            Hit::Vlan(e) => format!("vlan/{}", e.id),
+        }
+    }
+}
+
+async fn search_ip(state: &SqliteRepository, networks: &[Network], ip: IpAddr) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+
+    if let Ok(devices) = state
+        .get::<Device>(Some(HashMap::from([("ip", ip.into())])))
+        .await
+    {
+        hits.extend(
+            devices
+                .into_iter()
+                .map(|x| SearchHit::new(200, Hit::Device(x))),
+        );
+    }
+
+    if let Ok(services) = state
+        .get::<Service>(Some(HashMap::from([("ip", ip.into())])))
+        .await
+    {
+        hits.extend(
+            services
+                .into_iter()
+                .map(|x| SearchHit::new(150, Hit::Service(x))),
+        );
+    }
+
+    // The most specific network is the best hit
+    hits.extend(
+        networks
+            .iter()
+            .filter(|x| x.network.contains(&ip))
+            .map(|x| SearchHit::new(50 + x.network.prefix_len() as u16, Hit::Network(x.clone()))),
+    );
+
+    hits
+}
+
+/// The ips of the hardware with the mac
+async fn search_mac(state: &SqliteRepository, mac: MacAddr) -> Vec<SearchHit> {
+    state
+        .get::<Device>(Some(HashMap::from([("mac", mac.into())])))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| SearchHit::new(200, Hit::Device(x)))
+        .collect()
+}
+
+fn search_network(networks: &[Network], net: IpNet) -> Vec<SearchHit> {
+    networks
+        .iter()
+        .filter(|x| x.overlaps(&net))
+        .map(|x| {
+            let score = if x.network == net {
+                200
+            } else if x.network.contains(&net) {
+                50 + x.network.prefix_len() as u16
+            } else {
+                40
+            };
+            SearchHit::new(score, Hit::Network(x.clone()))
+        })
+        .collect()
+}
+
+async fn search_vlan(state: &SqliteRepository, networks: &[Network], vlan: Vlan) -> Vec<SearchHit> {
+    let mut hits = networks
+        .iter()
+        .filter(|x| x.vlan == Some(vlan))
+        .map(|x| SearchHit::new(90, Hit::Network(x.clone())))
+        .collect::<Vec<_>>();
+
+    if let Ok(vlans) = state
+        .get::<VlanEntry>(Some(HashMap::from([("vid", vlan.into())])))
+        .await
+    {
+        hits.extend(vlans.into_iter().map(|x| SearchHit::new(100, Hit::Vlan(x))));
+    }
+
+    hits
+}
+
+async fn search_text(
+    state: &SqliteRepository,
+    networks: &[Network],
+    text: &str,
+) -> Result<Vec<SearchHit>, ResponseError> {
+    let lower = text.to_lowercase();
+    let score = |value: &str| {
+        if value.to_lowercase() == lower {
+            100
+        } else {
+            30
+        }
+    };
+    let mut hits = Vec::new();
+
+    hits.extend(
+        networks
+            .iter()
+            .filter(|x| {
+                x.description
+                    .as_ref()
+                    .is_some_and(|x| x.to_lowercase().contains(&lower))
+            })
+            .map(|x| {
+                SearchHit::new(
+                    score(x.description.as_deref().unwrap_or_default()),
+                    Hit::Network(x.clone()),
+                )
+            }),
+    );
+
+    hits.extend(
+        state
+            .search::<Device>(&["description"], text)
+            .await?
+            .into_iter()
+            .map(|x| {
+                SearchHit::new(
+                    score(x.description.as_deref().unwrap_or_default()),
+                    Hit::Device(x),
+                )
+            }),
+    );
+
+    hits.extend(
+        state
+            .search::<Service>(&["description"], text)
+            .await?
+            .into_iter()
+            .map(|x| {
+                SearchHit::new(
+                    score(x.description.as_deref().unwrap_or_default()),
+                    Hit::Service(x),
+                )
+            }),
+    );
+
+    let services = state.search::<Services>(&["name"], text).await?;
+    for svc in services {
+        if let Ok(bindings) = state
+            .get::<Service>(Some(HashMap::from([("service_id", svc.id.into())])))
+            .await
+        {
+            hits.extend(
+                bindings
+                    .into_iter()
+                    .map(|x| SearchHit::new(score(&svc.name) - 10, Hit::Service(x))),
+            );
+        }
+        hits.push(SearchHit::new(score(&svc.name) + 10, Hit::Services(svc)));
+    }
+
+    hits.extend(
+        state
+            .search::<VlanEntry>(&["name", "description"], text)
+            .await?
+            .into_iter()
+            .map(|x| SearchHit::new(score(&x.name), Hit::Vlan(x))),
+    );
+
+    Ok(hits)
+}
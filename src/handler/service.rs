@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
 use super::{
+    openapi::{Affected, Failure, Insert, Select},
     query_params::{ParamPKService, ParamPKServiceGet},
     utils::TypeTable,
     RepositoryType, ResponseError, Uri,
 };
 use crate::{
     database::repository::{QueryResult, Repository},
+    events::{self, EventKind},
     models::service::{Service, ServiceUpdate},
 };
 use axum::{
@@ -14,16 +16,43 @@ use axum::{
     Json,
 };
 use libipam::response_error::Builder;
+use serde_json::json;
 
+#[utoipa::path(
+    post,
+    operation_id = "service_create",
+    path = "/service",
+    tag = "service",
+    request_body = Service,
+    responses(
+        (status = 201, description = "The service of the device created", body = Insert<Service>),
+        Failure
+    )
+)]
 pub async fn create(
     State(state): State<RepositoryType>,
     Json(service): Json<Service>,
 ) -> Result<QueryResult<Service>, ResponseError> {
     let state = state.lock().await;
 
-    Ok(state.insert::<Service>(vec![service]).await?)
+    let resp = state.insert::<Service>(vec![service.clone()]).await?;
+    events::emit(EventKind::ServiceCreated, &service);
+
+    Ok(resp)
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "service_update",
+    path = "/service",
+    tag = "service",
+    params(ParamPKService),
+    request_body = ServiceUpdate,
+    responses(
+        (status = 200, description = "The service updated", body = Affected),
+        Failure
+    )
+)]
 pub async fn update(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -35,8 +64,13 @@ pub async fn update(
     Json(updater): Json<ServiceUpdate>,
 ) -> Result<QueryResult<Service>, ResponseError> {
     let state = state.lock().await;
+    let key = HashMap::from([
+        ("port", (**updater.port.as_ref().unwrap_or(&port)).into()),
+        ("ip", updater.ip.unwrap_or(ip).into()),
+        ("network_id", updater.netwok_id.unwrap_or(network_id).into()),
+    ]);
 
-    Ok(state
+    let resp = state
         .update(
             updater,
             Some(HashMap::from([
@@ -46,9 +80,27 @@ pub async fn update(
             ])),
         )
         .await
-        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?)
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+    if resp.rows_affected() > 0 {
+        if let Ok(service) = state.get::<Service>(Some(key)).await {
+            events::emit(EventKind::ServiceUpdated, &service[0]);
+        }
+    }
+
+    Ok(resp)
 }
 
+#[utoipa::path(
+    delete,
+    operation_id = "service_delete",
+    path = "/service",
+    tag = "service",
+    params(ParamPKService),
+    responses(
+        (status = 200, description = "The service deleted", body = Affected),
+        Failure
+    )
+)]
 pub async fn delete(
     State(state): State<RepositoryType>,
     Query(ParamPKService {
@@ -58,16 +110,33 @@ pub async fn delete(
     }): Query<ParamPKService>,
 ) -> Result<QueryResult<Service>, ResponseError> {
     let state = state.lock().await;
+    let key = json!({ "port": port, "ip": ip, "network_id": network_id });
 
-    Ok(state
+    let resp = state
         .delete(Some(HashMap::from([
             ("port", port.into()),
             ("ip", ip.into()),
             ("network_id", network_id.into()),
         ])))
-        .await?)
+        .await?;
+    if resp.rows_affected() > 0 {
+        events::emit(EventKind::ServiceDeleted, &key);
+    }
+
+    Ok(resp)
 }
 
+#[utoipa::path(
+    get,
+    operation_id = "service_get",
+    path = "/service",
+    tag = "service",
+    params(ParamPKServiceGet),
+    responses(
+        (status = 200, description = "The services of the device", body = Select<Service>),
+        Failure
+    )
+)]
 pub async fn get(
     State(state): State<RepositoryType>,
     Query(ParamPKServiceGet {
@@ -1,8 +1,20 @@
 use response_error::Builder;
 
+use super::openapi::{Affected, Failure, Insert, Select};
 use super::*;
 use crate::models::service::{Services, ServicesUpdate};
 
+#[utoipa::path(
+    post,
+    operation_id = "services_create",
+    path = "/services",
+    tag = "services",
+    request_body = Services,
+    responses(
+        (status = 201, description = "The service created", body = Insert<Services>),
+        Failure
+    )
+)]
 pub async fn create(
     State(state): State<RepositoryType>,
     Json(service): Json<Services>,
@@ -12,6 +24,18 @@ pub async fn create(
     Ok(state.insert::<Services>(vec![service]).await?)
 }
 
+#[utoipa::path(
+    patch,
+    operation_id = "services_update",
+    path = "/services/{id}",
+    tag = "services",
+    params(("id" = Uuid, Path)),
+    request_body = ServicesUpdate,
+    responses(
+        (status = 200, description = "The service updated", body = Affected),
+        Failure
+    )
+)]
 pub async fn update(
     State(state): State<RepositoryType>,
     uri: Uri,
@@ -26,6 +50,17 @@ pub async fn update(
         .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?)
 }
 
+#[utoipa::path(
+    delete,
+    operation_id = "services_delete",
+    path = "/services/{id}",
+    tag = "services",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "The service deleted", body = Affected),
+        Failure
+    )
+)]
 pub async fn delete(
     State(state): State<RepositoryType>,
     Path(id): Path<uuid::Uuid>,
@@ -39,6 +74,17 @@ pub async fn delete(
         .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?)
 }
 
+#[utoipa::path(
+    get,
+    operation_id = "services_get",
+    path = "/services/{id}",
+    tag = "services",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "The service", body = Select<Services>),
+        Failure
+    )
+)]
 pub async fn get(
     State(state): State<RepositoryType>,
     uri: Uri,
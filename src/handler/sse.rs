@@ -0,0 +1,76 @@
+use super::openapi::Failure;
+use super::*;
+use crate::events::{self, Event};
+use axum::response::sse::{self, KeepAlive, Sse};
+use futures::{stream, Stream};
+use query_params::ParamEvents;
+use std::{convert::Infallible, time::Duration};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+/// Streams the change events, the `resync` event means that some events were lost
+/// and the client has to reload its state
+#[utoipa::path(
+    get,
+    operation_id = "events",
+    path = "/events",
+    tag = "events",
+    params(ParamEvents),
+    responses(
+        (status = 200, description = "The stream of events", body = String, content_type = "text/event-stream"),
+        Failure
+    )
+)]
+pub async fn events(
+    Query(ParamEvents { events, network_id }): Query<ParamEvents>,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let filters = events
+        .map(|x| {
+            x.split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_else(|| vec!["*".to_string()]);
+
+    let stream = stream::unfold(events::subscribe(), move |mut rx| {
+        let filters = filters.clone();
+        async move {
+            let event = next(&mut rx, &filters, network_id).await?;
+            Some((Ok(event), rx))
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+async fn next(
+    rx: &mut Receiver<Event>,
+    filters: &[String],
+    network_id: Option<Uuid>,
+) -> Option<sse::Event> {
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                if !events::matches(filters, event.event.as_str())
+                    || network_id.is_some_and(|x| !event.touches(x))
+                {
+                    continue;
+                }
+
+                match sse::Event::default()
+                    .id(event.id.to_string())
+                    .event(event.event.as_str())
+                    .json_data(&event)
+                {
+                    Ok(e) => return Some(e),
+                    Err(e) => tracing::error!("Event {}: {}", event.id, e),
+                }
+            }
+            Err(RecvError::Lagged(n)) => {
+                tracing::warn!("Event stream lagged, {} events lost", n);
+                return Some(sse::Event::default().event("resync").data(n.to_string()));
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::models::{
+    alert::{AlertLevel, Threshold, UpdateThreshold},
+    network::Network,
+};
+use response_error::Builder;
+
+#[utoipa::path(
+    get,
+    operation_id = "threshold_get_all",
+    path = "/threshold",
+    tag = "network",
+    responses(
+        (status = 200, description = "The thresholds of every network", body = Select<Threshold>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<Threshold>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Threshold>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "threshold_get_one",
+    path = "/network/{id}/threshold",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The threshold of the network", body = Select<Threshold>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Threshold>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Threshold>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "threshold_set",
+    path = "/network/{id}/threshold",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    request_body = models_data_entry::Threshold,
+    responses(
+        (status = 201, description = "The threshold created", body = Insert<Threshold>),
+        (status = 200, description = "The threshold updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn set(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(models_data_entry::Threshold { warning, critical }): Json<models_data_entry::Threshold>,
+) -> Result<QueryResult<Threshold>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    if !(1..=100).contains(&warning) || !(1..=100).contains(&critical) || warning > critical {
+        return Err(ResponseError::builder()
+            .title("Invalid threshold".to_string())
+            .detail(
+                "The thresholds must be between 1 and 100 and the warning can't exceed the critical"
+                    .to_string(),
+            )
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    if state
+        .get::<Threshold>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .is_ok()
+    {
+        state
+            .update::<Threshold, _>(
+                UpdateThreshold {
+                    warning: Some(warning),
+                    critical: Some(critical),
+                },
+                Some(HashMap::from([("network_id", id.into())])),
+            )
+            .await
+    } else {
+        state
+            .insert::<Threshold>(vec![Threshold {
+                network_id: id,
+                warning,
+                critical,
+                level: AlertLevel::default(),
+            }])
+            .await
+    }
+    .map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "threshold_delete",
+    path = "/network/{id}/threshold",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The threshold deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Threshold>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Threshold>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
@@ -0,0 +1,282 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::database::SqliteRepository;
+use crate::models::{
+    network::{Network, UpdateNetwork},
+    utils::TypeTable,
+    vlan::{UpdateVlanEntry, VlanEntry},
+};
+use query_params::ParamVlan;
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "vlan_create",
+    path = "/vlan",
+    tag = "vlan",
+    request_body = models_data_entry::Vlan,
+    responses(
+        (status = 201, description = "The vlan created", body = Insert<VlanEntry>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(vlan): Json<models_data_entry::Vlan>,
+) -> Result<QueryResult<VlanEntry>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    let vlan: VlanEntry = vlan.into();
+    tracing::info!("New vlan {:?}", vlan);
+
+    verify_scope(&state, &vlan)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    state.insert::<VlanEntry>(vec![vlan]).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "vlan_get_all",
+    path = "/vlan",
+    tag = "vlan",
+    params(ParamVlan),
+    responses(
+        (status = 200, description = "The vlans", body = Select<VlanEntry>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Query(ParamVlan {
+        vid,
+        office_id,
+        vlan_group,
+    }): Query<ParamVlan>,
+) -> Result<QueryResult<VlanEntry>, ResponseError> {
+    let state = state.lock().await;
+    let mut condition = HashMap::new();
+
+    if let Some(vid) = vid {
+        condition.insert("vid", vid.into());
+    }
+
+    if let Some(office_id) = office_id {
+        condition.insert("office_id", office_id.into());
+    }
+
+    if let Some(vlan_group) = vlan_group {
+        condition.insert("vlan_group", vlan_group.into());
+    }
+
+    let condition = if condition.is_empty() {
+        None
+    } else {
+        Some(condition)
+    };
+
+    state
+        .get::<VlanEntry>(condition)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "vlan_get_one",
+    path = "/vlan/{id}",
+    tag = "vlan",
+    params(("id" = Uuid, Path, description = "The id of the vlan")),
+    responses(
+        (status = 200, description = "The vlan", body = Select<VlanEntry>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<VlanEntry>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<VlanEntry>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "vlan_update",
+    path = "/vlan/{id}",
+    tag = "vlan",
+    params(("id" = Uuid, Path, description = "The id of the vlan")),
+    request_body = UpdateVlanEntry,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(updater): Json<UpdateVlanEntry>,
+) -> Result<QueryResult<VlanEntry>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let mut vlan = state
+        .get::<VlanEntry>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    if let Some(vid) = updater.vid {
+        vlan.vid = vid;
+    }
+    if let Some(name) = &updater.name {
+        vlan.name = name.clone();
+    }
+    if let Some(office_id) = updater.office_id {
+        vlan.office_id = Some(office_id).filter(|x| !x.is_nil());
+    }
+    if let Some(vlan_group) = &updater.vlan_group {
+        vlan.vlan_group = Some(vlan_group.clone()).filter(|x| !x.is_empty());
+    }
+
+    verify_scope(&state, &vlan)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let vid = updater.vid;
+    let resp = state
+        .update::<VlanEntry, _>(updater, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    // The networks of the vlan keep its vid
+    if vid.is_some() {
+        let network = UpdateNetwork {
+            network: None,
+            description: None,
+            vlan: vid,
+            vlan_id: Some(id),
+        };
+        state
+            .update::<Network, _>(network, Some(HashMap::from([("vlan_id", id.into())])))
+            .await
+            .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+    }
+
+    Ok(resp)
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "vlan_delete",
+    path = "/vlan/{id}",
+    tag = "vlan",
+    params(("id" = Uuid, Path, description = "The id of the vlan")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<VlanEntry>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<VlanEntry>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+// The scope of a vlan is an office, a vlan group or neither of them (global),
+// and both the vid and the name must be unique inside it
+async fn verify_scope(state: &SqliteRepository, vlan: &VlanEntry) -> Result<(), ResponseError> {
+    if vlan.office_id.is_some() && vlan.vlan_group.is_some() {
+        return Err(ResponseError::builder()
+            .title("Invalid scope".to_string())
+            .detail("A vlan belongs either to an office or to a vlan group".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    }
+
+    for (column, value) in [
+        ("vid", TypeTable::from(vlan.vid)),
+        ("name", vlan.name.clone().into()),
+    ] {
+        let condition = HashMap::from([
+            (column, value),
+            ("office_id", vlan.office_id.into()),
+            ("vlan_group", vlan.vlan_group.clone().into()),
+        ]);
+
+        if let Ok(vlans) = state.get::<VlanEntry>(Some(condition)).await {
+            if let Some(other) = vlans.iter().find(|x| x.id != vlan.id) {
+                return Err(ResponseError::builder()
+                    .title("Conflict".to_string())
+                    .detail(format!(
+                        "The vlan {} ({}) already uses the {} in this scope",
+                        *other.vid, other.name, column
+                    ))
+                    .status(StatusCode::CONFLICT)
+                    .build());
+            }
+        }
+    }
+
+    Ok(())
+}
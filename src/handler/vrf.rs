@@ -0,0 +1,219 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::models::{
+    network::Network,
+    vrf::{UpdateVrf, Vrf},
+};
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "vrf_create",
+    path = "/vrf",
+    tag = "vrf",
+    request_body = models_data_entry::Vrf,
+    responses(
+        (status = 201, description = "The vrf created", body = Insert<Vrf>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(vrf): Json<models_data_entry::Vrf>,
+) -> Result<QueryResult<Vrf>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+    tracing::info!("New vrf {:?}", vrf);
+
+    state.insert::<Vrf>(vec![vrf.into()]).await.map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "vrf_get_all",
+    path = "/vrf",
+    tag = "vrf",
+    responses(
+        (status = 200, description = "The vrfs", body = Select<Vrf>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<Vrf>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Vrf>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "vrf_get_one",
+    path = "/vrf/{id}",
+    tag = "vrf",
+    params(("id" = Uuid, Path, description = "The id of the vrf")),
+    responses(
+        (status = 200, description = "The vrf", body = Select<Vrf>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Vrf>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Vrf>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "vrf_get_networks",
+    path = "/vrf/{id}/network",
+    tag = "vrf",
+    params(("id" = Uuid, Path, description = "The id of the vrf")),
+    responses(
+        (status = 200, description = "The networks of the vrf", body = Select<Network>),
+        Failure
+    )
+)]
+pub async fn get_networks(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Network>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<Network>(Some(HashMap::from([
+            ("vrf_id", id.into()),
+            ("father", None::<Uuid>.into()),
+        ])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "vrf_update",
+    path = "/vrf/{id}",
+    tag = "vrf",
+    params(("id" = Uuid, Path, description = "The id of the vrf")),
+    request_body = UpdateVrf,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(vrf): Json<UpdateVrf>,
+) -> Result<QueryResult<Vrf>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .update::<Vrf, _>(vrf, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "vrf_delete",
+    path = "/vrf/{id}",
+    tag = "vrf",
+    params(("id" = Uuid, Path, description = "The id of the vrf")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Vrf>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    if state
+        .get::<Network>(Some(HashMap::from([("vrf_id", id.into())])))
+        .await
+        .is_ok()
+    {
+        return Err(ResponseError::builder()
+            .title("Conflict".to_string())
+            .detail("The vrf still has networks".to_string())
+            .status(StatusCode::CONFLICT)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    state
+        .delete::<Vrf>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
@@ -0,0 +1,372 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::events::EventKind;
+use crate::models::webhook::{Delivery, DeliveryStatus, UpdateDelivery, UpdateWebhook, Webhook};
+use response_error::Builder;
+
+#[utoipa::path(
+    post,
+    operation_id = "webhook_create",
+    path = "/webhook",
+    tag = "webhook",
+    request_body = models_data_entry::Webhook,
+    responses(
+        (status = 201, description = "The webhook created", body = Insert<Webhook>),
+        Failure
+    )
+)]
+pub async fn create(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Json(webhook): Json<models_data_entry::Webhook>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    verify(
+        Some(&webhook.url),
+        Some(&webhook.secret),
+        Some(&webhook.events),
+    )
+    .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let state = state.lock().await;
+    tracing::info!("New webhook {} {:?}", webhook.url, webhook.events);
+
+    state
+        .insert::<Webhook>(vec![webhook.into()])
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "webhook_get_all",
+    path = "/webhook",
+    tag = "webhook",
+    responses(
+        (status = 200, description = "The webhooks", body = Select<Webhook>),
+        Failure
+    )
+)]
+pub async fn get_all(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .get::<Webhook>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "webhook_get_one",
+    path = "/webhook/{id}",
+    tag = "webhook",
+    params(("id" = Uuid, Path, description = "The id of the webhook")),
+    responses(
+        (status = 200, description = "The webhook", body = Select<Webhook>),
+        Failure
+    )
+)]
+pub async fn get_one(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .get::<Webhook>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    patch,
+    operation_id = "webhook_update",
+    path = "/webhook/{id}",
+    tag = "webhook",
+    params(("id" = Uuid, Path, description = "The id of the webhook")),
+    request_body = UpdateWebhook,
+    responses(
+        (status = 200, description = "The rows updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn update(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(webhook): Json<UpdateWebhook>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    verify(
+        webhook.url.as_deref(),
+        webhook.secret.as_deref(),
+        webhook.events.as_deref(),
+    )
+    .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let state = state.lock().await;
+
+    state
+        .update::<Webhook, _>(webhook, Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// The delivery log of the webhook is removed by the database (ON DELETE CASCADE)
+#[utoipa::path(
+    delete,
+    operation_id = "webhook_delete",
+    path = "/webhook/{id}",
+    tag = "webhook",
+    params(("id" = Uuid, Path, description = "The id of the webhook")),
+    responses(
+        (status = 200, description = "The rows deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Webhook>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<Webhook>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "webhook_get_deliveries",
+    path = "/webhook/{id}/delivery",
+    tag = "webhook",
+    params(("id" = Uuid, Path, description = "The id of the webhook")),
+    responses(
+        (status = 200, description = "The deliveries of the webhook", body = Select<Delivery>),
+        Failure
+    )
+)]
+pub async fn get_deliveries(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Delivery>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    let mut deliveries = state
+        .get::<Delivery>(Some(HashMap::from([("webhook_id", id.into())])))
+        .await
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })?;
+
+    deliveries.sort_by_key(|x| std::cmp::Reverse(x.created_at));
+
+    Ok(deliveries.into())
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "webhook_redeliver",
+    path = "/webhook/{id}/delivery/{delivery_id}",
+    tag = "webhook",
+    params(("id" = Uuid, Path, description = "The id of the webhook"), ("delivery_id" = Uuid, Path, description = "The id of the delivery")),
+    responses(
+        (status = 200, description = "The delivery is pending again", body = Affected),
+        Failure
+    )
+)]
+pub async fn redeliver(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<QueryResult<Delivery>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let db = state.clone();
+    let state = state.lock().await;
+
+    let mut delivery = state
+        .get::<Delivery>(Some(HashMap::from([
+            ("id", delivery_id.into()),
+            ("webhook_id", id.into()),
+        ])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?
+        .remove(0);
+
+    if delivery.status == DeliveryStatus::Pending {
+        return Err(ResponseError::builder()
+            .title("Conflict".to_string())
+            .detail("The delivery is still pending".to_string())
+            .status(StatusCode::CONFLICT)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    delivery.attempts = 0;
+    delivery.status = DeliveryStatus::Pending;
+    delivery.response = None;
+    delivery.error = None;
+
+    let resp = state
+        .update::<Delivery, _>(
+            UpdateDelivery {
+                attempts: 0,
+                status: DeliveryStatus::Pending,
+                response: None,
+                error: None,
+            },
+            Some(HashMap::from([("id", delivery_id.into())])),
+        )
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })?;
+
+    tokio::spawn(crate::webhook::deliver(db, delivery));
+
+    Ok(resp)
+}
+
+fn verify(
+    url: Option<&str>,
+    secret: Option<&str>,
+    events: Option<&[String]>,
+) -> Result<(), ResponseError> {
+    if let Some(url) = url {
+        if !reqwest::Url::parse(url).is_ok_and(|x| matches!(x.scheme(), "http" | "https")) {
+            return Err(ResponseError::builder()
+                .title("Invalid webhook".to_string())
+                .detail(format!("The url {} isn't a valid http(s) url", url))
+                .status(StatusCode::BAD_REQUEST)
+                .build());
+        }
+    }
+
+    if secret.is_some_and(str::is_empty) {
+        return Err(ResponseError::builder()
+            .title("Invalid webhook".to_string())
+            .detail("The secret can't be empty".to_string())
+            .status(StatusCode::BAD_REQUEST)
+            .build());
+    }
+
+    if let Some(events) = events {
+        let valid = |x: &str| {
+            x == "*"
+                || EventKind::ALL.iter().any(|e| {
+                    e.as_str() == x || e.as_str().split('.').next() == x.strip_suffix(".*")
+                })
+        };
+
+        if events.is_empty() || events.iter().any(|x| !valid(x)) {
+            return Err(ResponseError::builder()
+                .title("Invalid webhook".to_string())
+                .detail(format!(
+                    "The events must be some of *, network.*, device.*, service.*, {}",
+                    EventKind::ALL.map(|x| x.as_str()).join(", ")
+                ))
+                .status(StatusCode::BAD_REQUEST)
+                .build());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,646 @@
+use super::openapi::{Affected, Failure, Insert, Select};
+use super::*;
+use crate::database::{repository::error::RepositoryError, SqliteRepository};
+use crate::models::{
+    device::Device,
+    dns::{
+        self, DnsDomain, DnsRecord, DnsUpdate, DnsZone, UpdateDnsDomain, UpdateDnsZone, Zone,
+        ZoneKind,
+    },
+    network::Network,
+};
+use axum::{
+    extract::Request,
+    http::{header, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use response_error::Builder;
+use std::collections::HashSet;
+
+#[utoipa::path(
+    get,
+    operation_id = "zone_get_domain",
+    path = "/network/{id}/dns",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The dns domain of the network", body = Select<DnsDomain>),
+        Failure
+    )
+)]
+pub async fn get_domain(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<DnsDomain>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<DnsDomain>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "zone_set_domain",
+    path = "/network/{id}/dns",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    request_body = models_data_entry::DnsDomain,
+    responses(
+        (status = 201, description = "The domain created", body = Insert<DnsDomain>),
+        (status = 200, description = "The domain updated", body = Affected),
+        Failure
+    )
+)]
+pub async fn set_domain(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+    Json(domain): Json<models_data_entry::DnsDomain>,
+) -> Result<QueryResult<DnsDomain>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let hostmaster = domain.hostmaster.replacen('@', ".", 1);
+    if !dns::is_domain(&domain.domain)
+        || !dns::is_domain(&domain.nameserver)
+        || !dns::is_domain(&hostmaster)
+        || domain.ttl == Some(0)
+    {
+        return Err(ResponseError::builder()
+            .title("Invalid dns domain".to_string())
+            .detail(
+                "The domain, nameserver and hostmaster must be valid names and the ttl can't be 0"
+                    .to_string(),
+            )
+            .status(StatusCode::BAD_REQUEST)
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .get::<Network>(Some(HashMap::from([("id", id.into())])))
+        .await
+        .map_err(|x| Into::<Builder>::into(ResponseError::from(x)).instance(uri.to_string()))?;
+
+    let domain = DnsDomain {
+        network_id: id,
+        domain: dns::normalize(&domain.domain),
+        ttl: domain.ttl.unwrap_or(dns::DEFAULT_TTL),
+        nameserver: dns::normalize(&domain.nameserver),
+        hostmaster: dns::normalize(&hostmaster),
+    };
+
+    if state
+        .get::<DnsDomain>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .is_ok()
+    {
+        state
+            .update::<DnsDomain, _>(
+                UpdateDnsDomain {
+                    domain: Some(domain.domain),
+                    ttl: Some(domain.ttl),
+                    nameserver: Some(domain.nameserver),
+                    hostmaster: Some(domain.hostmaster),
+                },
+                Some(HashMap::from([("network_id", id.into())])),
+            )
+            .await
+    } else {
+        state.insert::<DnsDomain>(vec![domain]).await
+    }
+    .map_err(|x| {
+        Into::<Builder>::into(ResponseError::from(x))
+            .instance(uri.to_string())
+            .build()
+    })
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "zone_delete_domain",
+    path = "/network/{id}/dns",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The domain deleted", body = Affected),
+        Failure
+    )
+)]
+pub async fn delete_domain(
+    State(state): State<RepositoryType>,
+    Extension(claim): Extension<Claims>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<DnsDomain>, ResponseError> {
+    if claim.role != Role::Admin {
+        return Err(ResponseError::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .detail(format!("User {} not authorizedh", claim.sub))
+            .title("Unauthorized".into())
+            .instance(uri.to_string())
+            .build());
+    }
+
+    let state = state.lock().await;
+
+    state
+        .delete::<DnsDomain>(Some(HashMap::from([("network_id", id.into())])))
+        .await
+        .map_err(|x| {
+            Into::<Builder>::into(ResponseError::from(x))
+                .instance(uri.to_string())
+                .build()
+        })
+}
+
+/// Records pushed to the dns server with dynamic updates
+#[utoipa::path(
+    get,
+    operation_id = "zone_get_records",
+    path = "/dns/record",
+    tag = "dns",
+    responses(
+        (status = 200, description = "The records pushed to the dns server", body = Select<DnsRecord>),
+        Failure
+    )
+)]
+pub async fn get_records(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<DnsRecord>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<DnsRecord>(None)
+        .await
+        .map(QueryResult::from)
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+/// Dynamic updates waiting to be sent or retried
+#[utoipa::path(
+    get,
+    operation_id = "zone_get_updates",
+    path = "/dns/update",
+    tag = "dns",
+    responses(
+        (status = 200, description = "The updates waiting to be sent", body = Select<DnsUpdate>),
+        Failure
+    )
+)]
+pub async fn get_updates(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+) -> Result<QueryResult<DnsUpdate>, ResponseError> {
+    let state = state.lock().await;
+
+    state
+        .get::<DnsUpdate>(None)
+        .await
+        .map(|mut x| {
+            x.sort_by_key(|x| x.next_attempt);
+            QueryResult::from(x)
+        })
+        .map_err(|x| {
+            let tmp: Builder = ResponseError::from(x).into();
+            tmp.instance(uri.to_string()).build()
+        })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "zone_get_zones",
+    path = "/network/{id}/zone",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network")),
+    responses(
+        (status = 200, description = "The forward and reverse zones of the network", body = Select<Zone>),
+        Failure
+    )
+)]
+pub async fn get_zones(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path(id): Path<Uuid>,
+) -> Result<QueryResult<Zone>, ResponseError> {
+    let state = state.lock().await;
+
+    let (_, zones) = build(&state, id)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    Ok(QueryResult::Select(zones))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "zone_export",
+    path = "/network/{id}/zone/{zone}",
+    tag = "network",
+    params(("id" = Uuid, Path, description = "The id of the network"), ("zone" = String, Path, description = "The name of the zone")),
+    responses(
+        (status = 200, description = "The zone file", body = String, content_type = "text/dns"),
+        Failure
+    )
+)]
+pub async fn export(
+    State(state): State<RepositoryType>,
+    uri: Uri,
+    Path((id, zone)): Path<(Uuid, String)>,
+) -> Result<Response, ResponseError> {
+    let state = state.lock().await;
+
+    let (settings, zones) = build(&state, id)
+        .await
+        .map_err(|x| Into::<Builder>::into(x).instance(uri.to_string()))?;
+
+    let zone = dns::normalize(&zone);
+    let Some(zone) = zones.into_iter().find(|x| x.zone == zone) else {
+        return Err(ResponseError::builder()
+            .title("Zone not found".to_string())
+            .detail(format!(
+                "The network {} doesn't generate the zone {}",
+                id, zone
+            ))
+            .status(StatusCode::NOT_FOUND)
+            .instance(uri.to_string())
+            .build());
+    };
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.zone\"", zone.zone),
+            ),
+        ],
+        zone.render(&settings),
+    )
+        .into_response())
+}
+
+/// Builds the forward zone of the network domain and the reverse zones of the network. The
+/// zones are read only, their serials are stored by `publish` after every change
+async fn build(
+    state: &SqliteRepository,
+    id: Uuid,
+) -> Result<(DnsDomain, Vec<Zone>), ResponseError> {
+    let networks = state.get::<Network>(None).await?;
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+
+    let Some(network) = networks.iter().find(|x| x.id == id) else {
+        return Err(ResponseError::builder()
+            .title("Network not found".to_string())
+            .detail(format!("The network {} doesn't exist", id))
+            .status(StatusCode::NOT_FOUND)
+            .build());
+    };
+
+    let Some(settings) = dns::domain_of(&networks, &domains, Some(id)).cloned() else {
+        return Err(ResponseError::builder()
+            .title("Dns domain not found".to_string())
+            .detail(format!(
+                "Neither the network {} nor its ancestors have a dns domain",
+                id
+            ))
+            .status(StatusCode::NOT_FOUND)
+            .build());
+    };
+
+    let devices = state.get::<Device>(None).await.unwrap_or_default();
+    let names = dns::names(&networks, &domains, &devices);
+
+    let mut zones = vec![dns::forward_zone(&settings, &names)];
+    zones.extend(
+        dns::reverse_zones(network.network)
+            .into_iter()
+            .map(|x| dns::reverse_zone(x, network.vrf, &names)),
+    );
+
+    // A zone changed by a request still being published shows the serial it will get
+    let today = time::OffsetDateTime::now_utc().date();
+    for zone in zones.iter_mut() {
+        let (name, vrf) = key_of(zone, network.vrf);
+        let current = state
+            .get::<DnsZone>(Some(HashMap::from([
+                ("zone", name.clone().into()),
+                ("vrf", vrf.into()),
+            ])))
+            .await
+            .ok()
+            .and_then(|mut x| x.pop())
+            .unwrap_or_else(|| DnsZone {
+                zone: name,
+                vrf,
+                serial: 0,
+                hash: String::new(),
+            });
+        zone.serial = current.serial_of(&zone.hash(), today);
+    }
+
+    Ok((settings, zones))
+}
+
+/// The key of a zone in `dns_zones`, the forward zones are shared by every vrf
+type ZoneKey = (String, Option<Uuid>);
+
+fn key_of(zone: &Zone, vrf: Option<Uuid>) -> ZoneKey {
+    match zone.kind {
+        ZoneKind::Forward => (zone.zone.clone(), None),
+        ZoneKind::Reverse => (zone.zone.clone(), vrf),
+    }
+}
+
+/// The zones of the network and its subnets
+async fn zones_of(state: &SqliteRepository, id: Uuid) -> HashSet<ZoneKey> {
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+    if domains.is_empty() {
+        return HashSet::new();
+    }
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+
+    let mut zones = HashSet::new();
+    for network in state.get_subtree(id).await.unwrap_or_default() {
+        let Some(settings) = dns::domain_of(&networks, &domains, Some(network.id)) else {
+            continue;
+        };
+        zones.insert((settings.domain.clone(), None));
+        zones.extend(
+            dns::reverse_zones(network.network)
+                .into_iter()
+                .map(|x| (dns::reverse_name(x), network.vrf)),
+        );
+    }
+
+    zones
+}
+
+/// Bumps the serial of every zone whose records have changed since it was last published,
+/// or only of the ones in `only`
+pub async fn publish(
+    state: &SqliteRepository,
+    only: Option<&HashSet<ZoneKey>>,
+) -> Result<(), RepositoryError> {
+    if only.is_some_and(HashSet::is_empty) {
+        return Ok(());
+    }
+    let domains = state.get::<DnsDomain>(None).await.unwrap_or_default();
+    if domains.is_empty() {
+        return Ok(());
+    }
+    let networks = state.get::<Network>(None).await.unwrap_or_default();
+    let devices = state.get::<Device>(None).await.unwrap_or_default();
+    let names = dns::names(&networks, &domains, &devices);
+
+    let mut seen = HashSet::new();
+    let mut wanted = |key: ZoneKey| only.is_none_or(|x| x.contains(&key)) && seen.insert(key);
+    let mut zones = Vec::new();
+    for network in &networks {
+        let Some(settings) = dns::domain_of(&networks, &domains, Some(network.id)) else {
+            continue;
+        };
+        if wanted((settings.domain.clone(), None)) {
+            zones.push((None, dns::forward_zone(settings, &names)));
+        }
+        for zone in dns::reverse_zones(network.network) {
+            if wanted((dns::reverse_name(zone), network.vrf)) {
+                zones.push((network.vrf, dns::reverse_zone(zone, network.vrf, &names)));
+            }
+        }
+    }
+
+    let current = state
+        .get::<DnsZone>(None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| ((x.zone.clone(), x.vrf), x))
+        .collect::<HashMap<_, _>>();
+
+    let today = time::OffsetDateTime::now_utc().date();
+    for (vrf, zone) in zones {
+        let hash = zone.hash();
+        match current.get(&(zone.zone.clone(), vrf)) {
+            Some(current) if current.hash == hash => {}
+            Some(current) => {
+                state
+                    .update::<DnsZone, _>(
+                        UpdateDnsZone {
+                            serial: Some(current.next_serial(today)),
+                            hash: Some(hash),
+                        },
+                        Some(HashMap::from([
+                            ("zone", zone.zone.into()),
+                            ("vrf", vrf.into()),
+                        ])),
+                    )
+                    .await?;
+            }
+            None => {
+                let mut current = DnsZone {
+                    zone: zone.zone,
+                    vrf,
+                    serial: 0,
+                    hash,
+                };
+                current.serial = current.next_serial(today);
+                state.insert::<DnsZone>(vec![current]).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The zones a request can change
+enum Scope {
+    Network(Uuid),
+    All,
+}
+
+/// Only the requests that change the networks, their devices or their domains change the
+/// zones. When the request is about a single network only its zones are published
+fn scope_of(req: &Request) -> Option<Scope> {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return None;
+    }
+
+    let id = |x: &str| x.parse::<Uuid>().ok();
+    let network_id = || {
+        req.uri()
+            .query()?
+            .split('&')
+            .find_map(|x| x.strip_prefix("network_id="))
+            .and_then(id)
+    };
+    let path = req.uri().path();
+    let segments = path
+        .strip_prefix("/api/v1")
+        .unwrap_or(path)
+        .split('/')
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>();
+
+    match segments[..] {
+        ["network", "clean", x] => id(x).map(Scope::Network),
+        ["network", x] | ["network", x, "move" | "dns"] if id(x).is_some() => {
+            id(x).map(Scope::Network)
+        }
+        // The thresholds and the dhcp of the network
+        ["network", x, ..] if id(x).is_some() => None,
+        ["network", ..] => Some(Scope::All),
+        ["device", x] if id(x).is_some() => id(x).map(Scope::Network),
+        ["device", ..] => Some(network_id().map_or(Scope::All, Scope::Network)),
+        ["import", _] | ["admin", "restore"] | ["dhcp", "lease"] => Some(Scope::All),
+        _ => None,
+    }
+}
+
+pub async fn on_change(State(state): State<RepositoryType>, req: Request, next: Next) -> Response {
+    let Some(scope) = scope_of(&req) else {
+        return next.run(req).await;
+    };
+
+    // A network that moves or is resized leaves the zones it was in
+    let before = match scope {
+        Scope::Network(id) => zones_of(&*state.lock().await, id).await,
+        Scope::All => HashSet::new(),
+    };
+    let resp = next.run(req).await;
+
+    if resp.status().is_success() {
+        let state = state.lock().await;
+        let published = match scope {
+            Scope::Network(id) => {
+                let mut zones = zones_of(&state, id).await;
+                zones.extend(before);
+                publish(&state, Some(&zones)).await
+            }
+            Scope::All => publish(&state, None).await,
+        };
+        if let Err(e) = published {
+            tracing::error!("Dns zone serials: {}", e);
+        }
+    }
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::fixture::*,
+        models::{device::Status, vrf::Vrf},
+    };
+
+    /// The serial of every published zone
+    async fn serials(state: &SqliteRepository) -> HashMap<ZoneKey, u32> {
+        state
+            .get::<DnsZone>(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|x| ((x.zone, x.vrf), x.serial))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn every_vrf_keeps_the_serials_of_its_reverse_zones() {
+        let state = repository().await;
+        let mut ids = Vec::new();
+        for name in ["red", "blue"] {
+            let vrf = Vrf {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                rd: None,
+                description: None,
+            };
+            let network = Network {
+                vrf: Some(vrf.id),
+                ..network("10.0.0.0/24")
+            };
+            let id = network.id;
+            ids.push((vrf.id, id));
+            state.insert(vec![vrf]).await.unwrap();
+            state.insert(vec![network]).await.unwrap();
+            state
+                .insert(vec![DnsDomain {
+                    network_id: id,
+                    domain: format!("{}.example.com", name),
+                    ttl: dns::DEFAULT_TTL,
+                    nameserver: "ns.example.com".to_string(),
+                    hostmaster: "hostmaster.example.com".to_string(),
+                }])
+                .await
+                .unwrap();
+            state
+                .insert(vec![Device {
+                    hostname: Some("gw".to_string()),
+                    ..device("10.0.0.1", id, Status::Reserved)
+                }])
+                .await
+                .unwrap();
+        }
+
+        publish(&state, None).await.unwrap();
+        let before = serials(&state).await;
+        let reverse = "0.0.10.in-addr.arpa".to_string();
+        assert_eq!(before.len(), 4);
+        assert!(ids
+            .iter()
+            .all(|(vrf, _)| before.contains_key(&(reverse.clone(), Some(*vrf)))));
+
+        // Nothing changed, the serials stay
+        publish(&state, None).await.unwrap();
+        assert_eq!(serials(&state).await, before);
+
+        // Only the zones of the red network are published again
+        let (red, network_id) = ids[0];
+        state
+            .insert(vec![Device {
+                hostname: Some("www".to_string()),
+                ..device("10.0.0.2", network_id, Status::Reserved)
+            }])
+            .await
+            .unwrap();
+        let zones = zones_of(&state, network_id).await;
+        assert_eq!(zones.len(), 2);
+        publish(&state, Some(&zones)).await.unwrap();
+
+        let after = serials(&state).await;
+        for (key, serial) in &before {
+            if zones.contains(key) {
+                assert!(after[key] > *serial);
+            } else {
+                assert_eq!(after[key], *serial);
+            }
+        }
+        assert!(zones.contains(&(reverse, Some(red))));
+    }
+}
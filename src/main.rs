@@ -1,21 +1,31 @@
+mod alert;
+mod cli;
 mod database;
+mod ddns;
+mod events;
+mod graphql;
 mod handler;
 mod models;
 mod services;
 mod trace_layer;
+mod webhook;
 
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
     response::{IntoResponse, Redirect},
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, MethodRouter},
     serve, Router,
 };
 use database::SqliteRepository;
-use handler::{services as svcs, *};
+use handler::{services as svcs, webhook as webhooks, *};
 use std::{env, sync::Arc};
 use tokio::sync::Mutex;
 use tower_http::{compression::CompressionLayer, services::ServeDir, trace::TraceLayer};
 
+/// Max size of the backups and exports that are uploaded
+const UPLOAD_LIMIT: usize = 256 * 1024 * 1024;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv::dotenv().ok();
@@ -23,6 +33,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
+    let db_name = env::var("DB_NAME").unwrap_or("./data.sqlite".to_string());
+
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        return cli::run(&SqliteRepository::new(&db_name).await?, &args).await;
+    }
+
     let ip = env::var("IP_ADDRESS").unwrap_or("0.0.0.0".to_string());
     let port = env::var("PORT").unwrap_or("3000".to_string());
 
@@ -35,61 +52,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("Listening: {}:{}", ip, port);
 
-    let db_name = env::var("DB_NAME").unwrap_or("./data.sqlite".to_string());
-
     let db = Arc::new(Mutex::new(SqliteRepository::new(&db_name).await?));
-    let network = Router::new()
-        .route("/clean/:id", delete(network::clean))
-        .route("/", post(network::create).get(network::get_all))
-        .route(
-            "/subnet",
-            post(network::create_network_child).get(network::get_all_with_father),
-        )
-        .route(
-            "/:id",
-            get(network::get_one)
-                .patch(network::update)
-                .delete(network::delete),
-        );
-
-    let device = Router::new()
-        .route(
-            "/",
-            post(device::create)
-                .delete(device::delete)
-                .patch(device::update)
-                .get(device::get),
-        )
-        .route("/:network_id", post(device::create_all_devices)) // create, update and get all devices
-        .route("/ping", patch(device::ping))
-        .route("/reserve", patch(device::reserve));
-
-    let user = Router::new().route("/", post(auth::create));
-
-    let service = Router::new().route(
-        "/",
-        post(service::create)
-            .patch(service::update)
-            .delete(service::delete)
-            .get(service::get),
-    );
-
-    let services = Router::new().route("/", post(svcs::create)).route(
-        "/:id",
-        patch(svcs::update).get(svcs::get).delete(svcs::delete),
-    );
-
-    let api = Router::new()
-        .nest("/service", service)
-        .nest("/services", services)
-        .nest("/network", network)
-        .nest("/device", device)
-        .nest("/user", user);
+    let alerts = alert::spawn(db.clone());
+    webhook::spawn(db.clone());
+    ddns::spawn(db.clone());
+    let api = api()
+        .layer(middleware::from_fn_with_state(alerts, alert::on_change))
+        .layer(middleware::from_fn_with_state(db.clone(), zone::on_change));
 
     let web = Router::new()
         .nest_service("/static", ServeDir::new("static"))
         .route("/", get(http::http_view_network))
         .route("/offices", get(http::offices))
+        .route("/rack/:location_id", get(http::rack))
         .route(
             "/favicon.ico",
             get(|| async { Redirect::to("/static/favicon.ico").into_response() }),
@@ -98,10 +73,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/service", get(http::service))
         .route("/:network_id", get(http::http_view_devices));
 
+    let ipam = Router::new()
+        .route("/prefixes/", get(netbox::prefixes))
+        .route("/prefixes/:id/", get(netbox::prefix))
+        .route("/ip-addresses/", get(netbox::ip_addresses))
+        .route("/ip-addresses/:id/", get(netbox::ip_address))
+        .route("/vlans/", get(netbox::vlans))
+        .route("/vlans/:id/", get(netbox::vlan))
+        .layer(middleware::from_fn(netbox::verify_token));
+
     let app = Router::new()
         .nest("/", web)
         .nest("/api/v1", api)
         .layer(middleware::from_fn(auth::verify_token))
+        .nest("/api/ipam", ipam)
         .route("/login", post(auth::login).get(http::login))
         .with_state(db.clone())
         .fallback(http::fallback)
@@ -116,3 +101,223 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// The routes of `/api/v1`
+fn api() -> Router<Arc<Mutex<SqliteRepository>>> {
+    api_routes()
+        .into_iter()
+        .fold(Router::new(), |api, (prefix, routes)| {
+            let router = routes
+                .into_iter()
+                .fold(Router::new(), |router, (path, methods)| {
+                    router.route(path, methods)
+                });
+            if prefix.is_empty() {
+                api.merge(router)
+            } else {
+                api.nest(prefix, router)
+            }
+        })
+}
+
+type Routes = Vec<(&'static str, MethodRouter<Arc<Mutex<SqliteRepository>>>)>;
+
+/// The routes of `/api/v1` under the prefix they are nested in, as a table so they can be
+/// listed
+fn api_routes() -> Vec<(&'static str, Routes)> {
+    let network: Routes = vec![
+        ("/clean/:id", delete(network::clean)),
+        ("/", post(network::create).get(network::get_all)),
+        ("/tree", get(network::get_forest)),
+        ("/merge", post(network::merge)),
+        ("/export", get(bulk::export_networks)),
+        ("/import", post(bulk::import_networks)),
+        ("/:id/tree", get(network::get_tree)),
+        ("/:id/move", post(network::move_network)),
+        (
+            "/:id/threshold",
+            get(threshold::get_one)
+                .put(threshold::set)
+                .delete(threshold::delete),
+        ),
+        (
+            "/:id/dns",
+            get(zone::get_domain)
+                .put(zone::set_domain)
+                .delete(zone::delete_domain),
+        ),
+        (
+            "/:id/dhcp",
+            get(dhcp::get_scope)
+                .put(dhcp::set_scope)
+                .delete(dhcp::delete_scope),
+        ),
+        (
+            "/:id/dhcp/reservation",
+            get(dhcp::get_reservations).post(dhcp::create_reservation),
+        ),
+        (
+            "/:id/dhcp/reservation/:mac",
+            delete(dhcp::delete_reservation),
+        ),
+        ("/:id/zone", get(zone::get_zones)),
+        ("/:id/zone/:zone", get(zone::export)),
+        (
+            "/subnet",
+            post(network::create_network_child).get(network::get_all_with_father),
+        ),
+        (
+            "/:id",
+            get(network::get_one)
+                .patch(network::update)
+                .delete(network::delete),
+        ),
+    ];
+
+    let device: Routes = vec![
+        (
+            "/",
+            post(device::create)
+                .delete(device::delete)
+                .patch(device::update)
+                .get(device::get),
+        ),
+        ("/:network_id", post(device::create_all_devices)), // create, update and get all devices
+        ("/ping", patch(device::ping)),
+        ("/reserve", patch(device::reserve)),
+        ("/export", get(bulk::export_devices)),
+        ("/import", post(bulk::import_devices)),
+    ];
+
+    let user: Routes = vec![("/", post(auth::create))];
+
+    let location: Routes = vec![
+        ("/", post(location::create).get(location::get_all)),
+        (
+            "/:id",
+            get(location::get_one)
+                .patch(location::update)
+                .delete(location::delete),
+        ),
+    ];
+
+    let office: Routes = vec![
+        ("/", post(office::create).get(office::get_all)),
+        (
+            "/:id",
+            get(office::get_one)
+                .patch(office::update)
+                .delete(office::delete),
+        ),
+        ("/:id/location", get(office::get_locations)),
+        ("/:id/device", get(office::get_devices)),
+    ];
+
+    let vlan: Routes = vec![
+        ("/", post(vlan::create).get(vlan::get_all)),
+        (
+            "/:id",
+            get(vlan::get_one).patch(vlan::update).delete(vlan::delete),
+        ),
+    ];
+
+    let asset: Routes = vec![
+        ("/", post(asset::create).get(asset::get_all)),
+        (
+            "/:id",
+            get(asset::get_one)
+                .patch(asset::update)
+                .delete(asset::delete),
+        ),
+        (
+            "/:id/interface",
+            get(asset::get_interfaces).post(asset::create_interface),
+        ),
+        (
+            "/:id/interface/:interface_id",
+            patch(asset::update_interface).delete(asset::delete_interface),
+        ),
+        ("/:id/device", get(asset::get_devices)),
+    ];
+
+    let vrf: Routes = vec![
+        ("/", post(vrf::create).get(vrf::get_all)),
+        (
+            "/:id",
+            get(vrf::get_one).patch(vrf::update).delete(vrf::delete),
+        ),
+        ("/:id/network", get(vrf::get_networks)),
+    ];
+
+    let webhook: Routes = vec![
+        ("/", post(webhooks::create).get(webhooks::get_all)),
+        (
+            "/:id",
+            get(webhooks::get_one)
+                .patch(webhooks::update)
+                .delete(webhooks::delete),
+        ),
+        ("/:id/delivery", get(webhooks::get_deliveries)),
+        ("/:id/delivery/:delivery_id", post(webhooks::redeliver)),
+    ];
+
+    let admin: Routes = vec![
+        ("/backup", get(admin::backup)),
+        (
+            "/restore",
+            post(admin::restore).layer(DefaultBodyLimit::max(UPLOAD_LIMIT)),
+        ),
+    ];
+
+    let service: Routes = vec![(
+        "/",
+        post(service::create)
+            .patch(service::update)
+            .delete(service::delete)
+            .get(service::get),
+    )];
+
+    let services: Routes = vec![
+        ("/", post(svcs::create)),
+        (
+            "/:id",
+            patch(svcs::update).get(svcs::get).delete(svcs::delete),
+        ),
+    ];
+
+    let api: Routes = vec![
+        (
+            "/import/:source",
+            post(migration::import).layer(DefaultBodyLimit::max(UPLOAD_LIMIT)),
+        ),
+        ("/events", get(sse::events)),
+        ("/search", get(search::search)),
+        ("/lookup/:ip", get(lookup::lookup)),
+        ("/oui/:mac", get(lookup::vendor)),
+        ("/threshold", get(threshold::get_all)),
+        ("/dns/record", get(zone::get_records)),
+        ("/dns/update", get(zone::get_updates)),
+        ("/dhcp/kea", get(dhcp::kea)),
+        ("/dhcp/dnsmasq", get(dhcp::dnsmasq)),
+        ("/dhcp/lease", post(dhcp::import_leases)),
+        ("/graphql", post(graphql::execute).get(graphql::sdl)),
+        ("/openapi.json", get(openapi::spec)),
+        ("/docs", get(http::docs)),
+    ];
+
+    vec![
+        ("/service", service),
+        ("/services", services),
+        ("/network", network),
+        ("/device", device),
+        ("/location", location),
+        ("/office", office),
+        ("/vlan", vlan),
+        ("/vrf", vrf),
+        ("/asset", asset),
+        ("/webhook", webhook),
+        ("/admin", admin),
+        ("/user", user),
+        ("", api),
+    ]
+}
@@ -0,0 +1,76 @@
+use super::*;
+use network::Network;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Threshold {
+    pub network_id: Uuid,
+    pub warning: u16,
+    pub critical: u16,
+    pub level: AlertLevel,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UpdateThreshold {
+    pub warning: Option<u16>,
+    pub critical: Option<u16>,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    sqlx::Type,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+    ToSchema,
+)]
+pub enum AlertLevel {
+    #[default]
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Threshold {
+    pub fn level(&self, utilization: f64) -> AlertLevel {
+        if utilization >= self.critical as f64 {
+            AlertLevel::Critical
+        } else if utilization >= self.warning as f64 {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Ok
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Alert {
+    pub network: Network,
+    pub level: AlertLevel,
+    pub previous: AlertLevel,
+    pub utilization: f64,
+    pub warning: u16,
+    pub critical: u16,
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:?}] The network {} is at {:.1}% of utilization ({} used of {}, warning {}%, critical {}%)",
+            self.level,
+            self.network.network,
+            self.utilization,
+            *self.network.used,
+            *self.network.available,
+            self.warning,
+            self.critical
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use super::*;
+use mac::MacAddr;
+use utoipa::ToSchema;
+
+/// A piece of hardware, the devices on its interfaces are its ips
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Asset {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateAsset {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Interface {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub name: String,
+    pub mac: Option<MacAddr>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateInterface {
+    pub name: Option<String>,
+    pub mac: Option<String>,
+}
@@ -0,0 +1,575 @@
+use super::*;
+use alert::Threshold;
+use asset::{Asset, Interface};
+use device::Device;
+use dhcp::{DhcpReservation, DhcpScope};
+use dns::{DnsDomain, DnsRecord, DnsUpdate, DnsZone};
+use location::Location;
+use network::Network;
+use office::Office;
+use service::{Service, Services};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::IpAddr,
+};
+use user::{Role, User};
+use utoipa::ToSchema;
+use vlan::VlanEntry;
+use vrf::Vrf;
+use webhook::Webhook;
+
+/// Version of the format of the backups, a restore refuses newer versions
+pub const VERSION: u32 = 1;
+
+/// Every table of the database, with the name of the table as key, but the webhook
+/// deliveries that are a log
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Backup {
+    pub version: u32,
+    pub created_at: i64,
+    /// The credentials of the devices were left out, a restore keeps the ones it has
+    #[serde(default)]
+    pub credentials_excluded: bool,
+    #[serde(default)]
+    pub vrfs: Vec<Vrf>,
+    #[serde(default)]
+    pub offices: Vec<Office>,
+    #[serde(default)]
+    pub location: Vec<Location>,
+    #[serde(default)]
+    pub vlans: Vec<VlanEntry>,
+    #[serde(default)]
+    pub networks: Vec<Network>,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+    #[serde(default)]
+    pub interfaces: Vec<Interface>,
+    #[serde(default)]
+    pub devices: Vec<Device>,
+    #[serde(default)]
+    pub services: Vec<Services>,
+    /// The services of the devices
+    #[serde(default)]
+    pub service: Vec<Service>,
+    #[serde(default)]
+    pub users: Vec<BackupUser>,
+    #[serde(default)]
+    pub thresholds: Vec<Threshold>,
+    #[serde(default)]
+    pub dhcp_scopes: Vec<DhcpScope>,
+    #[serde(default)]
+    pub dhcp_reservations: Vec<DhcpReservation>,
+    #[serde(default)]
+    pub dns_domains: Vec<DnsDomain>,
+    #[serde(default)]
+    pub dns_zones: Vec<DnsZone>,
+    #[serde(default)]
+    pub dns_records: Vec<DnsRecord>,
+    #[serde(default)]
+    pub dns_updates: Vec<DnsUpdate>,
+    #[serde(default)]
+    pub webhooks: Vec<BackupWebhook>,
+}
+
+/// A user whose hash of the password can be left out of the backup
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupUser {
+    pub id: Uuid,
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub role: Role,
+}
+
+/// The secret of a webhook isn't serialized by the api, the backup keeps it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupWebhook {
+    pub id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct RestoreReport {
+    pub version: u32,
+    pub replace: bool,
+    pub dry_run: bool,
+    pub committed: bool,
+    /// Rows of each table in the backup
+    #[schema(value_type = BTreeMap<String, usize>)]
+    pub tables: BTreeMap<&'static str, usize>,
+    /// Users without a password that don't exist in the database
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl From<User> for BackupUser {
+    fn from(value: User) -> Self {
+        Self {
+            id: value.id,
+            username: value.username,
+            password: Some(value.password),
+            role: value.role,
+        }
+    }
+}
+
+impl From<Webhook> for BackupWebhook {
+    fn from(value: Webhook) -> Self {
+        Self {
+            id: value.id,
+            url: value.url,
+            secret: value.secret,
+            events: value.events,
+            description: value.description,
+        }
+    }
+}
+
+impl From<BackupWebhook> for Webhook {
+    fn from(value: BackupWebhook) -> Self {
+        Self {
+            id: value.id,
+            url: value.url,
+            secret: value.secret,
+            events: value.events,
+            description: value.description,
+        }
+    }
+}
+
+impl Backup {
+    pub fn without_credentials(mut self) -> Self {
+        for device in &mut self.devices {
+            device.credential = None;
+        }
+        self.credentials_excluded = true;
+        self
+    }
+
+    pub fn without_passwords(mut self) -> Self {
+        for user in &mut self.users {
+            user.password = None;
+        }
+        self
+    }
+
+    pub fn tables(&self) -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([
+            ("vrfs", self.vrfs.len()),
+            ("offices", self.offices.len()),
+            ("location", self.location.len()),
+            ("vlans", self.vlans.len()),
+            ("networks", self.networks.len()),
+            ("assets", self.assets.len()),
+            ("interfaces", self.interfaces.len()),
+            ("devices", self.devices.len()),
+            ("services", self.services.len()),
+            ("service", self.service.len()),
+            ("users", self.users.len()),
+            ("thresholds", self.thresholds.len()),
+            ("dhcp_scopes", self.dhcp_scopes.len()),
+            ("dhcp_reservations", self.dhcp_reservations.len()),
+            ("dns_domains", self.dns_domains.len()),
+            ("dns_zones", self.dns_zones.len()),
+            ("dns_records", self.dns_records.len()),
+            ("dns_updates", self.dns_updates.len()),
+            ("webhooks", self.webhooks.len()),
+        ])
+    }
+
+    /// The rows that are repeated or reference a row that is neither in the backup nor in
+    /// `existing`, the rows of the database that are kept when the backup is merged
+    pub fn check(&self, existing: Option<&Backup>) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.version > VERSION {
+            errors.push(format!(
+                "The version {} of the backup isn't supported, the last one is {}",
+                self.version, VERSION
+            ));
+            return errors;
+        }
+
+        // The rows of the backup go last, they replace the ones of the database with their key
+        let both = || existing.into_iter().chain(std::iter::once(self));
+
+        repeated(
+            &mut errors,
+            "vrfs",
+            self.vrfs.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "offices",
+            self.offices.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "location",
+            self.location.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "vlans",
+            self.vlans.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "networks",
+            self.networks.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "assets",
+            self.assets.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "interfaces",
+            self.interfaces.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "devices",
+            self.devices
+                .iter()
+                .map(|x| format!("{} in {}", x.ip, x.network_id)),
+        );
+        repeated(
+            &mut errors,
+            "services",
+            self.services.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "users",
+            self.users.iter().map(|x| x.id.to_string()),
+        );
+        repeated(
+            &mut errors,
+            "webhooks",
+            self.webhooks.iter().map(|x| x.id.to_string()),
+        );
+
+        // The unique columns of the rows after the merge
+        let vrfs = merged(&self.vrfs, existing.map(|x| &x.vrfs[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "vrfs",
+            "name",
+            vrfs.iter().map(|x| Some(x.name.clone())),
+        );
+        unique(&mut errors, "vrfs", "rd", vrfs.iter().map(|x| x.rd.clone()));
+
+        let offices = merged(&self.offices, existing.map(|x| &x.offices[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "offices",
+            "name",
+            offices.iter().map(|x| Some(x.name.clone())),
+        );
+        unique(
+            &mut errors,
+            "offices",
+            "address",
+            offices.iter().map(|x| x.address.clone()),
+        );
+
+        let locations = merged(&self.location, existing.map(|x| &x.location[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "location",
+            "label",
+            locations.iter().map(|x| {
+                x.office_id
+                    .map(|id| format!("{} in the office {}", x.label, id))
+            }),
+        );
+
+        let vlans = merged(&self.vlans, existing.map(|x| &x.vlans[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "vlans",
+            "vid",
+            vlans
+                .iter()
+                .map(|x| Some(format!("{} in {}", *x.vid, vlan_scope(x)))),
+        );
+        unique(
+            &mut errors,
+            "vlans",
+            "name",
+            vlans
+                .iter()
+                .map(|x| Some(format!("{} in {}", x.name, vlan_scope(x)))),
+        );
+
+        let assets = merged(&self.assets, existing.map(|x| &x.assets[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "assets",
+            "name",
+            assets.iter().map(|x| Some(x.name.clone())),
+        );
+
+        let interfaces = merged(&self.interfaces, existing.map(|x| &x.interfaces[..]), |x| {
+            x.id
+        });
+        unique(
+            &mut errors,
+            "interfaces",
+            "name",
+            interfaces
+                .iter()
+                .map(|x| Some(format!("{} in the asset {}", x.name, x.asset_id))),
+        );
+
+        let users = merged(&self.users, existing.map(|x| &x.users[..]), |x| x.id);
+        unique(
+            &mut errors,
+            "users",
+            "username",
+            users.iter().map(|x| Some(x.username.clone())),
+        );
+
+        // The scopes without an id get a new one
+        let scopes = merged(
+            &self.dhcp_scopes,
+            existing.map(|x| &x.dhcp_scopes[..]),
+            |x| x.network_id,
+        );
+        unique(
+            &mut errors,
+            "dhcp_scopes",
+            "subnet_id",
+            scopes
+                .iter()
+                .map(|x| Some(x.subnet_id).filter(|x| *x != 0).map(|x| x.to_string())),
+        );
+
+        let reservations = merged(
+            &self.dhcp_reservations,
+            existing.map(|x| &x.dhcp_reservations[..]),
+            |x| (x.network_id, x.mac.to_string()),
+        );
+        unique(
+            &mut errors,
+            "dhcp_reservations",
+            "ip",
+            reservations
+                .iter()
+                .map(|x| Some(format!("{} in {}", x.ip, x.network_id))),
+        );
+
+        let vrfs = both()
+            .flat_map(|x| &x.vrfs)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+        let offices = both()
+            .flat_map(|x| &x.offices)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+        let locations = both()
+            .flat_map(|x| &x.location)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+        let networks = both()
+            .flat_map(|x| &x.networks)
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+        let assets = both()
+            .flat_map(|x| &x.assets)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+        let interfaces = both()
+            .flat_map(|x| &x.interfaces)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+        let devices = both()
+            .flat_map(|x| &x.devices)
+            .map(|x| (x.ip, x.network_id))
+            .collect::<HashSet<(IpAddr, Uuid)>>();
+        let services = both()
+            .flat_map(|x| &x.services)
+            .map(|x| x.id)
+            .collect::<HashSet<_>>();
+
+        for x in &self.location {
+            if x.office_id.is_some_and(|id| !offices.contains(&id)) {
+                errors.push(format!(
+                    "location {}: the office {} doesn't exist",
+                    x.id,
+                    x.office_id.unwrap_or_default()
+                ));
+            }
+        }
+
+        for x in &self.vlans {
+            if x.office_id.is_some_and(|id| !offices.contains(&id)) {
+                errors.push(format!(
+                    "vlans {}: the office {} doesn't exist",
+                    x.id,
+                    x.office_id.unwrap_or_default()
+                ));
+            }
+        }
+
+        for x in &self.networks {
+            if x.vrf.is_some_and(|id| !vrfs.contains(&id)) {
+                errors.push(format!(
+                    "networks {}: the vrf {} doesn't exist",
+                    x.id,
+                    x.vrf.unwrap_or_default()
+                ));
+            }
+            match x.father.map(|id| (id, networks.get(&id))) {
+                Some((id, None)) => errors.push(format!(
+                    "networks {}: the father {} doesn't exist",
+                    x.id, id
+                )),
+                Some((_, Some(father))) if !father.network.contains(&x.network) => {
+                    errors.push(format!(
+                        "networks {}: {} isn't inside its father {}",
+                        x.id, x.network, father.network
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        for x in &self.interfaces {
+            if !assets.contains(&x.asset_id) {
+                errors.push(format!(
+                    "interfaces {}: the asset {} doesn't exist",
+                    x.id, x.asset_id
+                ));
+            }
+        }
+
+        for x in &self.devices {
+            match networks.get(&x.network_id) {
+                None => errors.push(format!(
+                    "devices {}: the network {} doesn't exist",
+                    x.ip, x.network_id
+                )),
+                Some(network) if !network.network.contains(&x.ip) => errors.push(format!(
+                    "devices {}: the ip isn't inside the network {}",
+                    x.ip, network.network
+                )),
+                _ => {}
+            }
+            if x.location.is_some_and(|id| !locations.contains(&id)) {
+                errors.push(format!(
+                    "devices {}: the location {} doesn't exist",
+                    x.ip,
+                    x.location.unwrap_or_default()
+                ));
+            }
+            if x.interface_id.is_some_and(|id| !interfaces.contains(&id)) {
+                errors.push(format!(
+                    "devices {}: the interface {} doesn't exist",
+                    x.ip,
+                    x.interface_id.unwrap_or_default()
+                ));
+            }
+        }
+
+        for x in &self.service {
+            if !devices.contains(&(x.ip, x.netwok_id)) {
+                errors.push(format!(
+                    "service {}:{}: the device {} in {} doesn't exist",
+                    x.ip, *x.port, x.ip, x.netwok_id
+                ));
+            }
+            if !services.contains(&x.service_id) {
+                errors.push(format!(
+                    "service {}:{}: the service {} doesn't exist",
+                    x.ip, *x.port, x.service_id
+                ));
+            }
+        }
+
+        for x in &self.dhcp_reservations {
+            if !devices.contains(&(x.ip, x.network_id)) {
+                errors.push(format!(
+                    "dhcp_reservations {}: the device {} in {} doesn't exist",
+                    x.mac, x.ip, x.network_id
+                ));
+            }
+        }
+
+        let network_tables = [
+            (
+                "thresholds",
+                self.thresholds.iter().map(|x| x.network_id).collect(),
+            ),
+            (
+                "dhcp_scopes",
+                self.dhcp_scopes.iter().map(|x| x.network_id).collect(),
+            ),
+            (
+                "dns_domains",
+                self.dns_domains.iter().map(|x| x.network_id).collect(),
+            ),
+        ];
+        for (table, ids) in network_tables as [(&str, Vec<Uuid>); 3] {
+            for id in ids.into_iter().filter(|x| !networks.contains_key(x)) {
+                errors.push(format!("{} {}: the network doesn't exist", table, id));
+            }
+        }
+
+        errors
+    }
+}
+
+/// The rows of the table after the merge, the rows of the database whose key isn't in the
+/// backup and the rows of the backup
+fn merged<'a, T, K>(rows: &'a [T], existing: Option<&'a [T]>, key: impl Fn(&T) -> K) -> Vec<&'a T>
+where
+    K: Eq + std::hash::Hash,
+{
+    let keys = rows.iter().map(&key).collect::<HashSet<_>>();
+    existing
+        .unwrap_or_default()
+        .iter()
+        .filter(|x| !keys.contains(&key(x)))
+        .chain(rows)
+        .collect()
+}
+
+/// A row without value doesn't repeat it, like a NULL in the database
+fn unique<I>(errors: &mut Vec<String>, table: &str, column: &str, values: I)
+where
+    I: Iterator<Item = Option<String>>,
+{
+    let mut seen = HashSet::new();
+    for value in values.flatten() {
+        if !seen.insert(value.clone()) {
+            errors.push(format!("{} {}: the {} is repeated", table, value, column));
+        }
+    }
+}
+
+/// The office or group of the vlan, its vid and name are unique inside it
+fn vlan_scope(vlan: &VlanEntry) -> String {
+    match (vlan.office_id, &vlan.vlan_group) {
+        (Some(office), _) => format!("the office {}", office),
+        (None, Some(group)) => format!("the group {}", group),
+        (None, None) => "the global scope".to_string(),
+    }
+}
+
+fn repeated<I>(errors: &mut Vec<String>, table: &str, keys: I)
+where
+    I: Iterator<Item = String>,
+{
+    let mut seen = HashSet::new();
+    for key in keys {
+        if !seen.insert(key.clone()) {
+            errors.push(format!("{} {}: the row is repeated", table, key));
+        }
+    }
+}
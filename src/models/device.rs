@@ -1,23 +1,88 @@
 use super::*;
+use mac::MacAddr;
 use std::net::IpAddr;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
 pub struct UpdateDevice {
+    #[schema(value_type = Option<String>)]
     pub ip: Option<IpAddr>,
     pub description: Option<String>,
-    pub location: Option<String>,
+    pub location: Option<Uuid>,
+    pub rack_unit: Option<u16>,
+    pub rack_height: Option<u16>,
     pub network_id: Option<Uuid>,
     pub credential: Option<Credential>,
+    pub hostname: Option<String>,
+    pub fqdn: Option<String>,
+    pub mac: Option<String>,
+    pub interface_id: Option<Uuid>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct Device {
+    #[schema(value_type = String)]
     pub ip: IpAddr,
     pub description: Option<String>,
     pub location: Option<Uuid>,
+    pub rack_unit: Option<u16>,
+    pub rack_height: Option<u16>,
     pub status: Status,
     pub network_id: uuid::Uuid,
     pub credential: Option<Credential>,
+    pub hostname: Option<String>,
+    pub fqdn: Option<String>,
+    pub mac: Option<MacAddr>,
+    pub interface_id: Option<Uuid>,
+    pub last_seen: Option<i64>,
+}
+
+/// What a dhcp lease tells about a device
+#[derive(Debug, Default)]
+pub struct UpdateDeviceLease {
+    pub status: Option<Status>,
+    pub last_seen: Option<i64>,
+    pub mac: Option<MacAddr>,
+    pub hostname: Option<String>,
+}
+
+impl Device {
+    /// A ping or a reservation counts the address as used by the network and its ancestors,
+    /// it stays used while the device is offline
+    pub fn is_taken(&self) -> bool {
+        self.status != Status::Unknown
+    }
+
+    /// A device of `create_all_devices` that nobody has filled in
+    pub fn is_placeholder(&self) -> bool {
+        self.status == Status::Unknown
+            && self.description.is_none()
+            && self.location.is_none()
+            && self.credential.is_none()
+            && self.hostname.is_none()
+            && self.fqdn.is_none()
+            && self.mac.is_none()
+            && self.interface_id.is_none()
+            && self.last_seen.is_none()
+    }
+
+    /// `None` when the device isn't in a rack or its last unit is past `u16::MAX`
+    pub fn rack_units(&self) -> Option<std::ops::RangeInclusive<u16>> {
+        let unit = self.rack_unit?;
+        unit.checked_add(self.rack_height.unwrap_or(1).max(1) - 1)
+            .map(|end| unit..=end)
+    }
+}
+
+impl Device {
+    /// The fqdn or the hostname inside the domain of the network
+    pub fn dns_name(&self, domain: Option<&str>) -> Option<String> {
+        match (&self.fqdn, &self.hostname, domain) {
+            (Some(fqdn), _, _) => Some(fqdn.clone()),
+            (None, Some(hostname), Some(domain)) => Some(format!("{}.{}", hostname, domain)),
+            _ => None,
+        }
+    }
 }
 
 impl std::cmp::PartialEq<IpAddr> for Device {
@@ -26,13 +91,13 @@ impl std::cmp::PartialEq<IpAddr> for Device {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct Credential {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, sqlx::Type, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, sqlx::Type, PartialEq, ToSchema)]
 pub enum Status {
     Reserved,
     Unknown,
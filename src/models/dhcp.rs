@@ -0,0 +1,228 @@
+use super::*;
+use ipnet::IpNet;
+use mac::MacAddr;
+use serde_json::json;
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+pub const DEFAULT_LEASE_TIME: u32 = 86400;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct DhcpScope {
+    pub network_id: Uuid,
+    pub pools: Vec<Pool>,
+    #[schema(value_type = Option<String>)]
+    pub gateway: Option<IpAddr>,
+    #[schema(value_type = Vec<String>)]
+    pub dns_servers: Vec<IpAddr>,
+    pub lease_time: u32,
+    pub options: Vec<DhcpOption>,
+    /// The id of the subnet in Kea, unique and kept while the scope exists
+    #[serde(default)]
+    pub subnet_id: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct Pool {
+    #[schema(value_type = String)]
+    pub start: IpAddr,
+    #[schema(value_type = String)]
+    pub end: IpAddr,
+}
+
+impl Pool {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.start, self.end, ip) {
+            (IpAddr::V4(_), IpAddr::V4(_), IpAddr::V4(_))
+            | (IpAddr::V6(_), IpAddr::V6(_), IpAddr::V6(_)) => self.start <= *ip && *ip <= self.end,
+            _ => false,
+        }
+    }
+
+    pub fn overlaps(&self, other: &Pool) -> bool {
+        self.contains(&other.start) || self.contains(&other.end) || other.contains(&self.start)
+    }
+}
+
+impl std::fmt::Display for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
+
+/// An option by its code, the data is written as it goes in the server configuration
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct DhcpOption {
+    pub code: u16,
+    pub data: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct DhcpReservation {
+    pub network_id: Uuid,
+    #[schema(value_type = String)]
+    pub ip: IpAddr,
+    pub mac: MacAddr,
+    pub hostname: Option<String>,
+}
+
+/// A scope with its network and reservations, as the dhcp servers see it
+pub struct Subnet<'a> {
+    pub network: IpNet,
+    pub scope: &'a DhcpScope,
+    pub reservations: Vec<&'a DhcpReservation>,
+    pub domain: Option<&'a str>,
+}
+
+impl Subnet<'_> {
+    /// Kea needs a numeric id that doesn't change while the subnet exists
+    pub fn id(&self) -> u32 {
+        self.scope.subnet_id
+    }
+
+    pub fn tag(&self) -> String {
+        format!("net-{}", self.scope.network_id.simple())
+    }
+
+    /// The subnet entry of the `subnet4` or `subnet6` list of Kea
+    pub fn kea(&self) -> serde_json::Value {
+        let v4 = self.network.addr().is_ipv4();
+        let mut options = Vec::new();
+
+        if let Some(gateway) = self.scope.gateway.filter(|_| v4) {
+            options.push(json!({ "name": "routers", "data": gateway.to_string() }));
+        }
+        if !self.scope.dns_servers.is_empty() {
+            options.push(json!({
+                "name": if v4 { "domain-name-servers" } else { "dns-servers" },
+                "data": join(&self.scope.dns_servers, ", "),
+            }));
+        }
+        if let Some(domain) = self.domain {
+            options.push(json!({
+                "name": if v4 { "domain-name" } else { "domain-search" },
+                "data": domain,
+            }));
+        }
+        options.extend(
+            self.scope
+                .options
+                .iter()
+                .map(|x| json!({ "code": x.code, "data": x.data })),
+        );
+
+        let reservations = self
+            .reservations
+            .iter()
+            .map(|x| {
+                let mut resp = json!({ "hw-address": x.mac.to_string() });
+                if v4 {
+                    resp["ip-address"] = json!(x.ip.to_string());
+                } else {
+                    resp["ip-addresses"] = json!([x.ip.to_string()]);
+                }
+                if let Some(hostname) = &x.hostname {
+                    resp["hostname"] = json!(hostname);
+                }
+                resp
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "id": self.id(),
+            "subnet": self.network.to_string(),
+            "pools": self.scope.pools.iter().map(|x| json!({ "pool": x.to_string() })).collect::<Vec<_>>(),
+            "valid-lifetime": self.scope.lease_time,
+            "option-data": options,
+            "reservations": reservations,
+        })
+    }
+
+    /// The lines of the subnet in a dnsmasq configuration file, tagged by network
+    pub fn dnsmasq(&self) -> String {
+        let tag = self.tag();
+        let (prefix, mask) = match self.network {
+            IpNet::V4(net) => ("option", net.netmask().to_string()),
+            IpNet::V6(net) => ("option6", net.prefix_len().to_string()),
+        };
+        let addr = |ip: &IpAddr| match ip {
+            IpAddr::V4(ip) => ip.to_string(),
+            IpAddr::V6(ip) => format!("[{}]", ip),
+        };
+
+        let mut resp = format!("# {} ({})\n", self.network, self.scope.network_id);
+        for pool in &self.scope.pools {
+            resp.push_str(&format!(
+                "dhcp-range=set:{},{},{},{},{}\n",
+                tag, pool.start, pool.end, mask, self.scope.lease_time
+            ));
+        }
+        if let Some(gateway) = self.scope.gateway.filter(IpAddr::is_ipv4) {
+            resp.push_str(&format!(
+                "dhcp-option=tag:{},option:router,{}\n",
+                tag, gateway
+            ));
+        }
+        if !self.scope.dns_servers.is_empty() {
+            let servers = self.scope.dns_servers.iter().map(addr).collect::<Vec<_>>();
+            resp.push_str(&format!(
+                "dhcp-option=tag:{},{}:dns-server,{}\n",
+                tag,
+                prefix,
+                servers.join(",")
+            ));
+        }
+        if let Some(domain) = self.domain {
+            let name = if prefix == "option" {
+                "domain-name"
+            } else {
+                "domain-search"
+            };
+            resp.push_str(&format!(
+                "dhcp-option=tag:{},{}:{},{}\n",
+                tag, prefix, name, domain
+            ));
+        }
+        for option in &self.scope.options {
+            resp.push_str(&format!(
+                "dhcp-option=tag:{},{}{},{}\n",
+                tag,
+                if prefix == "option6" { "option6:" } else { "" },
+                option.code,
+                option.data
+            ));
+        }
+        for reservation in &self.reservations {
+            let mut line = format!("dhcp-host={},{}", reservation.mac, addr(&reservation.ip));
+            if let Some(hostname) = &reservation.hostname {
+                line.push_str(&format!(",{}", hostname));
+            }
+            resp.push_str(&line);
+            resp.push('\n');
+        }
+
+        resp
+    }
+}
+
+/// The id of a new subnet, after the ones of every scope
+pub fn next_subnet_id(scopes: &[DhcpScope]) -> u32 {
+    scopes.iter().map(|x| x.subnet_id).max().unwrap_or(0) + 1
+}
+
+/// The whole Kea configuration, the subnets are split by family
+pub fn kea(subnets: &[Subnet]) -> serde_json::Value {
+    let (v4, v6): (Vec<_>, Vec<_>) = subnets.iter().partition(|x| x.network.addr().is_ipv4());
+
+    json!({
+        "Dhcp4": { "subnet4": v4.iter().map(|x| x.kea()).collect::<Vec<_>>() },
+        "Dhcp6": { "subnet6": v6.iter().map(|x| x.kea()).collect::<Vec<_>>() },
+    })
+}
+
+fn join(ips: &[IpAddr], sep: &str) -> String {
+    ips.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(sep)
+}
@@ -0,0 +1,469 @@
+use super::*;
+use device::Device;
+use ipnet::IpNet;
+use network::Network;
+use std::{collections::HashMap, net::IpAddr};
+use utoipa::ToSchema;
+
+pub const DEFAULT_TTL: u32 = 3600;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct DnsDomain {
+    pub network_id: Uuid,
+    pub domain: String,
+    pub ttl: u32,
+    pub nameserver: String,
+    pub hostmaster: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UpdateDnsDomain {
+    pub domain: Option<String>,
+    pub ttl: Option<u32>,
+    pub nameserver: Option<String>,
+    pub hostmaster: Option<String>,
+}
+
+impl DnsDomain {
+    pub fn contains(&self, name: &str) -> bool {
+        name == self.domain || name.ends_with(&format!(".{}", self.domain))
+    }
+}
+
+/// Last serial published for a zone and the hash of the records it was published with. The
+/// reverse zones of every vrf have the same names, so the vrf is part of the key
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DnsZone {
+    pub zone: String,
+    #[serde(default)]
+    pub vrf: Option<Uuid>,
+    pub serial: u32,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UpdateDnsZone {
+    pub serial: Option<u32>,
+    pub hash: Option<String>,
+}
+
+/// A record as it was pushed to the dns server with a dynamic update
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct DnsRecord {
+    pub zone: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub data: String,
+    pub ttl: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq, Eq, Hash, ToSchema)]
+pub enum DnsAction {
+    Add,
+    Delete,
+}
+
+impl DnsRecord {
+    /// The ttl isn't part of the identity of a record, adding it again updates it
+    pub fn key(&self) -> (&str, &str, &str, &str) {
+        (&self.zone, &self.name, &self.kind, &self.data)
+    }
+
+    pub fn from_record(zone: &str, record: Record, ttl: u32) -> Self {
+        Self {
+            zone: zone.to_string(),
+            name: record.name,
+            kind: record.kind.to_string(),
+            data: record.data,
+            ttl,
+        }
+    }
+}
+
+/// A dynamic update waiting to be sent to the dns server
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct DnsUpdate {
+    pub id: Uuid,
+    pub action: DnsAction,
+    #[serde(flatten)]
+    pub record: DnsRecord,
+    pub attempts: u16,
+    pub next_attempt: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UpdateDnsUpdate {
+    pub attempts: Option<u16>,
+    pub next_attempt: Option<i64>,
+    pub error: Option<String>,
+}
+
+impl DnsZone {
+    /// Serials follow the YYYYMMDDnn convention, they never go backwards
+    pub fn next_serial(&self, today: time::Date) -> u32 {
+        let base = today.year() as u32 * 1_000_000
+            + u8::from(today.month()) as u32 * 10_000
+            + today.day() as u32 * 100;
+
+        base.max(self.serial + 1)
+    }
+
+    /// The serial of the zone while its records hash to `hash`
+    pub fn serial_of(&self, hash: &str, today: time::Date) -> u32 {
+        if self.hash == hash {
+            self.serial
+        } else {
+            self.next_serial(today)
+        }
+    }
+}
+
+/// The domain of a network is its own or the one of its nearest ancestor
+pub fn domain_of<'a>(
+    networks: &[Network],
+    domains: &'a [DnsDomain],
+    mut id: Option<Uuid>,
+) -> Option<&'a DnsDomain> {
+    while let Some(tmp) = id {
+        if let Some(domain) = domains.iter().find(|x| x.network_id == tmp) {
+            return Some(domain);
+        }
+        id = networks.iter().find(|x| x.id == tmp).and_then(|x| x.father);
+    }
+    None
+}
+
+/// A device with a name, the domain of its network and the vrf it is in
+#[derive(Debug)]
+pub struct Name<'a> {
+    pub name: String,
+    pub ip: IpAddr,
+    pub domain: Option<&'a str>,
+    pub vrf: Option<Uuid>,
+}
+
+/// The names of every device of every network
+pub fn names<'a>(
+    networks: &[Network],
+    domains: &'a [DnsDomain],
+    devices: &[Device],
+) -> Vec<Name<'a>> {
+    let by_id = networks
+        .iter()
+        .map(|x| (x.id, x))
+        .collect::<HashMap<_, _>>();
+
+    devices
+        .iter()
+        .filter_map(|device| {
+            let network = by_id.get(&device.network_id)?;
+            let domain = domain_of(networks, domains, Some(network.id)).map(|x| x.domain.as_str());
+            Some(Name {
+                name: device.dns_name(domain)?,
+                ip: device.ip,
+                domain,
+                vrf: network.vrf,
+            })
+        })
+        .collect()
+}
+
+/// The forward zone of the domain with the devices of every network it is the domain of,
+/// siblings included
+pub fn forward_zone(settings: &DnsDomain, names: &[Name]) -> Zone {
+    let records = names
+        .iter()
+        .filter(|x| x.domain == Some(settings.domain.as_str()) && settings.contains(&x.name))
+        .map(|x| Record::forward(&x.name, x.ip))
+        .collect();
+
+    Zone::new(settings.domain.clone(), ZoneKind::Forward, records)
+}
+
+/// The reverse zone with the devices of the vrf inside it, whatever their network
+pub fn reverse_zone(reverse: IpNet, vrf: Option<Uuid>, names: &[Name]) -> Zone {
+    let records = names
+        .iter()
+        .filter(|x| x.vrf == vrf && reverse.contains(&x.ip))
+        .map(|x| Record::reverse(&x.name, x.ip))
+        .collect();
+
+    Zone::new(reverse_name(reverse), ZoneKind::Reverse, records)
+}
+
+/// A label as described in RFC 1123
+pub fn is_label(label: &str) -> bool {
+    (1..=63).contains(&label.len())
+        && label.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+}
+
+pub fn is_domain(domain: &str) -> bool {
+    let domain = domain.strip_suffix('.').unwrap_or(domain);
+    domain.len() <= 253 && domain.split('.').all(is_label)
+}
+
+/// Returns the name in lowercase and without the trailing dot
+pub fn normalize(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase()
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ZoneKind {
+    Forward,
+    Reverse,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct Record {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Zone {
+    pub zone: String,
+    pub kind: ZoneKind,
+    pub serial: u32,
+    pub records: Vec<Record>,
+}
+
+impl Record {
+    pub fn forward(name: &str, ip: IpAddr) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: if ip.is_ipv4() { "A" } else { "AAAA" },
+            data: ip.to_string(),
+        }
+    }
+
+    pub fn reverse(name: &str, ip: IpAddr) -> Self {
+        Self {
+            name: ptr_name(ip),
+            kind: "PTR",
+            data: format!("{}.", name),
+        }
+    }
+}
+
+impl Zone {
+    pub fn new(zone: String, kind: ZoneKind, mut records: Vec<Record>) -> Self {
+        records.sort_by(|a, b| (&a.name, &a.data).cmp(&(&b.name, &b.data)));
+        records.dedup_by(|a, b| a.name == b.name && a.kind == b.kind && a.data == b.data);
+
+        Self {
+            zone,
+            kind,
+            serial: 0,
+            records,
+        }
+    }
+
+    /// Hash of the records, the serial only changes when it does
+    pub fn hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for record in &self.records {
+            hasher.update(format!("{} {} {}\n", record.name, record.kind, record.data));
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Renders the zone in the BIND master file format
+    pub fn render(&self, settings: &DnsDomain) -> String {
+        let mut resp = format!(
+            "$ORIGIN {}.\n$TTL {}\n@\tIN\tSOA\t{}.\t{}.\t(\n\t\t{}\t; serial\n\t\t3600\t; refresh\n\t\t900\t; retry\n\t\t1209600\t; expire\n\t\t{}\t; minimum\n\t\t)\n@\tIN\tNS\t{}.\n",
+            self.zone,
+            settings.ttl,
+            normalize(&settings.nameserver),
+            normalize(&settings.hostmaster).replacen('@', ".", 1),
+            self.serial,
+            settings.ttl,
+            normalize(&settings.nameserver),
+        );
+
+        for record in &self.records {
+            let name = if record.name == self.zone {
+                "@"
+            } else {
+                record
+                    .name
+                    .strip_suffix(&format!(".{}", self.zone))
+                    .unwrap_or(&record.name)
+            };
+            resp.push_str(&format!("{}\tIN\t{}\t{}\n", name, record.kind, record.data));
+        }
+
+        resp
+    }
+}
+
+/// Reverse zones are delegated on octet (ipv4) or nibble (ipv6) boundaries, a network
+/// that isn't aligned spans several zones or is part of a bigger one
+pub fn reverse_zones(network: IpNet) -> Vec<IpNet> {
+    let (step, max) = match network {
+        IpNet::V4(_) => (8, 24),
+        IpNet::V6(_) => (4, 124),
+    };
+    let len = network.prefix_len().div_ceil(step) * step;
+
+    if len > max {
+        IpNet::new(network.addr(), max)
+            .map(|x| vec![x.trunc()])
+            .unwrap_or_default()
+    } else {
+        network
+            .subnets(len.max(step))
+            .map(Iterator::collect)
+            .unwrap_or_default()
+    }
+}
+
+pub fn reverse_name(network: IpNet) -> String {
+    let labels = match network.addr() {
+        IpAddr::V4(ip) => ip.octets()[..network.prefix_len() as usize / 8]
+            .iter()
+            .rev()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        IpAddr::V6(ip) => ip
+            .octets()
+            .iter()
+            .flat_map(|x| [x >> 4, x & 0xf])
+            .take(network.prefix_len() as usize / 4)
+            .collect::<Vec<_>>()
+            .iter()
+            .rev()
+            .map(|x| format!("{:x}", x))
+            .collect::<Vec<_>>(),
+    };
+
+    let suffix = if network.addr().is_ipv4() {
+        "in-addr.arpa"
+    } else {
+        "ip6.arpa"
+    };
+
+    if labels.is_empty() {
+        suffix.to_string()
+    } else {
+        format!("{}.{}", labels.join("."), suffix)
+    }
+}
+
+pub fn ptr_name(ip: IpAddr) -> String {
+    reverse_name(IpNet::from(ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(network: &str, father: Option<Uuid>) -> Network {
+        let network: IpNet = network.parse().unwrap();
+        Network {
+            id: Uuid::new_v4(),
+            father,
+            vrf: None,
+            vlan: None,
+            vlan_id: None,
+            network,
+            description: None,
+            available: 0.into(),
+            used: 0.into(),
+            free: 0.into(),
+        }
+    }
+
+    fn domain(network_id: Uuid, domain: &str) -> DnsDomain {
+        DnsDomain {
+            network_id,
+            domain: domain.to_string(),
+            ttl: DEFAULT_TTL,
+            nameserver: format!("ns.{}", domain),
+            hostmaster: format!("hostmaster.{}", domain),
+        }
+    }
+
+    fn device(network_id: Uuid, ip: &str, hostname: &str) -> Device {
+        Device {
+            ip: ip.parse().unwrap(),
+            description: None,
+            location: None,
+            rack_unit: None,
+            rack_height: None,
+            status: device::Status::Online,
+            network_id,
+            credential: None,
+            hostname: Some(hostname.to_string()),
+            fqdn: None,
+            mac: None,
+            interface_id: None,
+            last_seen: None,
+        }
+    }
+
+    #[test]
+    fn zones_have_the_devices_of_every_network() {
+        // Two /25 siblings with the same domain share the forward and the reverse zone
+        let low = network("10.0.0.0/25", None);
+        let high = network("10.0.0.128/25", None);
+        let other = network("10.0.1.0/24", None);
+        let domains = vec![
+            domain(low.id, "example.com"),
+            domain(high.id, "example.com"),
+            domain(other.id, "other.com"),
+        ];
+        let devices = vec![
+            device(low.id, "10.0.0.1", "low"),
+            device(high.id, "10.0.0.129", "high"),
+            device(other.id, "10.0.1.1", "other"),
+        ];
+        let networks = vec![low, high, other];
+        let names = names(&networks, &domains, &devices);
+
+        let forward = forward_zone(&domains[0], &names);
+        let records = forward
+            .records
+            .iter()
+            .map(|x| (x.name.as_str(), x.data.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                ("high.example.com", "10.0.0.129"),
+                ("low.example.com", "10.0.0.1")
+            ]
+        );
+
+        let reverse = reverse_zones(networks[0].network);
+        assert_eq!(reverse, vec!["10.0.0.0/24".parse::<IpNet>().unwrap()]);
+        let reverse = reverse_zone(reverse[0], None, &names);
+        assert_eq!(reverse.zone, "0.0.10.in-addr.arpa");
+        let records = reverse
+            .records
+            .iter()
+            .map(|x| (x.name.as_str(), x.data.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                ("1.0.0.10.in-addr.arpa", "low.example.com."),
+                ("129.0.0.10.in-addr.arpa", "high.example.com.")
+            ]
+        );
+
+        // Another vrf has its own reverse zones
+        assert!(
+            reverse_zone("10.0.0.0/24".parse().unwrap(), Some(Uuid::new_v4()), &names)
+                .records
+                .is_empty()
+        );
+    }
+}
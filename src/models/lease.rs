@@ -0,0 +1,207 @@
+use super::*;
+use mac::MacAddr;
+use std::{collections::HashMap, net::IpAddr};
+
+/// Kea writes the lifetime of the infinite leases as the max of u32
+const INFINITE: i64 = u32::MAX as i64;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Lease {
+    pub ip: IpAddr,
+    pub mac: Option<MacAddr>,
+    pub hostname: Option<String>,
+    /// Last time the client talked to the server
+    pub seen: Option<i64>,
+    pub ends: Option<i64>,
+    pub active: bool,
+}
+
+impl Lease {
+    fn new(ip: IpAddr) -> Self {
+        Self {
+            ip,
+            mac: None,
+            hostname: None,
+            seen: None,
+            ends: None,
+            active: false,
+        }
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.active && self.ends.is_none_or(|x| x > now)
+    }
+}
+
+/// Parses an ISC dhcpd leases file or a Kea lease CSV file, the lines that can't be read
+/// are returned apart. Both are journals, the last lease of an ip is the current one
+pub fn parse(content: &str) -> (Vec<Lease>, Vec<String>) {
+    let kea = content
+        .lines()
+        .map(str::trim)
+        .find(|x| !x.is_empty() && !x.starts_with('#'))
+        .is_some_and(|x| x.starts_with("address,"));
+
+    let (leases, invalid) = if kea {
+        parse_kea(content)
+    } else {
+        parse_dhcpd(content)
+    };
+
+    let mut pos = HashMap::new();
+    let mut resp: Vec<Lease> = Vec::new();
+    for lease in leases {
+        match pos.get(&lease.ip) {
+            Some(&i) => resp[i] = lease,
+            None => {
+                pos.insert(lease.ip, resp.len());
+                resp.push(lease);
+            }
+        }
+    }
+
+    (resp, invalid)
+}
+
+fn parse_dhcpd(content: &str) -> (Vec<Lease>, Vec<String>) {
+    let mut leases = Vec::new();
+    let mut invalid = Vec::new();
+    let mut current: Option<Lease> = None;
+
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(ip) = line.strip_prefix("lease ") {
+            current = match ip.trim_end_matches('{').trim().parse() {
+                Ok(ip) => Some(Lease::new(ip)),
+                Err(_) => {
+                    invalid.push(format!("line {}: {}", n + 1, line));
+                    None
+                }
+            };
+            continue;
+        }
+
+        if line == "}" {
+            leases.extend(current.take());
+            continue;
+        }
+
+        let Some(lease) = current.as_mut() else {
+            continue;
+        };
+
+        let statement = line.trim_end_matches(';');
+        if let Some(mac) = statement.strip_prefix("hardware ethernet ") {
+            lease.mac = mac.parse().ok();
+        } else if let Some(hostname) = statement.strip_prefix("client-hostname ") {
+            lease.hostname = Some(hostname.trim_matches('"').to_string());
+        } else if let Some(state) = statement.strip_prefix("binding state ") {
+            lease.active = state == "active";
+        } else if let Some(date) = statement.strip_prefix("cltt ") {
+            lease.seen = dhcpd_date(date).or(lease.seen);
+        } else if let Some(date) = statement.strip_prefix("starts ") {
+            lease.seen = lease.seen.or(dhcpd_date(date));
+        } else if let Some(date) = statement.strip_prefix("ends ") {
+            lease.ends = dhcpd_date(date);
+        }
+    }
+
+    (leases, invalid)
+}
+
+/// Dates are `<weekday> yyyy/mm/dd hh:mm:ss` in UTC or `epoch <seconds>`, the end of a lease
+/// can also be `never`
+fn dhcpd_date(date: &str) -> Option<i64> {
+    let date = date
+        .split('#')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_end_matches(';');
+    if let Some(epoch) = date.strip_prefix("epoch ") {
+        return epoch.trim().parse().ok();
+    }
+
+    let (_, date) = date.split_once(' ')?;
+    let values = date
+        .split(['/', ' ', ':'])
+        .map(|x| x.parse::<i32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let [year, month, day, hour, minute, second] = values[..] else {
+        return None;
+    };
+
+    let date = time::Date::from_calendar_date(
+        year,
+        time::Month::try_from(u8::try_from(month).ok()?).ok()?,
+        u8::try_from(day).ok()?,
+    )
+    .ok()?;
+    let time = time::Time::from_hms(
+        u8::try_from(hour).ok()?,
+        u8::try_from(minute).ok()?,
+        u8::try_from(second).ok()?,
+    )
+    .ok()?;
+
+    Some(
+        time::PrimitiveDateTime::new(date, time)
+            .assume_utc()
+            .unix_timestamp(),
+    )
+}
+
+/// The columns are found by the header, lease4 and lease6 files share the ones we need
+fn parse_kea(content: &str) -> (Vec<Lease>, Vec<String>) {
+    let mut leases = Vec::new();
+    let mut invalid = Vec::new();
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, x)| !x.trim().is_empty() && !x.starts_with('#'));
+
+    let Some((_, header)) = lines.next() else {
+        return (leases, invalid);
+    };
+    let header = header.trim().split(',').collect::<Vec<_>>();
+    let column = |name: &str| header.iter().position(|x| *x == name);
+    let (Some(address), Some(expire)) = (column("address"), column("expire")) else {
+        invalid.push("The header doesn't have the address and expire columns".to_string());
+        return (leases, invalid);
+    };
+    let hwaddr = column("hwaddr");
+    let lifetime = column("valid_lifetime");
+    let hostname = column("hostname");
+    let state = column("state");
+
+    for (n, line) in lines {
+        let values = line.trim().split(',').collect::<Vec<_>>();
+        let get = |pos: Option<usize>| pos.and_then(|x| values.get(x)).filter(|x| !x.is_empty());
+
+        let (Some(Ok(ip)), Some(Ok(expire))) = (
+            get(Some(address)).map(|x| x.parse::<IpAddr>()),
+            get(Some(expire)).map(|x| x.parse::<i64>()),
+        ) else {
+            invalid.push(format!("line {}: {}", n + 1, line));
+            continue;
+        };
+        let lifetime = get(lifetime).and_then(|x| x.parse::<i64>().ok());
+
+        leases.push(Lease {
+            ip,
+            mac: get(hwaddr).and_then(|x| x.parse().ok()),
+            // Kea escapes the commas of the values
+            hostname: get(hostname).map(|x| x.replace("&#x2c", ",")),
+            seen: lifetime.filter(|x| *x != INFINITE).map(|x| expire - x),
+            ends: Some(expire).filter(|_| lifetime != Some(INFINITE)),
+            // 0 is assigned, then declined, expired-reclaimed and released
+            active: get(state).is_none_or(|x| *x == "0"),
+        });
+    }
+
+    (leases, invalid)
+}
@@ -1 +1,38 @@
+use super::*;
+use utoipa::ToSchema;
 
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Location {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub location_type: LocationType,
+    pub label: String,
+    pub units: Option<u16>,
+    pub office_id: Option<Uuid>,
+}
+
+impl Location {
+    pub const DEFAULT_RACK_UNITS: u16 = 42;
+
+    pub fn rack_units(&self) -> u16 {
+        self.units.unwrap_or(Self::DEFAULT_RACK_UNITS)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateLocation {
+    #[serde(rename = "type")]
+    pub location_type: Option<LocationType>,
+    pub label: Option<String>,
+    pub units: Option<u16>,
+    pub office_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+pub enum LocationType {
+    Rack,
+    Desk,
+    #[serde(rename = "Rack Cabinet")]
+    #[sqlx(rename = "Rack Cabinet")]
+    RackCabinet,
+}
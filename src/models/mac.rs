@@ -0,0 +1,240 @@
+use super::*;
+use serde::{de, Deserializer, Serializer};
+use std::{collections::HashMap, sync::OnceLock};
+use utoipa::{
+    openapi::{ObjectBuilder, RefOr, Schema, Type},
+    PartialSchema, ToSchema,
+};
+
+/// Organizationally unique identifiers of common vendors, `OUI_FILE` replaces it with a
+/// full table like the IEEE oui.txt or the Wireshark manuf file
+const OUI: &str = include_str!("oui.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+#[derive(Debug)]
+pub struct MacAddrError;
+
+impl std::fmt::Display for MacAddrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "A mac address must be six bytes like aa:bb:cc:dd:ee:ff, aa-bb-cc-dd-ee-ff or aabb.ccdd.eeff"
+        )
+    }
+}
+
+impl std::error::Error for MacAddrError {}
+
+impl std::str::FromStr for MacAddr {
+    type Err = MacAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The first separator is the one of every group
+        let separator = s.chars().find(|x| matches!(x, ':' | '-' | '.'));
+        let groups = match separator {
+            Some(separator) => s.split(separator).collect::<Vec<_>>(),
+            None => vec![s],
+        };
+        let digits = groups.concat();
+
+        let valid_groups = match (separator, groups.len()) {
+            (Some(':' | '-'), 6) => groups.iter().all(|x| x.len() == 2),
+            (Some('.'), 3) => groups.iter().all(|x| x.len() == 4),
+            (None, 1) => true,
+            _ => false,
+        };
+        if !valid_groups || digits.len() != 12 || !digits.chars().all(|x| x.is_ascii_hexdigit()) {
+            return Err(MacAddrError);
+        }
+
+        let mut mac = [0; 6];
+        for (i, byte) in mac.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).map_err(|_| MacAddrError)?;
+        }
+
+        Ok(Self(mac))
+    }
+}
+
+impl std::fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+impl Serialize for MacAddr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl PartialSchema for MacAddr {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .examples(["aa:bb:cc:dd:ee:ff"])
+            .into()
+    }
+}
+
+impl ToSchema for MacAddr {}
+
+impl<'de> Deserialize<'de> for MacAddr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl MacAddr {
+    pub fn oui(&self) -> [u8; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// Randomized or virtual addresses don't belong to any vendor
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    pub fn vendor(&self) -> Option<&'static str> {
+        if self.is_local() || self.is_multicast() {
+            return None;
+        }
+        vendors().get(&self.oui()).map(String::as_str)
+    }
+}
+
+fn vendors() -> &'static HashMap<[u8; 3], String> {
+    static VENDORS: OnceLock<HashMap<[u8; 3], String>> = OnceLock::new();
+
+    VENDORS.get_or_init(|| {
+        let file = std::env::var("OUI_FILE").ok().and_then(|path| {
+            std::fs::read_to_string(&path)
+                .inspect_err(|e| tracing::error!("Can't read the oui table {}: {}", path, e))
+                .ok()
+        });
+        let table = parse_oui(file.as_deref().unwrap_or(OUI));
+        tracing::debug!("Loaded {} vendors", table.len());
+        table
+    })
+}
+
+/// Every line starts with the prefix (`00:00:0C`, `00-00-0C` or `00000C`) followed by
+/// the vendor, the lines that don't are ignored. The IEEE file repeats every prefix in a
+/// `(base 16)` line and indents the address of the vendor, they are skipped
+fn parse_oui(table: &str) -> HashMap<[u8; 3], String> {
+    table
+        .lines()
+        .filter(|x| !x.starts_with('#') && !x.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let (prefix, vendor) = line.trim().split_once(char::is_whitespace)?;
+            let vendor = vendor.trim_start();
+            if vendor.starts_with("(base 16)") {
+                return None;
+            }
+            let digits = prefix
+                .chars()
+                .filter(|x| !matches!(x, ':' | '-' | '.'))
+                .collect::<String>();
+            if digits.len() != 6 || !digits.chars().all(|x| x.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            let mut oui = [0; 3];
+            for (i, byte) in oui.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).ok()?;
+            }
+
+            let vendor = vendor.strip_prefix("(hex)").unwrap_or(vendor).trim();
+            (!vendor.is_empty()).then(|| (oui, vendor.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_needs_a_single_separator() {
+        let mac = MacAddr([0xaa, 0xbb, 0xcc, 0x00, 0x11, 0x22]);
+        for text in [
+            "aa:bb:cc:00:11:22",
+            "AA-BB-CC-00-11-22",
+            "aabb.cc00.1122",
+            "aabbcc001122",
+        ] {
+            assert_eq!(text.parse::<MacAddr>().unwrap(), mac, "{}", text);
+        }
+
+        for text in [
+            "aa:bb-cc:00-11:22",
+            "aa:bb:cc:00:11-22",
+            "aabb.cc00-1122",
+            "aa.bb.cc.00.11.22",
+            "aabb:cc00:1122",
+            "aa:bb:cc:00:11",
+            "aa:bb:cc:00:11:2g",
+        ] {
+            assert!(text.parse::<MacAddr>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_oui_reads_the_ieee_file() {
+        let table = parse_oui(
+            "OUI/MA-L                                                    Organization
+company_id                                                  Organization
+                                                            Address
+
+00-22-72   (hex)\t\tAmerican Micro-Fuel Device Corp.
+002272     (base 16)\t\tAmerican Micro-Fuel Device Corp.
+\t\t\t\t2181 Buchanan Loop
+\t\t\t\tFerndale  WA  98248
+\t\t\t\tUS
+
+00-D0-EF   (hex)\t\tIGT
+00D0EF     (base 16)\t\tIGT
+\t\t\t\t9295 PROTOTYPE DRIVE
+\t\t\t\tRENO  NV  89511
+\t\t\t\tUS
+
+08-61-95   (hex)\t\tRockwell Automation
+086195     (base 16)\t\tRockwell Automation
+\t\t\t\t1 Allen-Bradley Dr.
+\t\t\t\tMayfield Heights  OH  44124-6118
+\t\t\t\tUS
+",
+        );
+
+        assert_eq!(
+            table,
+            HashMap::from([
+                (
+                    [0x00, 0x22, 0x72],
+                    "American Micro-Fuel Device Corp.".to_string()
+                ),
+                ([0x00, 0xd0, 0xef], "IGT".to_string()),
+                ([0x08, 0x61, 0x95], "Rockwell Automation".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use super::*;
+use device::Status;
+use ipnet::IpNet;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::IpAddr,
+};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    PhpIpam,
+    NetBox,
+}
+
+/// The exports of each kind of object, a section is the body of a response of the api of
+/// the source (json) or the content of a csv file
+#[derive(Debug, Deserialize, Default)]
+pub struct Export {
+    pub vrfs: Option<Value>,
+    #[serde(alias = "locations")]
+    pub sites: Option<Value>,
+    pub vlans: Option<Value>,
+    #[serde(alias = "subnets")]
+    pub prefixes: Option<Value>,
+    #[serde(alias = "ip_addresses", alias = "ip-addresses")]
+    pub addresses: Option<Value>,
+}
+
+/// A row of an export, the keys are in lowercase and the nested objects of the json
+/// exports are flattened (`vrf.name`)
+#[derive(Debug, Default)]
+pub struct Fields {
+    source: Option<Source>,
+    values: BTreeMap<String, String>,
+    used: HashSet<String>,
+}
+
+/// Something referenced by its id in the source or by its name
+#[derive(Debug, Default, Clone)]
+pub struct Reference {
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ExtVrf {
+    pub id: Option<String>,
+    pub name: String,
+    pub rd: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ExtSite {
+    pub id: Option<String>,
+    pub name: String,
+    pub address: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ExtVlan {
+    pub id: Option<String>,
+    pub vid: u16,
+    pub name: String,
+    pub description: Option<String>,
+    pub site: Reference,
+    pub group: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ExtPrefix {
+    pub id: Option<String>,
+    pub network: IpNet,
+    pub description: Option<String>,
+    pub vrf: Reference,
+    pub vlan: Reference,
+    pub vid: Option<u16>,
+}
+
+#[derive(Debug)]
+pub struct ExtAddress {
+    pub ip: IpAddr,
+    /// The prefix of the address in the source
+    pub subnet: Option<String>,
+    pub vrf: Reference,
+    pub status: Option<Status>,
+    pub dns_name: Option<String>,
+    pub description: Option<String>,
+    pub mac: Option<String>,
+}
+
+/// The rows of a section: a csv, a list of objects or a response of the api of phpIPAM
+/// (`data`) or NetBox (`results`)
+pub fn rows(source: Source, section: &Value) -> Result<Vec<Fields>, String> {
+    let list = match section {
+        Value::String(csv) => return rows_csv(source, csv),
+        Value::Array(list) => list,
+        Value::Object(obj) => match obj.get("data").or(obj.get("results")) {
+            Some(Value::Array(list)) => list,
+            _ => return Err("The json must be a list, or have it in data or results".into()),
+        },
+        _ => return Err("A section must be a csv or a json list".into()),
+    };
+
+    list.iter()
+        .map(|x| match x {
+            Value::Object(_) => {
+                let mut fields = Fields {
+                    source: Some(source),
+                    ..Default::default()
+                };
+                flatten(&mut fields.values, "", x);
+                Ok(fields)
+            }
+            _ => Err(format!("The element {} isn't an object", x)),
+        })
+        .collect()
+}
+
+fn rows_csv(source: Source, content: &str) -> Result<Vec<Fields>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let header = reader
+        .headers()
+        .map_err(|x| x.to_string())?
+        .iter()
+        .map(|x| x.to_lowercase().replace([' ', '-'], "_"))
+        .collect::<Vec<_>>();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|x| x.to_string())?;
+            Ok(Fields {
+                source: Some(source),
+                values: header
+                    .iter()
+                    .cloned()
+                    .zip(record.iter().map(str::to_string))
+                    .collect(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+fn flatten(values: &mut BTreeMap<String, String>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(obj) => {
+            for (key, value) in obj {
+                let key = key.to_lowercase();
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(values, &key, value);
+            }
+        }
+        Value::Null => {}
+        Value::Array(list) if list.is_empty() => {}
+        Value::String(s) => {
+            values.insert(prefix.to_string(), s.clone());
+        }
+        _ => {
+            values.insert(prefix.to_string(), value.to_string());
+        }
+    }
+}
+
+impl Fields {
+    /// The first of the keys with a value, the keys are taken as mapped
+    pub fn get(&mut self, keys: &[&str]) -> Option<String> {
+        let mut resp = None;
+        for key in keys {
+            self.used.insert(top(key).to_string());
+            if resp.is_none() {
+                resp = self
+                    .values
+                    .get(*key)
+                    .map(|x| x.trim())
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_string);
+            }
+        }
+        resp
+    }
+
+    /// Like `get`, but phpIPAM uses `0` when nothing is referenced
+    fn id(&mut self, keys: &[&str]) -> Option<String> {
+        let phpipam = self.source == Some(Source::PhpIpam);
+        self.get(keys).filter(|x| !(phpipam && x == "0"))
+    }
+
+    fn reference(&mut self, ids: &[&str], names: &[&str]) -> Reference {
+        Reference {
+            id: self.id(ids),
+            name: self.get(names),
+        }
+    }
+
+    /// The fields with a value that weren't mapped
+    pub fn unsupported(&self) -> Vec<&str> {
+        let mut resp = self
+            .values
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, _)| top(k))
+            .filter(|k| !self.used.contains(*k) && !IGNORED.contains(k))
+            .collect::<Vec<_>>();
+        resp.dedup();
+        resp
+    }
+}
+
+/// Metadata of the apis and values they compute, they don't describe the objects
+const IGNORED: [&str; 10] = [
+    "id",
+    "url",
+    "display",
+    "display_url",
+    "family",
+    "_depth",
+    "children",
+    "created",
+    "last_updated",
+    "editdate",
+];
+
+fn top(key: &str) -> &str {
+    key.split('.').next().unwrap_or_default()
+}
+
+impl Reference {
+    pub fn is_empty(&self) -> bool {
+        self.id.is_none() && self.name.is_none()
+    }
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.name, &self.id) {
+            (Some(name), _) => write!(f, "{}", name),
+            (None, Some(id)) => write!(f, "#{}", id),
+            (None, None) => write!(f, "-"),
+        }
+    }
+}
+
+impl Fields {
+    pub fn vrf(&mut self) -> Result<ExtVrf, String> {
+        Ok(ExtVrf {
+            id: self.id(&["id", "vrfid"]),
+            name: self.get(&["name"]).ok_or("The vrf doesn't have a name")?,
+            rd: self.get(&["rd"]),
+            description: self.get(&["description"]),
+        })
+    }
+
+    pub fn site(&mut self) -> Result<ExtSite, String> {
+        Ok(ExtSite {
+            id: self.id(&["id"]),
+            name: self.get(&["name"]).ok_or("The site doesn't have a name")?,
+            address: self.get(&["physical_address", "address"]),
+            description: self.get(&["description"]),
+        })
+    }
+
+    pub fn vlan(&mut self) -> Result<ExtVlan, String> {
+        let vid = self
+            .get(&["vid", "number"])
+            .ok_or("The vlan doesn't have a number")?;
+        Ok(ExtVlan {
+            id: self.id(&["id", "vlanid"]),
+            vid: vid
+                .parse()
+                .map_err(|_| format!("The vlan number {} isn't valid", vid))?,
+            name: self.get(&["name"]).ok_or("The vlan doesn't have a name")?,
+            description: self.get(&["description"]),
+            site: self.reference(&["site.id"], &["site.name", "site"]),
+            group: self.get(&["group.name", "group"]),
+        })
+    }
+
+    /// The folders of phpIPAM don't have a network and are skipped
+    pub fn prefix(&mut self) -> Result<Option<ExtPrefix>, String> {
+        if self.get(&["isfolder"]).is_some_and(|x| x == "1") {
+            return Ok(None);
+        }
+
+        let network = match (self.get(&["prefix", "subnet"]), self.get(&["mask"])) {
+            (Some(subnet), Some(mask)) if !subnet.contains('/') => format!("{}/{}", subnet, mask),
+            (Some(subnet), _) => subnet,
+            (None, _) => return Err("The prefix doesn't have a network".to_string()),
+        };
+        // The hierarchy is rebuilt from the networks
+        self.get(&["mastersubnetid"]);
+        let vid = self
+            .get(&["vlan.vid"])
+            .map(|x| x.parse().map_err(|_| format!("The vlan {} isn't valid", x)))
+            .transpose()?;
+
+        Ok(Some(ExtPrefix {
+            id: self.id(&["id"]),
+            network: network
+                .parse::<IpNet>()
+                .map_err(|x| format!("{}: {}", network, x))?
+                .trunc(),
+            description: self.get(&["description"]),
+            vrf: self.reference(&["vrf.id", "vrfid"], &["vrf.name", "vrf"]),
+            vlan: self.reference(&["vlan.id", "vlanid"], &["vlan.name", "vlan"]),
+            vid,
+        }))
+    }
+
+    pub fn address(&mut self) -> Result<ExtAddress, String> {
+        let address = self
+            .get(&["address", "ip", "ip_addr", "ip_address"])
+            .ok_or("The address doesn't have an ip")?;
+        let ip = address.split('/').next().unwrap_or_default();
+
+        let status = match self.get(&["status.value", "status", "tag", "state", "ip_state"]) {
+            Some(value) => {
+                Some(status(&value).ok_or(format!("The status {} isn't supported", value))?)
+            }
+            None => None,
+        };
+
+        Ok(ExtAddress {
+            ip: ip
+                .parse()
+                .map_err(|_| format!("The ip {} isn't valid", ip))?,
+            subnet: self.id(&["subnetid"]),
+            vrf: self.reference(&["vrf.id", "vrfid"], &["vrf.name", "vrf"]),
+            status,
+            dns_name: self.get(&["dns_name", "hostname"]),
+            description: self.get(&["description"]),
+            mac: self.get(&["mac"]),
+        })
+    }
+}
+
+/// The tags of phpIPAM (by id or name) and the statuses of NetBox
+fn status(value: &str) -> Option<Status> {
+    match value.to_lowercase().as_str() {
+        "1" | "offline" | "deprecated" => Some(Status::Offline),
+        "2" | "used" | "active" | "online" => Some(Status::Online),
+        "3" | "reserved" => Some(Status::Reserved),
+        "4" | "dhcp" | "slaac" | "unknown" => Some(Status::Unknown),
+        _ => None,
+    }
+}
@@ -1,16 +1,30 @@
+pub mod alert;
+pub mod asset;
+pub mod backup;
 pub mod device;
+pub mod dhcp;
+pub mod dns;
+pub mod netbox;
 pub mod network;
 pub mod service;
 pub mod user;
+pub mod lease;
 pub mod location;
+pub mod mac;
+pub mod migration;
 pub mod utils;
+pub mod vlan;
+pub mod vrf;
+pub mod webhook;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub mod office {
     use super::*;
-    #[derive(Debug, Deserialize, Serialize, Clone)]
+    use utoipa::ToSchema;
+
+    #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
     pub struct Office {
         pub id: Uuid,
         pub name: String,
@@ -18,7 +32,9 @@ pub mod office {
         pub description: Option<String>,
     }
 
+    #[derive(Debug, Deserialize, Serialize, Default, ToSchema)]
     pub struct UpdateOffice {
+        pub name: Option<String>,
         pub description: Option<String>,
         pub address: Option<String>,
     }
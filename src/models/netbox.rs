@@ -0,0 +1,175 @@
+use super::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// NetBox paginates every list with `limit` and `offset`
+pub const PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub count: usize,
+    pub next: Option<String>,
+    pub previous: Option<String>,
+    pub results: Vec<T>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct Choice {
+    pub value: &'static str,
+    pub label: &'static str,
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct Family {
+    pub value: u8,
+    pub label: &'static str,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NestedVrf {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub name: String,
+    pub rd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NestedVlan {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub vid: u16,
+    pub name: String,
+}
+
+/// Sites and vlan groups
+#[derive(Debug, Serialize, Clone)]
+pub struct NestedObject {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub name: String,
+    pub slug: String,
+}
+
+/// The fields of NetBox that don't exist here, they are always empty
+#[derive(Debug, Serialize, Default)]
+pub struct Unsupported {
+    pub tenant: Option<()>,
+    pub role: Option<()>,
+    pub comments: String,
+    pub tags: Vec<()>,
+    pub custom_fields: HashMap<String, ()>,
+    pub created: Option<()>,
+    pub last_updated: Option<()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Prefix {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub family: Family,
+    pub prefix: String,
+    pub vrf: Option<NestedVrf>,
+    pub vlan: Option<NestedVlan>,
+    pub status: Choice,
+    pub is_pool: bool,
+    pub mark_utilized: bool,
+    pub description: String,
+    pub children: usize,
+    #[serde(rename = "_depth")]
+    pub depth: usize,
+    #[serde(flatten)]
+    pub unsupported: Unsupported,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IpAddress {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub family: Family,
+    pub address: String,
+    pub vrf: Option<NestedVrf>,
+    pub status: Choice,
+    pub assigned_object_type: Option<()>,
+    pub assigned_object_id: Option<()>,
+    pub assigned_object: Option<()>,
+    pub nat_inside: Option<()>,
+    pub nat_outside: Vec<()>,
+    pub dns_name: String,
+    pub description: String,
+    #[serde(flatten)]
+    pub unsupported: Unsupported,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Vlan {
+    pub id: u32,
+    pub url: String,
+    pub display: String,
+    pub site: Option<NestedObject>,
+    pub group: Option<NestedObject>,
+    pub vid: u16,
+    pub name: String,
+    pub status: Choice,
+    pub description: String,
+    pub prefix_count: usize,
+    #[serde(flatten)]
+    pub unsupported: Unsupported,
+}
+
+pub const ACTIVE: Choice = Choice {
+    value: "active",
+    label: "Active",
+};
+
+pub const RESERVED: Choice = Choice {
+    value: "reserved",
+    label: "Reserved",
+};
+
+pub const DEPRECATED: Choice = Choice {
+    value: "deprecated",
+    label: "Deprecated",
+};
+
+impl Family {
+    pub fn of(ipv4: bool) -> Self {
+        if ipv4 {
+            Self {
+                value: 4,
+                label: "IPv4",
+            }
+        } else {
+            Self {
+                value: 6,
+                label: "IPv6",
+            }
+        }
+    }
+}
+
+/// The ids of NetBox are integers, they are derived from the keys of the objects so they are
+/// always the same
+pub fn id(key: &[u8]) -> u32 {
+    let hash = Sha256::digest(key);
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]) % (i32::MAX as u32) + 1
+}
+
+pub fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|x: char| !x.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The body of the errors of NetBox
+#[derive(Debug, Serialize)]
+pub struct Detail {
+    pub detail: &'static str,
+}
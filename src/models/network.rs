@@ -4,12 +4,26 @@ use serde::{
     de::{self, Visitor},
     Deserialize, Serialize,
 };
+use std::collections::{HashMap, HashSet};
+use utoipa::{
+    openapi::{ObjectBuilder, RefOr, Schema, Type},
+    PartialSchema, ToSchema,
+};
+use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct UpdateNetwork {
+    #[schema(value_type = Option<String>)]
     pub network: Option<IpNet>,
     pub description: Option<String>,
     pub vlan: Option<Vlan>,
+    pub vlan_id: Option<Uuid>,
+}
+
+impl Network {
+    pub fn overlaps(&self, other: &IpNet) -> bool {
+        self.network.contains(other) || other.contains(&self.network)
+    }
 }
 
 #[derive(Debug)]
@@ -19,34 +33,91 @@ pub struct UpdateNetworkCount {
     pub available: Option<HostCount>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct Network {
     pub id: uuid::Uuid,
     pub father: Option<uuid::Uuid>,
+    pub vrf: Option<uuid::Uuid>,
     pub vlan: Option<Vlan>,
+    /// The entry of the registry of vlans
+    pub vlan_id: Option<Uuid>,
+    #[schema(value_type = String)]
     pub network: IpNet,
     pub description: Option<String>,
+    #[schema(value_type = u64)]
     pub available: HostCount,
+    #[schema(value_type = u64)]
     pub used: HostCount,
+    #[schema(value_type = u64)]
     pub free: HostCount,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworkTree {
+    #[serde(flatten)]
+    pub network: Network,
+    pub utilization: f64,
+    #[schema(no_recursion)]
+    pub children: Vec<NetworkTree>,
+}
+
+impl NetworkTree {
+    /// Builds the trees whose roots are the networks without a father in `networks`
+    pub fn build(networks: Vec<Network>) -> Vec<Self> {
+        let ids = networks.iter().map(|x| x.id).collect::<HashSet<_>>();
+        let mut children: HashMap<Option<Uuid>, Vec<Network>> = HashMap::new();
+
+        for network in networks {
+            let father = network.father.filter(|x| ids.contains(x));
+            children.entry(father).or_default().push(network);
+        }
+
+        Self::nodes(None, &mut children)
+    }
+
+    fn nodes(
+        father: Option<Uuid>,
+        children: &mut HashMap<Option<Uuid>, Vec<Network>>,
+    ) -> Vec<Self> {
+        let mut networks = children.remove(&father).unwrap_or_default();
+        networks.sort_by_key(|x| x.network);
+
+        networks
+            .into_iter()
+            .map(|network| {
+                let children = Self::nodes(Some(network.id), children);
+                // The counters of a network already include the ones of its subnets
+                let available = *network.available as u64;
+                let used = *network.used as u64;
+
+                Self {
+                    network,
+                    utilization: if available == 0 {
+                        0.0
+                    } else {
+                        used as f64 * 100.0 / available as f64
+                    },
+                    children,
+                }
+            })
+            .collect()
+    }
+}
+
 impl std::ops::Deref for Vlan {
     type Target = u16;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
-impl std::ops::DerefMut for Vlan {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Vlan(u16);
 
 impl Vlan {
+    pub const MIN: u16 = 1;
+    pub const MAX: u16 = 4094;
+
     pub fn new(num: u16) -> Result<Self, VlanError> {
         let mut tmp = Self(0);
         tmp.set_vlan(num)?;
@@ -54,10 +125,10 @@ impl Vlan {
     }
 
     pub fn set_vlan(&mut self, num: u16) -> Result<(), VlanError> {
-        if num > 4096 {
+        if !(Self::MIN..=Self::MAX).contains(&num) {
             return Err(VlanError::InvalidVlan);
         }
-        **self = num;
+        self.0 = num;
 
         Ok(())
     }
@@ -68,6 +139,21 @@ pub enum VlanError {
     InvalidVlan,
 }
 
+impl std::fmt::Display for VlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidVlan => write!(
+                f,
+                "The vlan id must be between {} and {}",
+                Vlan::MIN,
+                Vlan::MAX
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VlanError {}
+
 impl Serialize for Vlan {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -77,6 +163,18 @@ impl Serialize for Vlan {
     }
 }
 
+impl PartialSchema for Vlan {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::Integer)
+            .minimum(Some(Self::MIN))
+            .maximum(Some(Self::MAX))
+            .into()
+    }
+}
+
+impl ToSchema for Vlan {}
+
 impl<'de> Deserialize<'de> for Vlan {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -95,7 +193,25 @@ impl<'de> Visitor<'de> for VlanVisitor {
     where
         E: serde::de::Error,
     {
-        Ok(Vlan(v))
+        Vlan::new(v).map_err(de::Error::custom)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u16::try_from(v)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(v), &self))
+            .and_then(|x| self.visit_u16(x))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u16::try_from(v)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Signed(v), &self))
+            .and_then(|x| self.visit_u16(x))
     }
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -106,11 +222,64 @@ impl<'de> Visitor<'de> for VlanVisitor {
     where
         E: serde::de::Error,
     {
-        v.parse::<u16>().map(Vlan).map_err(|_| {
-            de::Error::invalid_value(
-                de::Unexpected::Str(v),
-                &"An string that representing a valid Vlan id",
-            )
-        })
+        v.parse::<u16>()
+            .map_err(|_| {
+                de::Error::invalid_value(
+                    de::Unexpected::Str(v),
+                    &"An string that representing a valid Vlan id",
+                )
+            })
+            .and_then(|x| self.visit_u16(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(id: u128, father: Option<u128>, net: &str, used: u32) -> Network {
+        let network = net.parse::<IpNet>().unwrap();
+        let available = match network {
+            IpNet::V4(x) => (1u32 << (32 - x.prefix_len())) - 2,
+            IpNet::V6(_) => unreachable!(),
+        };
+        Network {
+            id: Uuid::from_u128(id),
+            father: father.map(Uuid::from_u128),
+            vrf: None,
+            vlan: None,
+            vlan_id: None,
+            network,
+            description: None,
+            available: available.into(),
+            used: used.into(),
+            free: (available - used).into(),
+        }
+    }
+
+    #[test]
+    fn tree_nests_the_subnets_with_their_utilization() {
+        // 10.0.0.0/16 -> 10.0.0.0/24 -> 10.0.0.0/26, a device in the /26 counts in the three
+        let networks = vec![
+            network(3, Some(2), "10.0.0.0/26", 1),
+            network(1, None, "10.0.0.0/16", 1),
+            network(2, Some(1), "10.0.0.0/24", 1),
+        ];
+
+        let tree = NetworkTree::build(networks);
+        assert_eq!(tree.len(), 1);
+
+        let root = &tree[0];
+        assert_eq!(root.network.id, Uuid::from_u128(1));
+        assert!((root.utilization - 100.0 / 65534.0).abs() < 1e-9);
+
+        let middle = &root.children[0];
+        assert_eq!(middle.network.id, Uuid::from_u128(2));
+        assert!((middle.utilization - 100.0 / 254.0).abs() < 1e-9);
+
+        let leaf = &middle.children[0];
+        assert_eq!(leaf.network.id, Uuid::from_u128(3));
+        assert!(leaf.children.is_empty());
+        assert!((leaf.utilization - 100.0 / 62.0).abs() < 1e-9);
     }
 }
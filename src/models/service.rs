@@ -1,34 +1,39 @@
 use libipam::type_net::port::Port;
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Serialize, Debug, Deserialize, Clone)]
+#[derive(Serialize, Debug, Deserialize, Clone, ToSchema)]
 pub struct Service {
+    #[schema(value_type = u16)]
     pub port: Port,
+    #[schema(value_type = String)]
     pub ip: IpAddr,
     pub netwok_id: Uuid,
     pub service_id: Uuid,
-    pub description: String,
+    pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ServiceUpdate {
+    #[schema(value_type = Option<u16>)]
     pub port: Option<Port>,
+    #[schema(value_type = Option<String>)]
     pub ip: Option<IpAddr>,
     pub netwok_id: Option<Uuid>,
     pub service_id: Option<Uuid>,
     pub description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct Services {
     pub id: Uuid,
     pub name: String,
-    pub version: String,
+    pub version: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct ServicesUpdate {
     pub name: Option<String>,
     pub version: Option<String>,
@@ -1,6 +1,7 @@
 use super::*;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct User {
     pub id: uuid::Uuid,
     pub username: String,
@@ -15,7 +16,7 @@ struct UpdateUser {
     role: Option<Role>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, sqlx::Type)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, sqlx::Type, ToSchema)]
 pub enum Role {
     Admin,
     Guest,
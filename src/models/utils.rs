@@ -1,5 +1,8 @@
 use super::device::*;
-use super::{network::*, *};
+use super::{
+    alert::*, asset::*, dhcp::*, dns::*, location::*, mac::*, network::*, vlan::*, vrf::*,
+    webhook::*, *,
+};
 use ipnet::IpNet;
 use libipam::type_net::host_count::HostCount;
 use libipam::type_net::port::Port;
@@ -27,9 +30,16 @@ impl Table for Device {
             "ip",
             "description",
             "location",
+            "rack_unit",
+            "rack_height",
             "status",
             "network_id",
             "credential",
+            "hostname",
+            "fqdn",
+            "mac",
+            "interface_id",
+            "last_seen",
         ]
     }
 
@@ -37,219 +47,1038 @@ impl Table for Device {
         String::from("devices")
     }
 
-    fn query_insert() -> String {
-        format!("INSERT INTO {} (ip, network_id, description, location, status, credential) VALUES ($1, $2, $3, $4, $5, $6)", Self::name())
-    }
+    fn query_insert() -> String {
+        format!("INSERT INTO {} (ip, network_id, description, location, rack_unit, rack_height, status, credential, hostname, fqdn, mac, interface_id, last_seen) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)", Self::name())
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.ip.into(),
+            self.network_id.into(),
+            self.description.into(),
+            self.location.into(),
+            self.rack_unit.into(),
+            self.rack_height.into(),
+            self.status.into(),
+            self.credential.into(),
+            self.hostname.into(),
+            self.fqdn.into(),
+            self.mac.into(),
+            self.interface_id.into(),
+            self.last_seen.into(),
+        ]
+    }
+}
+
+impl Table for Network {
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "network",
+            "available",
+            "used",
+            "vlan",
+            "free",
+            "description",
+            "father",
+            "vrf_id",
+            "vlan_id",
+        ]
+    }
+
+    fn name() -> String {
+        String::from("networks")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, network, available, used, free, vlan, description, father, vrf_id, vlan_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.network.into(),
+            self.available.into(),
+            self.used.into(),
+            self.free.into(),
+            self.vlan.into(),
+            self.description.into(),
+            self.father.into(),
+            self.vrf.into(),
+            self.vlan_id.into(),
+        ]
+    }
+}
+
+impl Table for office::Office {
+    fn name() -> String {
+        String::from("offices")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, name, description, address) VALUES ($1, $2, $3, $4)",
+            office::Office::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.name.into(),
+            self.description.into(),
+            self.address.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "name", "description", "address"]
+    }
+}
+
+impl Table for Location {
+    fn name() -> String {
+        String::from("location")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, type, label, units, office_id) VALUES ($1, $2, $3, $4, $5)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.location_type.into(),
+            self.label.into(),
+            self.units.into(),
+            self.office_id.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "type", "label", "units", "office_id"]
+    }
+}
+
+impl Table for VlanEntry {
+    fn name() -> String {
+        String::from("vlans")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, vid, name, description, office_id, vlan_group) VALUES ($1, $2, $3, $4, $5, $6)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.vid.into(),
+            self.name.into(),
+            self.description.into(),
+            self.office_id.into(),
+            self.vlan_group.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "vid",
+            "name",
+            "description",
+            "office_id",
+            "vlan_group",
+        ]
+    }
+}
+
+impl Table for Vrf {
+    fn name() -> String {
+        String::from("vrfs")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, name, rd, description) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.name.into(),
+            self.rd.into(),
+            self.description.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "name", "rd", "description"]
+    }
+}
+
+impl Table for Asset {
+    fn name() -> String {
+        String::from("assets")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, name, description) VALUES ($1, $2, $3)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![self.id.into(), self.name.into(), self.description.into()]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "name", "description"]
+    }
+}
+
+impl Table for Interface {
+    fn name() -> String {
+        String::from("interfaces")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, asset_id, name, mac) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.asset_id.into(),
+            self.name.into(),
+            self.mac.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "asset_id", "name", "mac"]
+    }
+}
+
+impl Table for DhcpScope {
+    fn name() -> String {
+        String::from("dhcp_scopes")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (network_id, pools, gateway, dns_servers, lease_time, options, subnet_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        let network_id = self.network_id;
+        let subnet_id = self.subnet_id;
+        let mut resp = vec![network_id.into()];
+        resp.extend(
+            self.get_pair()
+                .map(|mut x| {
+                    ["pools", "gateway", "dns_servers", "lease_time", "options"]
+                        .iter()
+                        .filter_map(|key| x.remove(key))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default(),
+        );
+        resp.push(subnet_id.into());
+        resp
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "network_id",
+            "pools",
+            "gateway",
+            "dns_servers",
+            "lease_time",
+            "options",
+            "subnet_id",
+        ]
+    }
+}
+
+impl Table for DhcpReservation {
+    fn name() -> String {
+        String::from("dhcp_reservations")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (network_id, ip, mac, hostname) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.network_id.into(),
+            self.ip.into(),
+            self.mac.into(),
+            self.hostname.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["network_id", "ip", "mac", "hostname"]
+    }
+}
+
+impl Table for DnsDomain {
+    fn name() -> String {
+        String::from("dns_domains")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (network_id, domain, ttl, nameserver, hostmaster) VALUES ($1, $2, $3, $4, $5)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.network_id.into(),
+            self.domain.into(),
+            self.ttl.into(),
+            self.nameserver.into(),
+            self.hostmaster.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["network_id", "domain", "ttl", "nameserver", "hostmaster"]
+    }
+}
+
+impl Table for DnsZone {
+    fn name() -> String {
+        String::from("dns_zones")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (zone, vrf, serial, hash) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.zone.into(),
+            self.vrf.into(),
+            self.serial.into(),
+            self.hash.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["zone", "vrf", "serial", "hash"]
+    }
+}
+
+impl Table for DnsRecord {
+    fn name() -> String {
+        String::from("dns_records")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (zone, name, type, data, ttl) VALUES ($1, $2, $3, $4, $5)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.zone.into(),
+            self.name.into(),
+            self.kind.into(),
+            self.data.into(),
+            self.ttl.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["zone", "name", "type", "data", "ttl"]
+    }
+}
+
+impl Table for DnsUpdate {
+    fn name() -> String {
+        String::from("dns_updates")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, action, zone, name, type, data, ttl, attempts, next_attempt, error) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.action.into(),
+            self.record.zone.into(),
+            self.record.name.into(),
+            self.record.kind.into(),
+            self.record.data.into(),
+            self.record.ttl.into(),
+            self.attempts.into(),
+            self.next_attempt.into(),
+            self.error.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "action",
+            "zone",
+            "name",
+            "type",
+            "data",
+            "ttl",
+            "attempts",
+            "next_attempt",
+            "error",
+        ]
+    }
+}
+
+impl Table for Threshold {
+    fn name() -> String {
+        String::from("thresholds")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (network_id, warning, critical, level) VALUES ($1, $2, $3, $4)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.network_id.into(),
+            self.warning.into(),
+            self.critical.into(),
+            self.level.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["network_id", "warning", "critical", "level"]
+    }
+}
+
+impl Table for Webhook {
+    fn name() -> String {
+        String::from("webhooks")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, url, secret, events, description) VALUES ($1, $2, $3, $4, $5)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.url.into(),
+            self.secret.into(),
+            self.events.join(",").into(),
+            self.description.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "url", "secret", "events", "description"]
+    }
+}
+
+impl Table for Delivery {
+    fn name() -> String {
+        String::from("webhook_deliveries")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, webhook_id, event_id, event, payload, attempts, status, response, error) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.id.into(),
+            self.webhook_id.into(),
+            self.event_id.into(),
+            self.event.into(),
+            self.payload.into(),
+            self.attempts.into(),
+            self.status.into(),
+            self.response.into(),
+            self.error.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec![
+            "id",
+            "webhook_id",
+            "event_id",
+            "event",
+            "payload",
+            "attempts",
+            "status",
+            "response",
+            "error",
+            "created_at",
+        ]
+    }
+}
+
+impl Table for service::Services {
+    fn name() -> String {
+        String::from("services")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (id, name, version) VALUES ($1, $2, $3)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![self.id.into(), self.name.into(), self.version.into()]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "name", "version"]
+    }
+}
+
+impl Table for service::Service {
+    fn name() -> String {
+        String::from("service")
+    }
+
+    fn query_insert() -> String {
+        format!(
+            "INSERT INTO {} (port, ip, network_id, service_id, description) VALUES ($1, $2, $3, $4, $5)",
+            Self::name()
+        )
+    }
+
+    fn get_fields(self) -> Vec<TypeTable> {
+        vec![
+            self.port.into(),
+            self.ip.into(),
+            self.netwok_id.into(),
+            self.service_id.into(),
+            self.description.into(),
+        ]
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["port", "ip", "network_id", "service_id", "description"]
+    }
+}
+
+impl<'a> Updatable<'a> for UpdateDevice {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut pair = HashMap::new();
+
+        if let Some(tmp) = self.ip {
+            pair.insert("ip", tmp.into());
+        }
+
+        if let Some(tmp) = self.description {
+            pair.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.network_id {
+            pair.insert("network_id", tmp.into());
+        }
+
+        if let Some(tmp) = self.location {
+            pair.insert(
+                "location",
+                if tmp.is_nil() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.rack_unit {
+            pair.insert("rack_unit", if tmp == 0 { None } else { Some(tmp) }.into());
+        }
+
+        if let Some(tmp) = self.rack_height {
+            pair.insert(
+                "rack_height",
+                if tmp == 0 { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(cred) = self.credential {
+            pair.insert(
+                "credential",
+                if cred.password.is_empty() && cred.username.is_empty() {
+                    None
+                } else {
+                    Some(cred)
+                }
+                .into(),
+            );
+        }
+
+        if let Some(tmp) = self.hostname {
+            pair.insert(
+                "hostname",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.fqdn {
+            pair.insert("fqdn", if tmp.is_empty() { None } else { Some(tmp) }.into());
+        }
+
+        if let Some(tmp) = self.mac {
+            pair.insert("mac", if tmp.is_empty() { None } else { Some(tmp) }.into());
+        }
+
+        if let Some(tmp) = self.interface_id {
+            pair.insert(
+                "interface_id",
+                if tmp.is_nil() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if !pair.is_empty() {
+            Some(pair)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for UpdateDeviceLease {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut pair = HashMap::new();
+
+        if let Some(tmp) = self.status {
+            pair.insert("status", tmp.into());
+        }
+
+        if let Some(tmp) = self.last_seen {
+            pair.insert("last_seen", tmp.into());
+        }
+
+        if let Some(tmp) = self.mac {
+            pair.insert("mac", tmp.into());
+        }
+
+        if let Some(tmp) = self.hostname {
+            pair.insert("hostname", tmp.into());
+        }
+
+        if !pair.is_empty() {
+            Some(pair)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for Status {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([("status", self.into())]))
+    }
+}
+
+impl<'a> Updatable<'a> for UpdateNetwork {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut pair = HashMap::new();
+
+        if let Some(tmp) = self.description {
+            pair.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.network {
+            pair.insert("network", tmp.into());
+        }
+
+        if let Some(vlan) = self.vlan {
+            pair.insert("vlan", Some(vlan).into());
+            pair.insert("vlan_id", self.vlan_id.into());
+        }
+
+        if !pair.is_empty() {
+            Some(pair)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for office::UpdateOffice {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
+
+        if let Some(tmp) = self.address {
+            resp.insert(
+                "address",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.description {
+            resp.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for UpdateLocation {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.location_type {
+            resp.insert("type", tmp.into());
+        }
+
+        if let Some(tmp) = self.label {
+            resp.insert("label", tmp.into());
+        }
+
+        if let Some(tmp) = self.units {
+            resp.insert("units", if tmp == 0 { None } else { Some(tmp) }.into());
+        }
+
+        if let Some(tmp) = self.office_id {
+            resp.insert(
+                "office_id",
+                if tmp.is_nil() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Updatable<'a> for UpdateVlanEntry {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.vid {
+            resp.insert("vid", tmp.into());
+        }
+
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
+
+        if let Some(tmp) = self.description {
+            resp.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.office_id {
+            resp.insert(
+                "office_id",
+                if tmp.is_nil() { None } else { Some(tmp) }.into(),
+            );
+        }
+
+        if let Some(tmp) = self.vlan_group {
+            resp.insert(
+                "vlan_group",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
 
-    fn get_fields(self) -> Vec<TypeTable> {
-        vec![
-            self.ip.into(),
-            self.network_id.into(),
-            self.description.into(),
-            self.location.into(),
-            self.status.into(),
-            self.credential.into(),
-        ]
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
 }
 
-impl Table for Network {
-    fn columns() -> Vec<&'static str> {
-        vec![
-            "id",
-            "network",
-            "available",
-            "used",
-            "vlan",
-            "free",
-            "description",
-            "father",
-        ]
-    }
+impl<'a> Updatable<'a> for UpdateVrf {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
 
-    fn name() -> String {
-        String::from("networks")
-    }
+        if let Some(tmp) = self.rd {
+            resp.insert("rd", if tmp.is_empty() { None } else { Some(tmp) }.into());
+        }
 
-    fn query_insert() -> String {
-        format!(
-            "INSERT INTO {} (id, network, available, used, free, vlan, description, father) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-            Self::name()
-        )
-    }
+        if let Some(tmp) = self.description {
+            resp.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
 
-    fn get_fields(self) -> Vec<TypeTable> {
-        vec![
-            self.id.into(),
-            self.network.into(),
-            self.available.into(),
-            self.used.into(),
-            self.free.into(),
-            self.vlan.into(),
-            self.description.into(),
-            self.father.into(),
-        ]
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
 }
 
-impl Table for office::Office {
-    fn name() -> String {
-        String::from("offices")
-    }
+impl<'a> Updatable<'a> for UpdateAsset {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
 
-    fn query_insert() -> String {
-        format!(
-            "INSERT INTO {} (id, description, address) VALUES ($1, $2, $3)",
-            office::Office::name()
-        )
-    }
+        if let Some(tmp) = self.description {
+            resp.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
+        }
 
-    fn get_fields(self) -> Vec<TypeTable> {
-        vec![self.id.into(), self.description.into(), self.address.into()]
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
+}
 
-    fn columns() -> Vec<&'static str> {
-        vec!["id", "description", "address"]
+impl<'a> Updatable<'a> for UpdateInterface {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.name {
+            resp.insert("name", tmp.into());
+        }
+
+        if let Some(tmp) = self.mac {
+            resp.insert("mac", if tmp.is_empty() { None } else { Some(tmp) }.into());
+        }
+
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
 }
 
-impl Table for service::Services {
-    fn name() -> String {
-        String::from("services")
-    }
+impl<'a> Updatable<'a> for UpdateThreshold {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.warning {
+            resp.insert("warning", tmp.into());
+        }
 
-    fn query_insert() -> String {
-        format!(
-            "INSERT INTO {} (id, name, version) VALUES ($1, $2, $3)",
-            Self::name()
-        )
-    }
+        if let Some(tmp) = self.critical {
+            resp.insert("critical", tmp.into());
+        }
 
-    fn get_fields(self) -> Vec<TypeTable> {
-        vec![self.id.into(), self.name.into(), self.version.into()]
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
+}
 
-    fn columns() -> Vec<&'static str> {
-        vec!["id", "name", "version"]
+/// Every field is replaced, the scope is set as a whole
+impl<'a> Updatable<'a> for DhcpScope {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        Some(HashMap::from([
+            (
+                "pools",
+                serde_json::to_string(&self.pools)
+                    .unwrap_or_default()
+                    .into(),
+            ),
+            ("gateway", self.gateway.map(|x| x.to_string()).into()),
+            (
+                "dns_servers",
+                self.dns_servers
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    .into(),
+            ),
+            ("lease_time", self.lease_time.into()),
+            (
+                "options",
+                serde_json::to_string(&self.options)
+                    .unwrap_or_default()
+                    .into(),
+            ),
+        ]))
     }
 }
 
-impl Table for service::Service {
-    fn name() -> String {
-        String::from("services")
-    }
+impl<'a> Updatable<'a> for UpdateDnsDomain {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.domain {
+            resp.insert("domain", tmp.into());
+        }
 
-    fn query_insert() -> String {
-        format!(
-            "INSERT INTO {} (port, ip, network_id, service_id, descripcion) VALUES ($1, $2, $3, $4, $5)",
-            Self::name()
-        )
-    }
+        if let Some(tmp) = self.ttl {
+            resp.insert("ttl", tmp.into());
+        }
 
-    fn get_fields(self) -> Vec<TypeTable> {
-        vec![
-            self.port.into(),
-            self.ip.into(),
-            self.netwok_id.into(),
-            self.service_id.into(),
-            self.description.into(),
-        ]
-    }
+        if let Some(tmp) = self.nameserver {
+            resp.insert("nameserver", tmp.into());
+        }
 
-    fn columns() -> Vec<&'static str> {
-        vec!["port", "ip", "network_id", "service_id", "description"]
+        if let Some(tmp) = self.hostmaster {
+            resp.insert("hostmaster", tmp.into());
+        }
+
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
+        }
     }
 }
 
-impl<'a> Updatable<'a> for UpdateDevice {
+impl<'a> Updatable<'a> for UpdateDnsZone {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
-        let mut pair = HashMap::new();
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.serial {
+            resp.insert("serial", tmp.into());
+        }
 
-        if let Some(tmp) = self.ip {
-            pair.insert("ip", tmp.into());
+        if let Some(tmp) = self.hash {
+            resp.insert("hash", tmp.into());
         }
 
-        if let Some(tmp) = self.description {
-            pair.insert(
-                "description",
-                if tmp.is_empty() { None } else { Some(tmp) }.into(),
-            );
+        if !resp.is_empty() {
+            Some(resp)
+        } else {
+            None
         }
+    }
+}
 
-        if let Some(tmp) = self.network_id {
-            pair.insert("network_id", tmp.into());
+impl<'a> Updatable<'a> for UpdateDnsUpdate {
+    fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.attempts {
+            resp.insert("attempts", tmp.into());
         }
 
-        if let Some(tmp) = self.location {
-            let tmp = if tmp.is_empty() { None } else { Some(tmp) };
-            pair.insert("rack", tmp.into());
+        if let Some(tmp) = self.next_attempt {
+            resp.insert("next_attempt", tmp.into());
         }
 
-        if let Some(cred) = self.credential {
-            pair.insert(
-                "credential",
-                if cred.password.is_empty() && cred.username.is_empty() {
-                    None
-                } else {
-                    Some(cred)
-                }
-                .into(),
-            );
+        if let Some(tmp) = self.error {
+            resp.insert("error", Some(tmp).into());
         }
 
-        if !pair.is_empty() {
-            Some(pair)
+        if !resp.is_empty() {
+            Some(resp)
         } else {
             None
         }
     }
 }
 
-impl<'a> Updatable<'a> for Status {
+impl<'a> Updatable<'a> for AlertLevel {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
-        Some(HashMap::from([("status", self.into())]))
+        Some(HashMap::from([("level", self.into())]))
     }
 }
 
-impl<'a> Updatable<'a> for UpdateNetwork {
+impl<'a> Updatable<'a> for UpdateWebhook {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
-        let mut pair = HashMap::new();
+        let mut resp = HashMap::new();
+        if let Some(tmp) = self.url {
+            resp.insert("url", tmp.into());
+        }
 
-        if let Some(tmp) = self.description {
-            pair.insert(
-                "description",
-                if tmp.is_empty() { None } else { Some(tmp) }.into(),
-            );
+        if let Some(tmp) = self.secret {
+            resp.insert("secret", tmp.into());
         }
 
-        if let Some(tmp) = self.network {
-            pair.insert("network", tmp.into());
+        if let Some(tmp) = self.events {
+            resp.insert("events", tmp.join(",").into());
         }
 
-        if let Some(vlan) = self.vlan {
-            pair.insert("vlan", Some(vlan).into());
+        if let Some(tmp) = self.description {
+            resp.insert(
+                "description",
+                if tmp.is_empty() { None } else { Some(tmp) }.into(),
+            );
         }
 
-        if !pair.is_empty() {
-            Some(pair)
+        if !resp.is_empty() {
+            Some(resp)
         } else {
             None
         }
     }
 }
 
-impl<'a> Updatable<'a> for office::UpdateOffice {
+impl<'a> Updatable<'a> for UpdateDelivery {
     fn get_pair(self) -> Option<HashMap<&'a str, TypeTable>> {
-        let mut resp = HashMap::new();
-        if let Some(tmp) = self.address {
-            resp.insert("address", tmp.into());
-        }
-
-        if let Some(tmp) = self.description {
-            resp.insert("description", tmp.into());
-        }
-
-        Some(resp)
+        Some(HashMap::from([
+            ("attempts", self.attempts.into()),
+            ("status", self.status.into()),
+            ("response", self.response.into()),
+            ("error", self.error.into()),
+        ]))
     }
 }
 
@@ -326,12 +1155,32 @@ pub enum TypeTable {
     Status(device::Status),
     U32(u32),
     Role(user::Role),
+    LocationType(LocationType),
+    AlertLevel(AlertLevel),
+    DeliveryStatus(DeliveryStatus),
+    DnsAction(DnsAction),
     OptionU16(Option<u16>),
     BytesOption(Option<Vec<u8>>),
     U16(u16),
+    I64(i64),
+    OptionI64(Option<i64>),
     Null,
 }
 
+impl TypeTable {
+    pub fn is_null(&self) -> bool {
+        matches!(
+            self,
+            Self::Null
+                | Self::OptionUuid(None)
+                | Self::OptionString(None)
+                | Self::OptionU16(None)
+                | Self::OptionI64(None)
+                | Self::BytesOption(None)
+        )
+    }
+}
+
 impl From<Port> for TypeTable {
     fn from(value: Port) -> Self {
         Self::U16(*value)
@@ -344,12 +1193,30 @@ impl From<Option<Credential>> for TypeTable {
     }
 }
 
+impl From<Vlan> for TypeTable {
+    fn from(value: Vlan) -> Self {
+        Self::U16(*value)
+    }
+}
+
 impl From<Option<Vlan>> for TypeTable {
     fn from(value: Option<Vlan>) -> Self {
         Self::OptionU16(value.map(|vlan| *vlan))
     }
 }
 
+impl From<u16> for TypeTable {
+    fn from(value: u16) -> Self {
+        Self::U16(value)
+    }
+}
+
+impl From<Option<u16>> for TypeTable {
+    fn from(value: Option<u16>) -> Self {
+        Self::OptionU16(value)
+    }
+}
+
 impl From<Uuid> for TypeTable {
     fn from(value: Uuid) -> Self {
         TypeTable::Uuid(value)
@@ -362,6 +1229,42 @@ impl From<user::Role> for TypeTable {
     }
 }
 
+impl From<AlertLevel> for TypeTable {
+    fn from(value: AlertLevel) -> Self {
+        Self::AlertLevel(value)
+    }
+}
+
+impl From<DnsAction> for TypeTable {
+    fn from(value: DnsAction) -> Self {
+        Self::DnsAction(value)
+    }
+}
+
+impl From<i64> for TypeTable {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<Option<i64>> for TypeTable {
+    fn from(value: Option<i64>) -> Self {
+        Self::OptionI64(value)
+    }
+}
+
+impl From<DeliveryStatus> for TypeTable {
+    fn from(value: DeliveryStatus) -> Self {
+        Self::DeliveryStatus(value)
+    }
+}
+
+impl From<LocationType> for TypeTable {
+    fn from(value: LocationType) -> Self {
+        Self::LocationType(value)
+    }
+}
+
 impl From<Option<Uuid>> for TypeTable {
     fn from(value: Option<Uuid>) -> Self {
         Self::OptionUuid(value)
@@ -374,12 +1277,30 @@ impl From<IpAddr> for TypeTable {
     }
 }
 
+impl From<MacAddr> for TypeTable {
+    fn from(value: MacAddr) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<Option<MacAddr>> for TypeTable {
+    fn from(value: Option<MacAddr>) -> Self {
+        Self::OptionString(value.map(|x| x.to_string()))
+    }
+}
+
 impl From<IpNet> for TypeTable {
     fn from(value: IpNet) -> Self {
         Self::String(value.to_string())
     }
 }
 
+impl From<u32> for TypeTable {
+    fn from(value: u32) -> Self {
+        Self::U32(value)
+    }
+}
+
 impl From<HostCount> for TypeTable {
     fn from(value: HostCount) -> Self {
         Self::U32(*value)
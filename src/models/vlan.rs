@@ -0,0 +1,33 @@
+use super::network::Vlan;
+use super::*;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct VlanEntry {
+    pub id: Uuid,
+    pub vid: Vlan,
+    pub name: String,
+    pub description: Option<String>,
+    pub office_id: Option<Uuid>,
+    pub vlan_group: Option<String>,
+}
+
+impl VlanEntry {
+    /// The entry a bare vid refers to, only when no other scope has the same vid
+    pub fn by_vid(entries: &[Self], vid: Vlan) -> Option<&Self> {
+        let mut found = entries.iter().filter(|x| x.vid == vid);
+        match (found.next(), found.next()) {
+            (Some(entry), None) => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateVlanEntry {
+    pub vid: Option<Vlan>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub office_id: Option<Uuid>,
+    pub vlan_group: Option<String>,
+}
@@ -0,0 +1,17 @@
+use super::*;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Vrf {
+    pub id: Uuid,
+    pub name: String,
+    pub rd: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateVrf {
+    pub name: Option<String>,
+    pub rd: Option<String>,
+    pub description: Option<String>,
+}
@@ -0,0 +1,55 @@
+use super::*;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl Webhook {
+    pub fn accepts(&self, event: &str) -> bool {
+        crate::events::matches(&self.events, event)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, ToSchema)]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct Delivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event: String,
+    pub payload: String,
+    pub attempts: u16,
+    pub status: DeliveryStatus,
+    pub response: Option<u16>,
+    pub error: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug)]
+pub struct UpdateDelivery {
+    pub attempts: u16,
+    pub status: DeliveryStatus,
+    pub response: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
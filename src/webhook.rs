@@ -0,0 +1,226 @@
+use crate::database::{repository::Repository, SqliteRepository};
+use crate::events::{self, Event};
+use crate::models::webhook::{Delivery, DeliveryStatus, UpdateDelivery, Webhook};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tokio::sync::{broadcast::error::RecvError, Mutex};
+
+const DEFAULT_MAX_ATTEMPTS: u16 = 5;
+const DEFAULT_RETRY_DELAY: u64 = 10;
+const TIMEOUT: u64 = 10;
+
+pub const SIGNATURE_HEADER: &str = "X-Ipam-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Ipam-Timestamp";
+pub const EVENT_HEADER: &str = "X-Ipam-Event";
+pub const DELIVERY_HEADER: &str = "X-Ipam-Delivery";
+
+type RepositoryType = Arc<Mutex<SqliteRepository>>;
+
+/// Queues a delivery for every webhook interested in each event, and resumes the
+/// deliveries that were pending when the server stopped
+pub fn spawn(db: RepositoryType) {
+    let mut rx = events::subscribe();
+
+    tokio::spawn(async move {
+        let pending = db
+            .lock()
+            .await
+            .get::<Delivery>(Some(HashMap::from([(
+                "status",
+                DeliveryStatus::Pending.into(),
+            )])))
+            .await
+            .unwrap_or_default();
+
+        for delivery in pending {
+            tokio::spawn(deliver(db.clone(), delivery));
+        }
+
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let deliveries = {
+                        let state = db.lock().await;
+                        enqueue(&state, &event).await
+                    };
+
+                    for delivery in deliveries {
+                        tokio::spawn(deliver(db.clone(), delivery));
+                    }
+                }
+                Err(RecvError::Lagged(e)) => tracing::warn!("Webhooks lost {} events", e),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+async fn enqueue(state: &SqliteRepository, event: &Event) -> Vec<Delivery> {
+    let webhooks = state
+        .get::<Webhook>(None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|x| x.accepts(event.event.as_str()))
+        .collect::<Vec<_>>();
+
+    if webhooks.is_empty() {
+        return Vec::new();
+    }
+
+    let payload = match serde_json::to_string(event) {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::error!("Event {}: {}", event.id, e);
+            return Vec::new();
+        }
+    };
+
+    let deliveries = webhooks
+        .into_iter()
+        .map(|x| Delivery {
+            id: uuid::Uuid::new_v4(),
+            webhook_id: x.id,
+            event_id: event.id,
+            event: event.event.to_string(),
+            payload: payload.clone(),
+            attempts: 0,
+            status: DeliveryStatus::Pending,
+            response: None,
+            error: None,
+            created_at: event.timestamp,
+        })
+        .collect::<Vec<_>>();
+
+    match state.insert::<Delivery>(deliveries.clone()).await {
+        Ok(_) => deliveries,
+        Err(e) => {
+            tracing::error!("Webhook deliveries of the event {}: {}", event.id, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Sends the delivery until it's accepted or it runs out of attempts, waiting
+/// `WEBHOOK_RETRY_DELAY * 2^n` seconds between them
+pub async fn deliver(db: RepositoryType, mut delivery: Delivery) {
+    let max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|x| x.parse::<u16>().ok())
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+        .max(1);
+    let delay = env::var("WEBHOOK_RETRY_DELAY")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_RETRY_DELAY);
+
+    let Ok(mut webhook) = db
+        .lock()
+        .await
+        .get::<Webhook>(Some(HashMap::from([("id", delivery.webhook_id.into())])))
+        .await
+    else {
+        return;
+    };
+    let webhook = webhook.remove(0);
+
+    while delivery.status == DeliveryStatus::Pending {
+        delivery.attempts += 1;
+
+        match send(&webhook, &delivery).await {
+            Ok(status) => {
+                delivery.status = DeliveryStatus::Delivered;
+                delivery.response = Some(status);
+                delivery.error = None;
+            }
+            Err((status, e)) => {
+                tracing::warn!(
+                    "Webhook {} delivery {} attempt {}: {}",
+                    webhook.url,
+                    delivery.id,
+                    delivery.attempts,
+                    e
+                );
+                if delivery.attempts >= max_attempts {
+                    delivery.status = DeliveryStatus::Failed;
+                }
+                delivery.response = status;
+                delivery.error = Some(e);
+            }
+        }
+
+        let updater = UpdateDelivery {
+            attempts: delivery.attempts,
+            status: delivery.status,
+            response: delivery.response,
+            error: delivery.error.clone(),
+        };
+        if let Err(e) = db
+            .lock()
+            .await
+            .update::<Delivery, _>(updater, Some(HashMap::from([("id", delivery.id.into())])))
+            .await
+        {
+            tracing::error!("Webhook delivery {}: {}", delivery.id, e);
+            return;
+        }
+
+        if delivery.status == DeliveryStatus::Pending {
+            let backoff = delay.saturating_mul(1 << (delivery.attempts - 1).min(10));
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+        }
+    }
+}
+
+async fn send(webhook: &Webhook, delivery: &Delivery) -> Result<u16, (Option<u16>, String)> {
+    let timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
+    let resp = client()
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            format!(
+                "sha256={}",
+                sign(&webhook.secret, timestamp, &delivery.payload)
+            ),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = resp.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err((Some(status.as_u16()), format!("Response status {}", status)))
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `{timestamp}.{body}` with the secret of the webhook, the
+/// timestamp of the attempt goes in `X-Ipam-Timestamp` so the receivers can reject replays
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(TIMEOUT))
+            .build()
+            .unwrap_or_default()
+    })
+}
@@ -72,7 +72,7 @@ const reserve_ip = (event) => {
             const id = path[path.length-1];
             input_location.value = id;
         } else {
-            input_location = 'empty';
+            input_location.value = '';
         }
 
         checkbox_location_allow_cange.addEventListener('change', (tg) => {
@@ -102,8 +102,11 @@ const reserve_ip = (event) => {
                 send.description = input_description.value;
             }
 
-            if (checkbox_location_allow_cange.checked && location.textContent != input_location.value) {
-                send.rack = input_location.value;
+            if (checkbox_location_allow_cange.checked) {
+                const path = location.href ? location.href.split("/") : [''];
+                if (path[path.length-1] != input_location.value) {
+                    send.location = input_location.value || '00000000-0000-0000-0000-000000000000';
+                }
             }
 
             if (pass.textContent != input_pass.value || user.textContent != input_user.value) {
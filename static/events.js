@@ -0,0 +1,127 @@
+// Keeps the network and device pages up to date with the changes made by other users
+
+const network_id = document.getElementById("network_id")?.textContent.trim() || null;
+const table_main = document.getElementById("table_main");
+
+const SVG_STATUS = {
+    Reserved: "svg-reserve",
+    Online: "svg-online",
+    Offline: "svg-offline",
+    Unknown: "svg-unknown",
+};
+
+const TEXT_STATUS = {
+    Online: "text-online",
+    Offline: "text-danger",
+};
+
+let pending_reload = false;
+
+// Reload the page, but never under the user while a modal is open
+const reload = () => {
+    const modal = document.querySelector(".modal.show");
+    if (!modal) {
+        location.reload();
+        return;
+    }
+
+    if (!pending_reload) {
+        pending_reload = true;
+        modal.addEventListener("hidden.bs.modal", () => location.reload(), { once: true });
+    }
+}
+
+const update_row = (network) => {
+    if (!table_main) { return false; }
+
+    const row = [...table_main.querySelectorAll("tbody tr")]
+        .find(tr => tr.querySelector("[data-name='id']")?.textContent.trim() === network.id);
+
+    if (!row) { return false; }
+
+    for (const key of ["used", "free", "available", "description"]) {
+        const cell = row.querySelector(`[data-name='${key}']`);
+        if (cell) {
+            cell.textContent = network[key] ?? "";
+        }
+    }
+
+    const vlan = row.querySelector("[data-name='vlan']");
+    if (vlan && vlan.textContent.trim() !== String(network.vlan ?? "")) {
+        // The vlan badge is rendered by the server
+        reload();
+    }
+
+    return true;
+}
+
+const update_network = (network) => {
+    if (network.id === network_id) {
+        document.getElementById("network_used").textContent = network.used;
+        document.getElementById("network_free").textContent = network.free;
+    } else if (!update_row(network) && network.father === network_id) {
+        reload();
+    }
+}
+
+const update_device = (device) => {
+    const ip_ = device.ip.replaceAll(".", "_");
+    const svg = document.getElementById(`svg_${ip_}`);
+
+    if (!svg) {
+        reload();
+        return;
+    }
+    svg.classList = SVG_STATUS[device.status] || SVG_STATUS.Unknown;
+
+    const trigger = svg.closest("[data-bs-toggle='popover']");
+    const content = document.createElement("template");
+    content.innerHTML = trigger.getAttribute("data-bs-content");
+
+    const status = content.content.querySelector("#data_status");
+    status.textContent = device.status;
+    status.classList.remove(...Object.values(TEXT_STATUS));
+    if (TEXT_STATUS[device.status]) {
+        status.classList.add(TEXT_STATUS[device.status]);
+    }
+    content.content.querySelector("#description").textContent = device.description || "empty";
+
+    const reserve = content.content.querySelector("#to_reserve");
+    if (reserve && device.status !== "Unknown") {
+        reserve.remove();
+    }
+
+    trigger.setAttribute("data-bs-content", content.innerHTML);
+    bootstrap.Popover.getInstance(trigger)?.setContent({ ".popover-body": content.innerHTML });
+}
+
+const url = network_id ? `/api/v1/events?network_id=${network_id}` : "/api/v1/events?events=network.*";
+const source = new EventSource(url);
+
+source.addEventListener("network.updated", (event) => {
+    update_network(JSON.parse(event.data).data);
+});
+
+for (const name of ["network.created", "network.deleted"]) {
+    source.addEventListener(name, (event) => {
+        const network = JSON.parse(event.data).data;
+
+        if (network.id === network_id) {
+            location.href = "/";
+        } else if (network_id || !network.father) {
+            reload();
+        }
+    });
+}
+
+for (const name of ["device.updated", "device.status_changed"]) {
+    source.addEventListener(name, (event) => {
+        update_device(JSON.parse(event.data).data);
+    });
+}
+
+for (const name of ["device.created", "device.deleted"]) {
+    source.addEventListener(name, reload);
+}
+
+source.addEventListener("resync", reload);
@@ -0,0 +1,87 @@
+const form = document.getElementById("search_form");
+const results = document.getElementById("search_results");
+
+const hit_link = (hit) => {
+    const data = hit.data;
+    switch (hit.type) {
+        case "network":
+            return {href: `/${data.id}`, text: data.network, detail: data.description};
+        case "device":
+            return {href: `/${data.network_id}#device_${data.ip.replaceAll('.','_')}`, text: data.ip, detail: data.description};
+        case "service":
+            return {href: `/service?ip=${data.ip}&network_id=${data.netwok_id}&port=${data.port}`, text: `${data.ip}:${data.port}`, detail: data.description};
+        case "services":
+            return {href: "/services", text: data.name, detail: data.version};
+        case "vlan":
+            return {href: `/?vlan=${encodeURIComponent(data.name)}`, text: `${data.vid} - ${data.name}`, detail: data.description};
+        default:
+            return {href: "#", text: hit.type, detail: null};
+    }
+}
+
+const render = (hits) => {
+    results.innerHTML = "";
+    results.classList.remove("d-none");
+
+    const list = document.createElement("ul");
+    list.classList = "list-group";
+
+    if (hits.length == 0) {
+        const li = document.createElement("li");
+        li.classList = "list-group-item";
+        li.textContent = "No results";
+        list.appendChild(li);
+    }
+
+    for (const hit of hits) {
+        const {href, text, detail} = hit_link(hit);
+        const li = document.createElement("li");
+        li.classList = "list-group-item d-flex align-items-center";
+
+        const badge = document.createElement("span");
+        badge.classList = "badge text-bg-secondary me-2";
+        badge.textContent = hit.type;
+        li.appendChild(badge);
+
+        const a = document.createElement("a");
+        a.href = href;
+        a.classList = "link-dark link-offset-2 link-underline-opacity-25 link-underline-opacity-100-hover fw-bold";
+        a.textContent = text;
+        li.appendChild(a);
+
+        if (detail) {
+            const span = document.createElement("span");
+            span.classList = "ms-2 text-secondary";
+            span.textContent = detail;
+            li.appendChild(span);
+        }
+
+        if (hit.vrf) {
+            const vrf = document.createElement("span");
+            vrf.classList = "badge text-bg-light ms-auto";
+            vrf.textContent = `vrf ${hit.vrf}`;
+            li.appendChild(vrf);
+        }
+        list.appendChild(li);
+    }
+    results.appendChild(list);
+}
+
+if (form) {
+    form.addEventListener("submit", async (event) => {
+        event.preventDefault();
+        const q = form.querySelector("[name='q']").value.trim();
+
+        if (!q) {
+            results.classList.add("d-none");
+            results.innerHTML = "";
+            return;
+        }
+
+        const resp = await fetch(`/api/v1/search?q=${encodeURIComponent(q)}`);
+        if (resp.ok) {
+            const json = await resp.json();
+            render(json.data);
+        }
+    });
+}